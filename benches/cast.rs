@@ -16,6 +16,19 @@ use raytracer::plane::Plane;
 use raytracer::texture::Texture;
 use raytracer::matrix::Matrix;
 use raytracer::object::{ObjectEnum};
+use raytracer::render_settings::RenderSettings;
+use raytracer::world::World;
+
+// Fixed settings, so that results stay comparable between runs
+const BENCH_SETTINGS: RenderSettings = RenderSettings {
+    resolution_x: 256,
+    resolution_y: 256,
+    pixel_samples: 1,
+    sample_count: 16,
+    bounces: 2,
+    threads: 64,
+    preview_mode: false,
+};
 
 const TEST_RAY_1: Ray = Ray {
     origin: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
@@ -25,10 +38,10 @@ const TEST_RAY_1: Ray = Ray {
 pub fn cast_ray_1(c: &mut Criterion) {
     let mut meta_rng = thread_rng();
     let mut rng = SmallRng::from_rng(&mut meta_rng).unwrap();
-    let objs = construct_room_scene();
+    let world = World::new(construct_room_scene());
 
-    c.bench_function("cast_ray_1([TEST_RAY_1], [objs], [rng], 3)", |b| 
-        b.iter(|| raytracer::cast::cast_ray(black_box(&TEST_RAY_1), &objs, &mut rng, black_box(3))));
+    c.bench_function("cast_ray_1([TEST_RAY_1], [world], [rng], 3)", |b| 
+        b.iter(|| raytracer::cast::cast_ray(black_box(&TEST_RAY_1), &world, &BENCH_SETTINGS, &mut rng, black_box(3))));
 }
 
 
@@ -40,10 +53,10 @@ const TEST_RAY_2: Ray = Ray {
 pub fn cast_ray_2(c: &mut Criterion) {
     let mut meta_rng = thread_rng();
     let mut rng = SmallRng::from_rng(&mut meta_rng).unwrap();
-    let objs = construct_room_scene();
+    let world = World::new(construct_room_scene());
 
-    c.bench_function("cast_ray_2([TEST_RAY_2], [objs], [rng], 3)", |b| 
-        b.iter(|| raytracer::cast::cast_ray(black_box(&TEST_RAY_2), &objs, &mut rng, black_box(3))));
+    c.bench_function("cast_ray_2([TEST_RAY_2], [world], [rng], 3)", |b| 
+        b.iter(|| raytracer::cast::cast_ray(black_box(&TEST_RAY_2), &world, &BENCH_SETTINGS, &mut rng, black_box(3))));
 }
 
 
@@ -55,10 +68,10 @@ const TEST_RAY_3: Ray = Ray {
 pub fn cast_ray_3(c: &mut Criterion) {
     let mut meta_rng = thread_rng();
     let mut rng = SmallRng::from_rng(&mut meta_rng).unwrap();
-    let objs = construct_room_scene();
+    let world = World::new(construct_room_scene());
 
-    c.bench_function("cast_ray_3([TEST_RAY_3], [objs], [rng], 3)", |b| 
-        b.iter(|| raytracer::cast::cast_ray(black_box(&TEST_RAY_3), &objs, &mut rng, black_box(3))));
+    c.bench_function("cast_ray_3([TEST_RAY_3], [world], [rng], 3)", |b| 
+        b.iter(|| raytracer::cast::cast_ray(black_box(&TEST_RAY_3), &world, &BENCH_SETTINGS, &mut rng, black_box(3))));
 }
 
 
@@ -75,6 +88,8 @@ fn construct_room_scene() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: Some(Texture::Solid(Color(0.0, 1.0, 1.0))),
             texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -87,17 +102,19 @@ fn construct_room_scene() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
     // mesh
     objs.push(ObjectEnum::Mesh(Mesh::from_obj(
-        "/Users/brundolf/git/raytracer/test.obj", 
+        concat!(env!("CARGO_MANIFEST_DIR"), "/test.obj"), 
         &(Matrix::translation(&Vec3 { x: 0.0, y: -3.0, z: -10.0 })
         * Matrix::rotation_y(std::f32::consts::PI)
         * Matrix::scale(&Vec3::from_scalar(0.5))),
         None
-    )));
+    ).unwrap()));
 
     // ceiling
     objs.push(ObjectEnum::Plane(Plane::new(
@@ -110,6 +127,8 @@ fn construct_room_scene() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: Some(Texture::Solid(Color(1.0, 0.95, 0.8))),
             texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -119,11 +138,13 @@ fn construct_room_scene() -> Vec<ObjectEnum> {
         Vec3 { x: 0.0, y: 1.0, z: 0.0 },
         Vec3 { x: 0.0, y: 0.0, z: -1.0 },
         Material {
-            texture_albedo: Some(Texture::from_image("/Users/brundolf/git/raytracer/texture.jpg")),
+            texture_albedo: Some(Texture::from_image(concat!(env!("CARGO_MANIFEST_DIR"), "/texture.jpg")).unwrap()),
             texture_specular: None,
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -139,6 +160,8 @@ fn construct_room_scene() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -153,6 +176,8 @@ fn construct_room_scene() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -167,6 +192,8 @@ fn construct_room_scene() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -181,6 +208,8 @@ fn construct_room_scene() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
     
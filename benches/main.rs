@@ -1,23 +1,3 @@
-
-/*
- * NOTE: Always run with the following settings for consistent results:
- * 
- * 
-    // Image resolution, in pixels.
-    pub const RESOLUTION: usize = 256;
-
-    // Number of sample rays to cast for diffuse/specular illumination
-    pub const SAMPLE_COUNT: usize = 16;
-
-    // Max number of indirect bounces to make
-    pub const BOUNCES: u8 = 2;
-
-    // How many chunks the image should be split up into, for multithreading
-    pub const CELLS: usize = 64; // must be the square of an integer
-
-    pub const PREVIEW_MODE: bool = false;
- */
-
 use criterion::{criterion_group, criterion_main};
 
 mod cast;
@@ -26,6 +6,14 @@ criterion_group!(benches_cast,
     cast::cast_ray_2, 
     cast::cast_ray_3);
 
+mod mesh;
+criterion_group!(benches_mesh, 
+    mesh::intersection_bvh, 
+    mesh::intersection_brute_force,
+    mesh::parse_obj,
+    mesh::load_obj,
+    mesh::load_obj_cached);
+
 mod ray;
 criterion_group!(benches_ray, 
     ray::random_direction);
@@ -38,4 +26,9 @@ criterion_group!(benches_vec3,
     vec3::projected_on, 
     vec3::rotated_around);
 
-criterion_main!(benches_cast, benches_ray, benches_vec3);
+mod world;
+criterion_group!(benches_world, 
+    world::nearest_intersection, 
+    world::brute_force_intersection);
+
+criterion_main!(benches_cast, benches_mesh, benches_ray, benches_vec3, benches_world);
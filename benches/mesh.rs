@@ -0,0 +1,61 @@
+
+use std::fs;
+
+use criterion::{black_box, Criterion};
+
+use raytracer::ray::Ray;
+use raytracer::vec3::Vec3;
+use raytracer::mesh::{Mesh};
+use raytracer::matrix::Matrix;
+use raytracer::object::Object;
+use raytracer::obj_parser::ObjReader;
+
+const TREE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tree.obj");
+
+fn load_tree() -> (Mesh, Ray) {
+    let mesh = Mesh::from_obj(TREE_PATH, &Matrix::new(), None).unwrap();
+
+    // from off to the side, straight through the middle of the tree
+    let bounds = mesh.bounds().unwrap();
+    let center = bounds.centroid();
+    let origin = &center + &Vec3 { x: 0.0, y: 0.0, z: bounds.size().len() };
+    let ray = Ray { origin, direction: (&center - &origin).normalized() };
+
+    (mesh, ray)
+}
+
+pub fn intersection_bvh(c: &mut Criterion) {
+    let (mesh, ray) = load_tree();
+
+    c.bench_function("Mesh::nearest_face_intersection(tree.obj)", |b| 
+        b.iter(|| mesh.nearest_face_intersection(black_box(&ray))));
+}
+
+pub fn intersection_brute_force(c: &mut Criterion) {
+    let (mesh, ray) = load_tree();
+
+    c.bench_function("Mesh::brute_force_intersection(tree.obj)", |b| 
+        b.iter(|| mesh.brute_force_intersection(black_box(&ray))));
+}
+
+pub fn parse_obj(c: &mut Criterion) {
+    let data = fs::read(TREE_PATH).unwrap();
+
+    c.bench_function("ObjReader::next_line(tree.obj)", |b| 
+        b.iter(|| {
+            let mut reader = ObjReader::new(black_box(&data[..]));
+            while let Some(line) = reader.next_line().unwrap() {
+                black_box(line);
+            }
+        }));
+}
+
+pub fn load_obj(c: &mut Criterion) {
+    c.bench_function("Mesh::from_obj(tree.obj)", |b| 
+        b.iter(|| Mesh::from_obj(black_box(TREE_PATH), &Matrix::new(), None).unwrap()));
+}
+
+pub fn load_obj_cached(c: &mut Criterion) {
+    c.bench_function("Mesh::from_obj_cached(tree.obj)", |b| 
+        b.iter(|| Mesh::from_obj_cached(black_box(TREE_PATH), &Matrix::new(), None).unwrap()));
+}
@@ -0,0 +1,60 @@
+
+use criterion::{black_box, Criterion};
+
+use raytracer::ray::Ray;
+use raytracer::vec3::Vec3;
+use raytracer::material::Material;
+use raytracer::sphere::Sphere;
+use raytracer::plane::Plane;
+use raytracer::object::{ObjectEnum};
+use raytracer::world::World;
+
+// 100x100 spheres, plus a floor
+const GRID_SIZE: usize = 100;
+
+const TEST_RAY: Ray = Ray {
+    origin: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+    direction: Vec3 { x: 0.2, y: -0.1, z: -1.0 }
+};
+
+pub fn nearest_intersection(c: &mut Criterion) {
+    let world = construct_sphere_grid();
+
+    c.bench_function("World::nearest_intersection([TEST_RAY]) (10,000 spheres)", |b| 
+        b.iter(|| world.nearest_intersection(black_box(&TEST_RAY)).is_some()));
+}
+
+pub fn brute_force_intersection(c: &mut Criterion) {
+    let world = construct_sphere_grid();
+
+    c.bench_function("World::brute_force_intersection([TEST_RAY]) (10,000 spheres)", |b| 
+        b.iter(|| world.brute_force_intersection(black_box(&TEST_RAY)).is_some()));
+}
+
+fn construct_sphere_grid() -> World {
+    let mut objs: Vec<ObjectEnum> = Vec::new();
+
+    // floor
+    objs.push(ObjectEnum::Plane(Plane::new(
+        Vec3 { x: 0.0, y: -5.0, z: 0.0, },
+        Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+        Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+        Material::new()
+    )));
+
+    for x_inc in 0..GRID_SIZE {
+        for z_inc in 0..GRID_SIZE {
+            objs.push(ObjectEnum::Sphere(Sphere::new(
+                Vec3 { 
+                    x: (x_inc as f32 - GRID_SIZE as f32 / 2.0) * 2.0,
+                    y: -4.5,
+                    z: z_inc as f32 * -2.0 - 5.0,
+                },
+                0.5,
+                Material::new()
+            )));
+        }
+    }
+
+    World::new(objs)
+}
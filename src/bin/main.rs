@@ -7,7 +7,7 @@ use rand::prelude::*;
 use rand::{thread_rng};
 use rand::rngs::SmallRng;
 extern crate image;
-use image::{ImageBuffer, Rgb};
+use image::{ImageBuffer, ImageFormat, Rgb};
 extern crate crossbeam;
 //extern crate flame;
 #[macro_use]
@@ -17,57 +17,262 @@ use std::sync::{Arc, Mutex};
 use std::io::Write;
 use std::time::{Instant};
 
-use raytracer::fidelity_consts::{RESOLUTION_X,RESOLUTION_Y,BOUNCES,THREADS,TOTAL_BUFFER_SIZE,PIXELS_PER_THREAD};
+use raytracer::render_settings::RenderSettings;
 use raytracer::frame::Frame;
+use raytracer::camera::Camera;
 use raytracer::utils::clamp;
 use raytracer::scenes::{
     construct_reflect_scene,
+    construct_material_scene,
     construct_room_scene,
     construct_plane_texture_test,
     construct_sphere_texture_test,
     construct_wallpaper_scene,
     construct_wallpaper_scene_2,
+    construct_wallpaper_scene_3,
     construct_tree_scene};
 use raytracer::cast::cast_ray;
 use raytracer::color::Color;
+use raytracer::illumination::{Illumination,integrate};
 use raytracer::object::ObjectEnum;
+use raytracer::load_error::LoadError;
+use raytracer::scene::Scene;
+use raytracer::world::World;
 
 
+const USAGE: &str = "\
+Usage: raytracer [OPTIONS] [SCENE]
+
+SCENE is either the name of a built-in scene, the path to a scene file
+(see room.scene) or the path to a .gltf or .glb file. Defaults to the
+built-in \"room\" scene.
+
+Built-in scenes:
+    room, tree, reflect, material, plane-texture, sphere-texture,
+    wallpaper, wallpaper-2, wallpaper-3
+
+Options:
+    -o, --output <PATH>         Where to write the image [default: output.png]
+    -f, --format <FORMAT>       Image format: png, jpeg, bmp, gif, ico or pnm
+                                [default: guessed from the output path]
+    -r, --resolution <WxH>      Image size in pixels, e.g. 1920x1200
+    -a, --pixel-samples <N>     Rays cast through each pixel, for antialiasing
+                                and depth of field
+    -s, --samples <N>           Sample rays per diffuse/specular bounce
+    -b, --bounces <N>           Max number of indirect bounces
+    -t, --threads <N>           Number of chunks the image is split into
+        --seed <N>              Seed the random number generator, for
+                                reproducible renders
+    -p, --preview               Fast, unlit preview render
+    -h, --help                  Print this message
+";
+
+// Exit codes
+const EXIT_USAGE: i32 = 2;
+const EXIT_SCENE: i32 = 3;
+const EXIT_OUTPUT: i32 = 4;
+
+type SceneConstructor = fn() -> Result<Vec<ObjectEnum>,LoadError>;
+
+const BUILT_IN_SCENES: [(&str, SceneConstructor); 9] = [
+    ("room", construct_room_scene),
+    ("tree", construct_tree_scene),
+    ("reflect", construct_reflect_scene),
+    ("material", construct_material_scene),
+    ("plane-texture", construct_plane_texture_test),
+    ("sphere-texture", construct_sphere_texture_test),
+    ("wallpaper", construct_wallpaper_scene),
+    ("wallpaper-2", construct_wallpaper_scene_2),
+    ("wallpaper-3", construct_wallpaper_scene_3),
+];
+
+/**
+ * Everything that can be specified on the command line. Render settings
+ * given here override the ones in the scene.
+ */
+struct Options {
+    scene: String,
+    output: String,
+    format: Option<ImageFormat>,
+    resolution: Option<(usize,usize)>,
+    pixel_samples: Option<usize>,
+    samples: Option<usize>,
+    bounces: Option<u8>,
+    threads: Option<usize>,
+    seed: Option<u64>,
+    preview: bool,
+}
+
 fn main() {
-    let ray_frame = ray_trace();
-    write_image(&ray_frame);
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return;
+        },
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            std::process::exit(EXIT_USAGE);
+        }
+    };
+
+    let mut scene = load_scene(&options.scene).unwrap_or_else(|message| {
+        eprintln!("error: {}", message);
+        std::process::exit(EXIT_SCENE);
+    });
+
+    if let Some((x, y)) = options.resolution {
+        scene.settings.resolution_x = x;
+        scene.settings.resolution_y = y;
+    }
+    if let Some(pixel_samples) = options.pixel_samples {
+        scene.settings.pixel_samples = pixel_samples;
+    }
+    if let Some(samples) = options.samples {
+        scene.settings.sample_count = samples;
+    }
+    if let Some(bounces) = options.bounces {
+        scene.settings.bounces = bounces;
+    }
+    if let Some(threads) = options.threads {
+        scene.settings.threads = threads;
+    }
+    if options.preview {
+        scene.settings.preview_mode = true;
+    }
+
+    let meta_rng = match options.seed {
+        Some(seed) => SmallRng::seed_from_u64(seed),
+        None => SmallRng::from_rng(thread_rng()).unwrap(),
+    };
+
+    println!("Building acceleration structure...");
+    let world = World::new(scene.objects);
+
+    let ray_frame = ray_trace(world, &scene.camera, &scene.settings, meta_rng);
+
+    if let Err(message) = write_image(&ray_frame, &options.output, options.format) {
+        eprintln!("error: {}", message);
+        std::process::exit(EXIT_OUTPUT);
+    }
+}
+
+/**
+ * Returns `None` if help was requested.
+ */
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Options>,String> {
+    let mut options = Options {
+        scene: String::from("room"),
+        output: String::from("output.png"),
+        format: None,
+        resolution: None,
+        pixel_samples: None,
+        samples: None,
+        bounces: None,
+        threads: None,
+        seed: None,
+        preview: false,
+    };
+    let mut scene_given = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-p" | "--preview" => options.preview = true,
+            "-o" | "--output" => options.output = value_for(&arg, &mut args)?,
+            "-f" | "--format" => options.format = Some(parse_format(&value_for(&arg, &mut args)?)?),
+            "-r" | "--resolution" => options.resolution = Some(parse_resolution(&value_for(&arg, &mut args)?)?),
+            "-a" | "--pixel-samples" => options.pixel_samples = Some(parse_number(&arg, &value_for(&arg, &mut args)?)?),
+            "-s" | "--samples" => options.samples = Some(parse_number(&arg, &value_for(&arg, &mut args)?)?),
+            "-b" | "--bounces" => options.bounces = Some(parse_number(&arg, &value_for(&arg, &mut args)?)?),
+            "-t" | "--threads" => options.threads = Some(parse_number(&arg, &value_for(&arg, &mut args)?)?),
+            "--seed" => options.seed = Some(parse_number(&arg, &value_for(&arg, &mut args)?)?),
+            _ if arg.starts_with('-') => return Err(format!("unknown option \"{}\"", arg)),
+            _ if scene_given => return Err(format!("unexpected argument \"{}\"; only one scene can be given", arg)),
+            _ => {
+                options.scene = arg;
+                scene_given = true;
+            }
+        }
+    }
+
+    if options.pixel_samples == Some(0) || options.samples == Some(0) || options.threads == Some(0) {
+        return Err(String::from("--pixel-samples, --samples and --threads must be at least 1"));
+    }
+
+    Ok(Some(options))
+}
+
+fn value_for<I: Iterator<Item = String>>(option: &str, args: &mut I) -> Result<String,String> {
+    args.next().ok_or_else(|| format!("{} requires a value", option))
+}
+
+fn parse_number<T: std::str::FromStr>(option: &str, value: &str) -> Result<T,String> {
+    value.parse().map_err(|_| format!("invalid value \"{}\" for {}", value, option))
+}
+
+fn parse_resolution(value: &str) -> Result<(usize,usize),String> {
+    let dimensions: Vec<&str> = value.split('x').collect();
+
+    match dimensions.as_slice() {
+        [x, y] => match (x.parse(), y.parse()) {
+            (Ok(x), Ok(y)) if x > 0 && y > 0 => Ok((x, y)),
+            _ => Err(format!("invalid resolution \"{}\"", value)),
+        },
+        _ => Err(format!("invalid resolution \"{}\"; expected WIDTHxHEIGHT", value)),
+    }
+}
+
+fn parse_format(value: &str) -> Result<ImageFormat,String> {
+    match value.to_lowercase().as_str() {
+        "png" => Ok(ImageFormat::PNG),
+        "jpg" | "jpeg" => Ok(ImageFormat::JPEG),
+        "bmp" => Ok(ImageFormat::BMP),
+        "gif" => Ok(ImageFormat::GIF),
+        "ico" => Ok(ImageFormat::ICO),
+        "pnm" | "ppm" => Ok(ImageFormat::PNM),
+        _ => Err(format!("unsupported image format \"{}\"", value)),
+    }
+}
+
+/**
+ * Look the scene up by name among the built-in ones, falling back to
+ * loading it as a scene file.
+ */
+fn load_scene(name: &str) -> Result<Scene,String> {
+    match BUILT_IN_SCENES.iter().find(|scene| scene.0 == name) {
+        Some((_, construct)) => construct()
+            .map(Scene::new)
+            .map_err(|err| format!("failed to load built-in scene \"{}\": {}", name, err)),
+        None => Scene::from_file(name)
+            .map_err(|err| format!("failed to load scene \"{}\": {}", name, err)),
+    }
 }
 
 // Do the thing!
-fn ray_trace<'a>() -> Frame {
+fn ray_trace(world: World, camera: &Camera, settings: &RenderSettings, mut meta_rng: SmallRng) -> Frame {
     
     println!("Tracing scene...");
 
     let start_time = Instant::now();
-    
-    // Create list of objects
-    let objs: Vec<ObjectEnum> = construct_room_scene();
 
     // Create frame
-    let mut frame = Frame::new();
+    let mut frame = Frame::new(settings);
     let mut threads_done = 0;
 
     // Create thread wrappers
     let frame_mutex_arc: Arc<Mutex<&mut Frame>> = Arc::new(Mutex::new(&mut frame));
-    let objs_arc: Arc<&Vec<ObjectEnum>> = Arc::new(&objs);
+    let world = &world;
     let threads_done_mutex_arc = Arc::new(Mutex::new(&mut threads_done));
 
-    // ray_trace_segment(&mut frame, &objs, 0, 0, RESOLUTION, RESOLUTION);
+    // ray_trace_segment(&mut frame, &world, 0, 0, RESOLUTION, RESOLUTION);
 
     crossbeam::scope(move |scope| {
         print!("0.00%");
         std::io::stdout().flush().ok().expect("");
 
-        let mut meta_rng = thread_rng();
-
-        for thread in 0..THREADS {
-            let start_index = thread * PIXELS_PER_THREAD;
-            let objs_arc_clone = objs_arc.clone();
+        for thread in 0..settings.threads {
+            let start_index = thread * settings.pixels_per_thread();
             let frame_mutex_arc_clone = frame_mutex_arc.clone();
             let threads_done_mutex_arc_clone = threads_done_mutex_arc.clone();
             let rng = SmallRng::from_rng(&mut meta_rng).unwrap();
@@ -75,16 +280,18 @@ fn ray_trace<'a>() -> Frame {
             scope.spawn(move |_| {
                 ray_trace_segment(
                     frame_mutex_arc_clone, 
-                    objs_arc_clone, 
+                    world,
+                    camera,
+                    settings,
                     rng,
                     start_index,
-                    usize::min(start_index + PIXELS_PER_THREAD, TOTAL_BUFFER_SIZE)
+                    usize::min(start_index + settings.pixels_per_thread(), settings.total_buffer_size())
                 );
                 
                 let mut threads_done = threads_done_mutex_arc_clone.lock().unwrap();
                 **threads_done = (**threads_done) + 1;
 
-                print!("\r{}%           ", format!("{:.*}", 2, (**threads_done as f32 / THREADS as f32) * 100.0));
+                print!("\r{}%           ", format!("{:.*}", 2, (**threads_done as f32 / settings.threads as f32) * 100.0));
                 std::io::stdout().flush().ok().expect("");
             });
         }
@@ -99,16 +306,22 @@ fn ray_trace<'a>() -> Frame {
 /**
  * Raytrace one square sub-portion of the image (exists to facilitate threading)
  */
-fn ray_trace_segment(frame_mutex: Arc<Mutex<&mut Frame>>, objs: Arc<&Vec<ObjectEnum>>, mut rng: SmallRng, start: usize, end: usize) {
-    let mut buffer = [Color(0.0,0.0,0.0); PIXELS_PER_THREAD];
-    let range = end - start;
+fn ray_trace_segment(frame_mutex: Arc<Mutex<&mut Frame>>, world: &World, camera: &Camera, settings: &RenderSettings, mut rng: SmallRng, start: usize, end: usize) {
+    let range = end.saturating_sub(start);
+    let mut buffer = vec![Color(0.0,0.0,0.0); range];
 
     // Cast ray from each pixel
     for i in 0..range {
-        let xy = Frame::pos_from_index(i + start);
-        let ray = Frame::pixel_to_ray(&xy);
+        let xy = Frame::pos_from_index(i + start, settings);
+
+        let samples: Vec<Illumination> = (0..settings.pixel_samples)
+            .map(|_| match Frame::pixel_to_ray(&xy, camera, settings, &mut rng) {
+                Some(ray) => cast_ray(&ray, world, settings, &mut rng, settings.bounces),
+                None => Illumination::new(),
+            })
+            .collect();
 
-        let illumination = cast_ray(&ray, &objs, &mut rng, BOUNCES);
+        let illumination = integrate(&samples);
 
         buffer[i] = illumination.color * clamp(illumination.intensity, 0.0, 1.0);
     }
@@ -125,21 +338,27 @@ fn ray_trace_segment(frame_mutex: Arc<Mutex<&mut Frame>>, objs: Arc<&Vec<ObjectE
 }
 
 /**
- * Write a frame to a PNG file
+ * Write a frame to an image file
  */
-fn write_image(ray_frame: &Frame) {
-    println!("Writing to png...");
+fn write_image(ray_frame: &Frame, path: &str, format: Option<ImageFormat>) -> Result<(),String> {
+    println!("Writing to {}...", path);
 
-    let mut image: ImageBuffer::<Rgb<u8>,Vec<u8>> = ImageBuffer::new(RESOLUTION_X as u32, RESOLUTION_Y as u32);
+    let mut image: ImageBuffer::<Rgb<u8>,Vec<u8>> = ImageBuffer::new(ray_frame.width as u32, ray_frame.height as u32);
 
-    for x in 0..RESOLUTION_X {
-        for y in 0..RESOLUTION_Y {
+    for x in 0..ray_frame.width {
+        for y in 0..ray_frame.height {
             let color = ray_frame.get(x, y);
             image.get_pixel_mut(x as u32, y as u32).0 = color.to_u8();
         }
     }
 
-    image.save("output.png").unwrap();
+    let result = match format {
+        Some(format) => image.save_with_format(path, format),
+        None => image.save(path),
+    };
+    result.map_err(|err| format!("failed to write \"{}\": {}", path, err))?;
 
     println!("done");
+
+    Ok(())
 }
@@ -0,0 +1,153 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+
+/**
+ * An axis-aligned box, used to quickly rule out rays that can't hit
+ * whatever is inside of it.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl BoundingBox {
+
+    /**
+     * A box containing nothing, which anything can be unioned into.
+     */
+    pub fn empty() -> Self {
+        Self {
+            min: Vec3::from_scalar(f32::INFINITY),
+            max: Vec3::from_scalar(f32::NEG_INFINITY),
+        }
+    }
+
+    pub fn from_points<'a, I: IntoIterator<Item = &'a Vec3>>(points: I) -> Self {
+        let mut result = Self::empty();
+
+        for point in points {
+            result.grow(point);
+        }
+
+        result
+    }
+
+    pub fn grow(&mut self, point: &Vec3) {
+        self.min.x = f32::min(self.min.x, point.x);
+        self.min.y = f32::min(self.min.y, point.y);
+        self.min.z = f32::min(self.min.z, point.z);
+
+        self.max.x = f32::max(self.max.x, point.x);
+        self.max.y = f32::max(self.max.y, point.y);
+        self.max.z = f32::max(self.max.z, point.z);
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let mut result = *self;
+        result.grow(&other.min);
+        result.grow(&other.max);
+        result
+    }
+
+    /**
+     * The box covered by both (which might be empty)
+     */
+    pub fn overlap(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min: Vec3 {
+                x: f32::max(self.min.x, other.min.x),
+                y: f32::max(self.min.y, other.min.y),
+                z: f32::max(self.min.z, other.min.z),
+            },
+            max: Vec3 {
+                x: f32::min(self.max.x, other.max.x),
+                y: f32::min(self.max.y, other.max.y),
+                z: f32::min(self.max.z, other.max.z),
+            },
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        &(&self.min + &self.max) * 0.5
+    }
+
+    pub fn size(&self) -> Vec3 {
+        &self.max - &self.min
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        let size = self.size();
+        2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
+    /**
+     * How far along the ray it enters and leaves this box, if it passes
+     * through it at all. Unlike `ray_entry()` this covers the whole line,
+     * so either distance can be negative.
+     */
+    pub fn ray_span(&self, ray: &Ray, inverse_direction: &Vec3) -> Option<(f32,f32)> {
+        let tx1 = (self.min.x - ray.origin.x) * inverse_direction.x;
+        let tx2 = (self.max.x - ray.origin.x) * inverse_direction.x;
+        let ty1 = (self.min.y - ray.origin.y) * inverse_direction.y;
+        let ty2 = (self.max.y - ray.origin.y) * inverse_direction.y;
+        let tz1 = (self.min.z - ray.origin.z) * inverse_direction.z;
+        let tz2 = (self.max.z - ray.origin.z) * inverse_direction.z;
+
+        let near = f32::max(f32::max(f32::min(tx1, tx2), f32::min(ty1, ty2)), f32::min(tz1, tz2));
+        let far = f32::min(f32::min(f32::max(tx1, tx2), f32::max(ty1, ty2)), f32::max(tz1, tz2));
+
+        if near <= far {
+            Some((near, far))
+        } else {
+            None
+        }
+    }
+
+    /**
+     * How far along the ray it enters this box, if it does so before
+     * `max_distance` (0 if the ray starts inside). `inverse_direction` is
+     * passed in so that it can be computed once per ray instead of once
+     * per box.
+     */
+    pub fn ray_entry(&self, ray: &Ray, inverse_direction: &Vec3, max_distance: f32) -> Option<f32> {
+        let tx1 = (self.min.x - ray.origin.x) * inverse_direction.x;
+        let tx2 = (self.max.x - ray.origin.x) * inverse_direction.x;
+        let mut near = f32::min(tx1, tx2);
+        let mut far = f32::max(tx1, tx2);
+
+        let ty1 = (self.min.y - ray.origin.y) * inverse_direction.y;
+        let ty2 = (self.max.y - ray.origin.y) * inverse_direction.y;
+        near = f32::max(near, f32::min(ty1, ty2));
+        far = f32::min(far, f32::max(ty1, ty2));
+
+        let tz1 = (self.min.z - ray.origin.z) * inverse_direction.z;
+        let tz2 = (self.max.z - ray.origin.z) * inverse_direction.z;
+        near = f32::max(near, f32::min(tz1, tz2));
+        far = f32::min(far, f32::max(tz1, tz2));
+
+        let near = f32::max(near, 0.0);
+
+        if near <= far && near < max_distance {
+            Some(near)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn inverse_direction(ray: &Ray) -> Vec3 {
+    Vec3 {
+        x: 1.0 / ray.direction.x,
+        y: 1.0 / ray.direction.y,
+        z: 1.0 / ray.direction.z,
+    }
+}
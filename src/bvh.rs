@@ -0,0 +1,326 @@
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::intersection::Intersection;
+use crate::bounding_box::{BoundingBox,inverse_direction};
+use crate::mesh_cache::{CacheWriter,CacheReader};
+use crate::load_error::LoadError;
+
+// Relative costs used by the surface area heuristic
+const TRAVERSAL_COST: f32 = 1.0;
+const INTERSECTION_COST: f32 = 2.0;
+
+// Number of candidate split planes tried along each axis
+const SAH_BINS: usize = 12;
+
+// Nodes this small always become leaves
+const MIN_LEAF_SIZE: usize = 2;
+
+enum BvhNode {
+    Leaf { bounds: BoundingBox, first: usize, count: usize },
+    Interior { bounds: BoundingBox, left: usize, right: usize },
+}
+
+impl BvhNode {
+    fn bounds(&self) -> &BoundingBox {
+        match self {
+            BvhNode::Leaf { bounds, .. } => bounds,
+            BvhNode::Interior { bounds, .. } => bounds,
+        }
+    }
+}
+
+/**
+ * A bounding volume hierarchy over a list of primitives (like the faces of
+ * a mesh), which only knows about their bounding boxes. Primitives are
+ * referred to by their index in the list the hierarchy was built from.
+ */
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+
+    // primitive indices, ordered so that each leaf covers a contiguous range
+    indices: Vec<usize>,
+}
+
+impl Bvh {
+
+    /**
+     * Build the hierarchy top-down, splitting each node where the surface
+     * area heuristic estimates rays will be cheapest to trace.
+     */
+    pub fn build(bounds: &[BoundingBox]) -> Self {
+        let mut bvh = Self {
+            nodes: Vec::new(),
+            indices: (0..bounds.len()).collect(),
+        };
+
+        if !bounds.is_empty() {
+            let centroids: Vec<Vec3> = bounds.iter().map(|b| b.centroid()).collect();
+            bvh.build_node(bounds, &centroids, 0, bounds.len());
+        }
+
+        bvh
+    }
+
+    pub fn bounds(&self) -> BoundingBox {
+        self.nodes.first().map(|node| *node.bounds()).unwrap_or_else(BoundingBox::empty)
+    }
+
+    fn build_node(&mut self, bounds: &[BoundingBox], centroids: &[Vec3], start: usize, end: usize) -> usize {
+        let mut node_bounds = BoundingBox::empty();
+        let mut centroid_bounds = BoundingBox::empty();
+        for &index in &self.indices[start..end] {
+            node_bounds = node_bounds.union(&bounds[index]);
+            centroid_bounds.grow(&centroids[index]);
+        }
+
+        let node_index = self.nodes.len();
+        let count = end - start;
+        self.nodes.push(BvhNode::Leaf { bounds: node_bounds, first: start, count });
+
+        if count <= MIN_LEAF_SIZE {
+            return node_index;
+        }
+
+        let mid = match self.find_split(bounds, centroids, start, end, &node_bounds, &centroid_bounds) {
+            Some(mid) => mid,
+            None => return node_index,
+        };
+
+        let left = self.build_node(bounds, centroids, start, mid);
+        let right = self.build_node(bounds, centroids, mid, end);
+        self.nodes[node_index] = BvhNode::Interior { bounds: node_bounds, left, right };
+
+        node_index
+    }
+
+    /**
+     * Partition `indices[start..end]` along the cheapest split plane, and
+     * return where the second half starts; or `None` if the node is better
+     * off as a leaf.
+     */
+    fn find_split(&mut self, bounds: &[BoundingBox], centroids: &[Vec3], start: usize, end: usize, node_bounds: &BoundingBox, centroid_bounds: &BoundingBox) -> Option<usize> {
+        let count = end - start;
+        let extent = centroid_bounds.size();
+        let node_area = node_bounds.surface_area();
+
+        let mut best: Option<(usize,usize,f32)> = None; // (axis, bin, cost)
+
+        for axis in 0..3 {
+            let axis_extent = component(&extent, axis);
+            if axis_extent <= 0.0 {
+                continue;
+            }
+
+            let mut bin_bounds = [BoundingBox::empty(); SAH_BINS];
+            let mut bin_counts = [0usize; SAH_BINS];
+            for &index in &self.indices[start..end] {
+                let bin = bin_for(&centroids[index], centroid_bounds, axis, axis_extent);
+                bin_bounds[bin] = bin_bounds[bin].union(&bounds[index]);
+                bin_counts[bin] += 1;
+            }
+
+            // sweep from the right to get the cost of everything after each plane
+            let mut right_areas = [0.0; SAH_BINS];
+            let mut right_counts = [0usize; SAH_BINS];
+            let mut accumulated = BoundingBox::empty();
+            let mut accumulated_count = 0;
+            for bin in (1..SAH_BINS).rev() {
+                accumulated = accumulated.union(&bin_bounds[bin]);
+                accumulated_count += bin_counts[bin];
+                right_areas[bin] = accumulated.surface_area();
+                right_counts[bin] = accumulated_count;
+            }
+
+            let mut accumulated = BoundingBox::empty();
+            let mut accumulated_count = 0;
+            for bin in 1..SAH_BINS {
+                accumulated = accumulated.union(&bin_bounds[bin - 1]);
+                accumulated_count += bin_counts[bin - 1];
+
+                if accumulated_count == 0 || right_counts[bin] == 0 {
+                    continue;
+                }
+
+                let cost = TRAVERSAL_COST + INTERSECTION_COST *
+                    (accumulated.surface_area() * accumulated_count as f32 + right_areas[bin] * right_counts[bin] as f32) / node_area;
+
+                if best.map(|b| cost < b.2).unwrap_or(true) {
+                    best = Some((axis, bin, cost));
+                }
+            }
+        }
+
+        let (axis, split_bin, cost) = best?;
+        if cost >= INTERSECTION_COST * count as f32 {
+            return None;
+        }
+
+        let axis_extent = component(&extent, axis);
+        let mut mid = start;
+        for i in start..end {
+            if bin_for(&centroids[self.indices[i]], centroid_bounds, axis, axis_extent) < split_bin {
+                self.indices.swap(i, mid);
+                mid += 1;
+            }
+        }
+
+        Some(mid)
+    }
+
+    /**
+     * Find the nearest hit along a ray, and the index of the primitive that
+     * was hit. `intersect` is asked about every primitive whose bounds the
+     * ray passes through (nearest boxes first), and should return the
+     * ray's intersection with that primitive, if any.
+     */
+    pub fn nearest_intersection<F: FnMut(usize) -> Option<Intersection>>(&self, ray: &Ray, mut intersect: F) -> Option<(Intersection,usize)> {
+        let inverse_direction = inverse_direction(ray);
+        let mut nearest: Option<(Intersection,usize)> = None;
+        let mut nearest_distance = f32::INFINITY;
+
+        let root_entry = match self.nodes.first() {
+            Some(root) => root.bounds().ray_entry(ray, &inverse_direction, nearest_distance),
+            None => None,
+        };
+
+        let mut stack: Vec<(usize,f32)> = Vec::with_capacity(32);
+        if let Some(entry) = root_entry {
+            stack.push((0, entry));
+        }
+
+        while let Some((node_index, entry)) = stack.pop() {
+            if entry >= nearest_distance {
+                continue;
+            }
+
+            match &self.nodes[node_index] {
+                BvhNode::Leaf { first, count, .. } => {
+                    for &primitive in &self.indices[*first..first + count] {
+                        if let Some(intersection) = intersect(primitive) {
+                            if intersection.distance < nearest_distance {
+                                nearest_distance = intersection.distance;
+                                nearest = Some((intersection, primitive));
+                            }
+                        }
+                    }
+                },
+                BvhNode::Interior { left, right, .. } => {
+                    let left_entry = self.nodes[*left].bounds().ray_entry(ray, &inverse_direction, nearest_distance);
+                    let right_entry = self.nodes[*right].bounds().ray_entry(ray, &inverse_direction, nearest_distance);
+
+                    // push the farther child first, so the nearer one is visited first
+                    match (left_entry, right_entry) {
+                        (Some(l), Some(r)) => {
+                            if l <= r {
+                                stack.push((*right, r));
+                                stack.push((*left, l));
+                            } else {
+                                stack.push((*left, l));
+                                stack.push((*right, r));
+                            }
+                        },
+                        (Some(l), None) => stack.push((*left, l)),
+                        (None, Some(r)) => stack.push((*right, r)),
+                        (None, None) => (),
+                    }
+                },
+            }
+        }
+
+        nearest
+    }
+
+    /**
+     * Call `visit` with every primitive whose bounds the ray passes 
+     * through (ahead of its origin), in no particular order
+     */
+    pub fn for_each_candidate<F: FnMut(usize)>(&self, ray: &Ray, mut visit: F) {
+        let inverse_direction = inverse_direction(ray);
+        let mut stack: Vec<usize> = Vec::with_capacity(32);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if node.bounds().ray_entry(ray, &inverse_direction, f32::INFINITY).is_none() {
+                continue;
+            }
+
+            match node {
+                BvhNode::Leaf { first, count, .. } => {
+                    for &primitive in &self.indices[*first..first + count] {
+                        visit(primitive);
+                    }
+                },
+                BvhNode::Interior { left, right, .. } => {
+                    stack.push(*left);
+                    stack.push(*right);
+                },
+            }
+        }
+    }
+
+    pub(crate) fn write_cache(&self, writer: &mut CacheWriter) {
+        writer.list(&self.indices, |writer, &index| writer.index(index));
+        writer.list(&self.nodes, |writer, node| {
+            // whether it's an interior node, and then either its children or
+            // its range of primitives
+            let (bounds, a, b) = match node {
+                BvhNode::Leaf { bounds, first, count } => (bounds, first, count),
+                BvhNode::Interior { bounds, left, right } => (bounds, left, right),
+            };
+
+            writer.flag(matches!(node, BvhNode::Interior { .. }));
+            writer.vec3(&bounds.min);
+            writer.vec3(&bounds.max);
+            writer.index(*a);
+            writer.index(*b);
+        });
+    }
+
+    /**
+     * Read back a hierarchy written by `write_cache()`, over
+     * `primitive_count` primitives
+     */
+    pub(crate) fn read_cache(reader: &mut CacheReader, primitive_count: usize) -> Result<Self,LoadError> {
+        let indices = reader.list(|reader| reader.index(primitive_count))?;
+        let nodes = reader.list(|reader| {
+            let interior = reader.flag()?;
+            let bounds = BoundingBox { min: reader.vec3()?, max: reader.vec3()? };
+            let (a, b) = (reader.u32()? as usize, reader.u32()? as usize);
+
+            Ok(if interior {
+                BvhNode::Interior { bounds, left: a, right: b }
+            } else {
+                BvhNode::Leaf { bounds, first: a, count: b }
+            })
+        })?;
+
+        // children always come after their parent, which also rules out
+        // cycles that would send traversal round in circles
+        let in_range = nodes.iter().enumerate().all(|(node_index, node)| match node {
+            BvhNode::Leaf { first, count, .. } => first + count <= indices.len(),
+            BvhNode::Interior { left, right, .. } =>
+                *left > node_index && *right > node_index && *left < nodes.len() && *right < nodes.len(),
+        });
+        if !in_range {
+            return Err(reader.error("the cache's BVH refers to nodes or faces that aren't there"));
+        }
+
+        Ok(Self { nodes, indices })
+    }
+}
+
+fn component(vec: &Vec3, axis: usize) -> f32 {
+    match axis {
+        0 => vec.x,
+        1 => vec.y,
+        _ => vec.z,
+    }
+}
+
+fn bin_for(centroid: &Vec3, centroid_bounds: &BoundingBox, axis: usize, axis_extent: f32) -> usize {
+    let offset = (component(centroid, axis) - component(&centroid_bounds.min, axis)) / axis_extent;
+    usize::min((offset * SAH_BINS as f32) as usize, SAH_BINS - 1)
+}
@@ -0,0 +1,317 @@
+use std::f32::consts::PI;
+
+use rand::Rng;
+use rand::rngs::SmallRng;
+
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::render_settings::RenderSettings;
+use crate::utils::{TWO_PI,PI_OVER_TWO};
+
+/**
+ * How rays are fanned out from the camera across the image.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Projection {
+
+    // Standard rectilinear view, spanning the camera's field of view
+    Perspective,
+
+    // Parallel rays, with no foreshortening; `height` is the size of the
+    // view in world units
+    Orthographic { height: f32 },
+
+    // Circular image, with the camera's field of view spanning the height of
+    // the circle (up to 360 degrees)
+    Fisheye,
+
+    // The full sphere around the camera, 360 degrees across and 180 degrees
+    // top to bottom; meant for 2:1 images
+    Equirectangular,
+}
+
+/**
+ * A thin-lens camera, positioned by where it is, what it's looking at and
+ * which way is up. With an aperture of zero it behaves as a pinhole, and
+ * everything is in focus.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Camera {
+    pub position: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+
+    pub projection: Projection,
+
+    // Vertical field of view, in radians
+    pub fov: f32,
+
+    // Width over height of the image plane; `None` matches the image's
+    // resolution, so that pixels come out square
+    pub aspect_ratio: Option<f32>,
+
+    // Radius of the lens; larger apertures blur out-of-focus objects more.
+    // Only used by the perspective projection
+    pub aperture: f32,
+
+    // Distance from the camera to the plane that's in perfect focus;
+    // `None` focuses on the target
+    pub focus_distance: Option<f32>,
+}
+
+impl Camera {
+
+    /**
+     * At the origin, looking down -Z.
+     */
+    pub fn new() -> Self {
+        Self {
+            position: Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+            target: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+            up: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+            projection: Projection::Perspective,
+            fov: 2.0 * (0.5f32).atan(), // image plane 2 units tall, 2 units away
+            aspect_ratio: None,
+            aperture: 0.0,
+            focus_distance: None,
+        }
+    }
+
+    pub fn look_at(position: Vec3, target: Vec3, up: Vec3, fov_degrees: f32) -> Self {
+        Self {
+            position,
+            target,
+            up,
+            projection: Projection::Perspective,
+            fov: fov_degrees.to_radians(),
+            aspect_ratio: None,
+            aperture: 0.0,
+            focus_distance: None,
+        }
+    }
+
+    /**
+     * Unit vectors pointing forward, right and up from the camera's point
+     * of view. `up` is re-derived so that it's perpendicular to the view
+     * direction even if the configured one isn't.
+     */
+    pub fn basis(&self) -> (Vec3,Vec3,Vec3) {
+        let forward = (&self.target - &self.position).normalized();
+        let right = forward.cross(&self.up).normalized();
+        let up = right.cross(&forward);
+
+        (forward, right, up)
+    }
+
+    /**
+     * Initialize a ray projecting out from one pixel of the image. The ray
+     * goes through the center of the pixel unless several samples are
+     * being taken per pixel, in which case it's jittered within it.
+     *
+     * Returns `None` for pixels that the projection doesn't cover, like the
+     * corners outside of a fisheye's image circle.
+     */
+    pub fn ray_for_pixel(&self, pixel: &(usize,usize), settings: &RenderSettings, rng: &mut SmallRng) -> Option<Ray> {
+        let (forward, right, up) = self.basis();
+        let aspect_ratio = self.aspect_ratio.unwrap_or_else(|| settings.aspect_ratio());
+
+        let (offset_x, offset_y) = if settings.pixel_samples > 1 {
+            (rng.gen_range(0.0, 1.0), rng.gen_range(0.0, 1.0))
+        } else {
+            (0.5, 0.5)
+        };
+
+        // -1 to 1 across the image, with y pointing up
+        let x = (pixel.0 as f32 + offset_x) / settings.resolution_x as f32 * 2.0 - 1.0;
+        let y = 1.0 - (pixel.1 as f32 + offset_y) / settings.resolution_y as f32 * 2.0;
+
+        match self.projection {
+            Projection::Perspective => {
+                let half_height = (self.fov / 2.0).tan();
+                let half_width = half_height * aspect_ratio;
+
+                let direction = &(&forward + &(&right * (x * half_width))) + &(&up * (y * half_height));
+
+                Some(self.through_lens(&direction, &right, &up, rng))
+            },
+            Projection::Orthographic { height } => {
+                let half_height = height / 2.0;
+                let half_width = half_height * aspect_ratio;
+
+                Some(Ray {
+                    origin: &(&self.position + &(&right * (x * half_width))) + &(&up * (y * half_height)),
+                    direction: forward,
+                })
+            },
+            Projection::Fisheye => {
+                // equidistant: the angle away from the view direction grows
+                // linearly with the distance from the center of the image
+                let x = x * aspect_ratio;
+                let radius = (x * x + y * y).sqrt();
+
+                if radius > 1.0 {
+                    return None;
+                }
+
+                let theta = radius * self.fov / 2.0;
+                let (sin_theta, cos_theta) = theta.sin_cos();
+                let (sin_phi, cos_phi) = if radius > 0.0 { (y / radius, x / radius) } else { (0.0, 0.0) };
+
+                let sideways = &(&right * cos_phi) + &(&up * sin_phi);
+
+                Some(Ray {
+                    origin: self.position,
+                    direction: (&(&forward * cos_theta) + &(&sideways * sin_theta)).normalized(),
+                })
+            },
+            Projection::Equirectangular => {
+                let longitude = x * PI;
+                let latitude = y * PI_OVER_TWO;
+
+                let (sin_longitude, cos_longitude) = longitude.sin_cos();
+                let (sin_latitude, cos_latitude) = latitude.sin_cos();
+
+                let horizontal = &(&forward * cos_longitude) + &(&right * sin_longitude);
+
+                Some(Ray {
+                    origin: self.position,
+                    direction: (&(&horizontal * cos_latitude) + &(&up * sin_latitude)).normalized(),
+                })
+            },
+        }
+    }
+
+    /**
+     * Turn a pinhole ray direction into one that starts from a random point
+     * on the lens. Every ray through a given pixel converges on the same
+     * point of the focal plane, wherever on the lens it starts from.
+     */
+    fn through_lens(&self, direction: &Vec3, right: &Vec3, up: &Vec3, rng: &mut SmallRng) -> Ray {
+        if self.aperture <= 0.0 {
+            return Ray {
+                origin: self.position,
+                direction: direction.normalized(),
+            };
+        }
+
+        // `direction` is one unit long along the view axis, so this lands on
+        // a flat focal plane rather than a sphere around the camera
+        let focus_distance = self.focus_distance.unwrap_or_else(|| (&self.target - &self.position).len());
+        let focus_point = &self.position + &(direction * focus_distance);
+
+        let (lens_x, lens_y) = sample_disk(rng, self.aperture);
+        let origin = &(&self.position + &(right * lens_x)) + &(up * lens_y);
+
+        Ray {
+            origin,
+            direction: (&focus_point - &origin).normalized(),
+        }
+    }
+}
+
+/**
+ * A uniformly-distributed random point on a disk centered at the origin.
+ */
+fn sample_disk(rng: &mut SmallRng, radius: f32) -> (f32,f32) {
+    let r = radius * rng.gen_range(0.0f32, 1.0).sqrt();
+    let theta = rng.gen_range(0.0, TWO_PI);
+
+    (r * theta.cos(), r * theta.sin())
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand::rngs::SmallRng;
+
+    use crate::camera::{Camera,Projection};
+    use crate::vec3::Vec3;
+    use crate::render_settings::RenderSettings;
+
+    #[test]
+    fn test_center_ray_hits_target() {
+        let mut settings = RenderSettings::new();
+        settings.resolution_x = 5;
+        settings.resolution_y = 3;
+
+        let camera = Camera::look_at(
+            Vec3 { x: 1.0, y: 2.0, z: 3.0 },
+            Vec3 { x: -2.0, y: 0.0, z: -4.0 },
+            Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+            60.0);
+
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        let ray = camera.ray_for_pixel(&(2, 1), &settings, &mut rng).unwrap();
+        let expected = (&camera.target - &camera.position).normalized();
+
+        assert_eq!(ray.origin, camera.position);
+        assert!((&ray.direction - &expected).len() < 0.0001);
+
+        // the top-left pixel should be up and to the left of the target
+        let (_, right, up) = camera.basis();
+        let corner = camera.ray_for_pixel(&(0, 0), &settings, &mut rng).unwrap();
+        assert!(corner.direction.dot(&right) < 0.0);
+        assert!(corner.direction.dot(&up) > 0.0);
+    }
+
+    #[test]
+    fn test_thin_lens_converges_on_focal_plane() {
+        let mut settings = RenderSettings::new();
+        settings.resolution_x = 5;
+        settings.resolution_y = 3;
+
+        let mut camera = Camera::new();
+        camera.aperture = 0.5;
+        camera.focus_distance = Some(10.0);
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        let focus_point = Vec3 { x: 0.0, y: 0.0, z: -10.0 };
+
+        for _ in 0..16 {
+            let ray = camera.ray_for_pixel(&(2, 1), &settings, &mut rng).unwrap();
+            let distance = (focus_point.z - ray.origin.z) / ray.direction.z;
+            let hit = &ray.origin + &(&ray.direction * distance);
+
+            assert!(ray.origin.len() <= 0.5);
+            assert!((&hit - &focus_point).len() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_projections() {
+        let mut settings = RenderSettings::new();
+        settings.resolution_x = 4;
+        settings.resolution_y = 2;
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut camera = Camera::new();
+
+        // orthographic rays are parallel, and spread out over the view
+        camera.projection = Projection::Orthographic { height: 2.0 };
+        let left = camera.ray_for_pixel(&(0, 0), &settings, &mut rng).unwrap();
+        let right = camera.ray_for_pixel(&(3, 0), &settings, &mut rng).unwrap();
+        assert_eq!(left.direction, right.direction);
+        assert!((right.origin.x - left.origin.x - 3.0).abs() < 0.0001);
+
+        // an equirectangular image wraps all the way around
+        camera.projection = Projection::Equirectangular;
+        let behind_left = camera.ray_for_pixel(&(0, 0), &settings, &mut rng).unwrap();
+        let behind_right = camera.ray_for_pixel(&(3, 0), &settings, &mut rng).unwrap();
+        assert!(behind_left.direction.z > 0.0);
+        assert!(behind_right.direction.z > 0.0);
+        assert!(behind_left.direction.y > 0.0);
+
+        // a fisheye image doesn't cover the corners of a wide image
+        camera.projection = Projection::Fisheye;
+        assert!(camera.ray_for_pixel(&(0, 0), &settings, &mut rng).is_none());
+        assert!(camera.ray_for_pixel(&(1, 0), &settings, &mut rng).is_some());
+    }
+}
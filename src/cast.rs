@@ -3,10 +3,11 @@ use rand::rngs::SmallRng;
 //use flamer::flame;
 
 use crate::ray::Ray;
-use crate::intersection::Intersection;
 use crate::illumination::{Illumination};
 use crate::color::Color;
-use crate::object::{Object,ObjectEnum};
+use crate::object::Object;
+use crate::world::World;
+use crate::render_settings::RenderSettings;
 
 // misc
 const BACKGROUND_ILLUMINATION: Illumination = Illumination { color: Color(0.0, 0.0, 0.0), intensity: 0.0 };
@@ -17,24 +18,14 @@ const BACKGROUND_ILLUMINATION: Illumination = Illumination { color: Color(0.0, 0
  * Cast a single ray, from a pixel or from a bounce
  */
 //#[flame]
-pub fn cast_ray(ray: &Ray, objs: &Vec<ObjectEnum>, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
-    let mut nearest_intersection: Option<Intersection> = None;
-    let mut nearest_object: Option<&ObjectEnum> = None;
-
+pub fn cast_ray(ray: &Ray, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
 
     // Find nearest object intersection
-    for index in 0..objs.len() {
-        if let Some(intersection) = objs[index].intersection(&ray) {
-            if intersection.distance < nearest_intersection.as_ref().map(|int| int.distance).unwrap_or(std::f32::INFINITY) {
-                nearest_intersection = Some(intersection);
-                nearest_object = Some(&objs[index]);
-            }
-        }
-    }
+    let nearest = world.nearest_intersection(ray);
 
     // Compute total illumination at this intersection
-    let nearest_illumination: Illumination = nearest_object
-        .map(|obj| obj.shade(ray, objs, rng, bounces_remaining))
+    let nearest_illumination: Illumination = nearest
+        .map(|(mut intersection, obj)| obj.shade(&mut intersection, world, settings, rng, bounces_remaining))
         .unwrap_or(BACKGROUND_ILLUMINATION);
 
     return nearest_illumination;
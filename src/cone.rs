@@ -0,0 +1,207 @@
+use std::f32::consts::PI;
+
+use rand::rngs::SmallRng;
+
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::object::Object;
+use crate::intersection::{Intersection,Interval};
+use crate::material::Material;
+use crate::illumination::Illumination;
+use crate::render_settings::RenderSettings;
+use crate::bounding_box::BoundingBox;
+use crate::utils::{TWO_PI,solve_quadratic};
+use crate::world::World;
+
+// how close to the base a point has to be to count as being on it
+const BASE_EPSILON: f32 = 0.002;
+
+/**
+ * A solid cone standing upright (along +Y), with a circular base at its
+ * position and its tip `height` above that. Textured like a `Cylinder`:
+ * around and up the side, and flat across the base.
+ */
+pub struct Cone {
+    position: Vec3,
+    radius: f32,
+    height: f32,
+    material: Material,
+
+    // how much the radius shrinks per unit of height, squared
+    slope_squared: f32,
+}
+
+impl Cone {
+
+    pub fn new(position: Vec3, radius: f32, height: f32, material: Material) -> Self {
+        Self {
+            position,
+            radius,
+            height,
+            material,
+            slope_squared: (radius / height) * (radius / height),
+        }
+    }
+
+    /**
+     * Everywhere the ray crosses the surface (at any distance), with the
+     * normal there
+     */
+    fn surface_hits(&self, ray: &Ray) -> Vec<(f32,Vec3)> {
+        let origin = &ray.origin - &self.position;
+        let direction = &ray.direction;
+        let k2 = self.slope_squared;
+
+        let mut hits: Vec<(f32,Vec3)> = Vec::new();
+
+        // the side, where x^2 + z^2 = k^2 * (height - y)^2
+        let below_tip = self.height - origin.y;
+        let a = direction.x * direction.x + direction.z * direction.z - k2 * direction.y * direction.y;
+        let b = 2.0 * (origin.x * direction.x + origin.z * direction.z + k2 * below_tip * direction.y);
+        let c = origin.x * origin.x + origin.z * origin.z - k2 * below_tip * below_tip;
+
+        let side_roots = if a.abs() > 1e-12 {
+            solve_quadratic(a, b, c)
+        } else if b != 0.0 {
+            // parallel to the side: only one crossing
+            Some((-c / b, -c / b))
+        } else {
+            None
+        };
+
+        if let Some((t0, t1)) = side_roots {
+            for &t in [t0, t1].iter() {
+                let y = origin.y + direction.y * t;
+
+                // the quadratic also covers the upside-down cone above the tip
+                if y >= 0.0 && y <= self.height {
+                    let x = origin.x + direction.x * t;
+                    let z = origin.z + direction.z * t;
+                    hits.push((t, Vec3 { x, y: k2 * (self.height - y), z }.normalized()));
+                }
+            }
+        }
+
+        // the base
+        if direction.y != 0.0 {
+            let t = -origin.y / direction.y;
+            let x = origin.x + direction.x * t;
+            let z = origin.z + direction.z * t;
+
+            if x * x + z * z <= self.radius * self.radius {
+                hits.push((t, Vec3 { x: 0.0, y: -1.0, z: 0.0 }));
+            }
+        }
+
+        hits
+    }
+
+    fn hit_at(&self, ray: &Ray, distance: f32, normal: Vec3) -> Intersection {
+        let position = &ray.origin + &(&ray.direction * distance);
+
+        let mut intersection = Intersection::new(
+            distance,
+            &position + &(&normal * 0.001), // offset to avoid floating-point error
+            normal,
+            ray.direction,
+        );
+        intersection.uv = self.texture_coordinate(&position);
+
+        intersection
+    }
+}
+
+impl Object for Cone {
+
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        self.surface_hits(ray).into_iter()
+            .filter(|(distance, _)| *distance > 0.0)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(distance, normal)| self.hit_at(ray, distance, normal))
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let hits = self.surface_hits(ray);
+        if hits.len() < 2 {
+            return Vec::new();
+        }
+
+        // it's convex, so everything between the first and last hits is inside
+        let (entry_distance, entry_normal) = hits.iter().min_by(|a, b| a.0.total_cmp(&b.0)).unwrap();
+        let (exit_distance, exit_normal) = hits.iter().max_by(|a, b| a.0.total_cmp(&b.0)).unwrap();
+
+        vec![Interval::new(
+            self.hit_at(ray, *entry_distance, *entry_normal),
+            self.hit_at(ray, *exit_distance, *exit_normal),
+        )]
+    }
+
+    fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
+        let relative = point - &self.position;
+
+        if relative.y > BASE_EPSILON {
+            (
+                (relative.z.atan2(relative.x) + PI) / TWO_PI,
+                relative.y / self.height,
+            )
+        } else {
+            (
+                (relative.x / self.radius + 1.0) / 2.0,
+                (relative.z / self.radius + 1.0) / 2.0,
+            )
+        }
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        Some(BoundingBox {
+            min: &self.position - &Vec3 { x: self.radius, y: 0.0, z: self.radius },
+            max: &self.position + &Vec3 { x: self.radius, y: self.height, z: self.radius },
+        })
+    }
+
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+        self.material.shade(
+            intersection,
+            world,
+            settings,
+            rng,
+            bounces_remaining
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Vec3;
+    use crate::ray::Ray;
+    use crate::material::Material;
+    use crate::object::Object;
+    use crate::cone::Cone;
+
+    #[test]
+    fn test_side_and_base() {
+        let cone = Cone::new(Vec3 { x: 0.0, y: 0.0, z: -5.0 }, 1.0, 2.0, Material::new());
+
+        // halfway up, the radius is 0.5
+        let side = cone.intersection(&Ray {
+            origin: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+            direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+        }).unwrap();
+        assert!((side.distance - 4.5).abs() < 0.0001);
+        assert!((&side.normal - &Vec3 { x: 0.0, y: 0.5, z: 1.0 }.normalized()).len() < 0.0001);
+        assert!((side.uv.1 - 0.5).abs() < 0.0001);
+
+        let base = cone.intersection(&Ray {
+            origin: Vec3 { x: 0.5, y: -3.0, z: -5.0 },
+            direction: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+        }).unwrap();
+        assert!((base.distance - 3.0).abs() < 0.0001);
+        assert_eq!(base.normal, Vec3 { x: 0.0, y: -1.0, z: 0.0 });
+
+        // would hit the mirrored cone above the tip
+        assert!(cone.intersection(&Ray {
+            origin: Vec3 { x: 0.0, y: 3.0, z: 0.0 },
+            direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+        }).is_none());
+    }
+}
@@ -0,0 +1,266 @@
+use rand::rngs::SmallRng;
+
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::object::{Object,ObjectEnum};
+use crate::intersection::{Intersection,Interval};
+use crate::illumination::Illumination;
+use crate::render_settings::RenderSettings;
+use crate::bounding_box::BoundingBox;
+use crate::world::World;
+
+/**
+ * How a `Csg` combines its two children
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+
+    // the first child, with the second carved out of it
+    Difference,
+}
+
+impl CsgOperation {
+
+    fn contains(&self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_left || in_right,
+            CsgOperation::Intersection => in_left && in_right,
+            CsgOperation::Difference => in_left && !in_right,
+        }
+    }
+}
+
+/**
+ * Constructive solid geometry: a solid made by combining two others,
+ * using the intervals where a ray is inside of each. Its surface is made of
+ * pieces of the children's surfaces, and each piece is shaded with the
+ * material of the child it came from.
+ *
+ * Hits remember which child they came from in `Intersection::csg_path`,
+ * which has room for 64 levels of nesting.
+ */
+pub struct Csg {
+    operation: CsgOperation,
+    left: Box<ObjectEnum>,
+    right: Box<ObjectEnum>,
+}
+
+impl Csg {
+
+    pub fn new(operation: CsgOperation, left: ObjectEnum, right: ObjectEnum) -> Self {
+        Self {
+            operation,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    /**
+     * Combine any number of objects (a difference subtracts all of the
+     * others from the first). The tree is kept balanced, so that big
+     * combinations don't nest too deeply. Returns `None` for fewer than
+     * two objects.
+     */
+    pub fn from_objects(operation: CsgOperation, mut objects: Vec<ObjectEnum>) -> Option<Self> {
+        if objects.len() < 2 {
+            return None;
+        }
+
+        match operation {
+            CsgOperation::Difference => {
+                let rest = objects.split_off(1);
+                let first = objects.pop().unwrap();
+
+                Some(Self::new(operation, first, balanced(CsgOperation::Union, rest)))
+            },
+            _ => match balanced(operation, objects) {
+                ObjectEnum::Csg(csg) => Some(csg),
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    pub fn operation(&self) -> CsgOperation {
+        self.operation
+    }
+}
+
+/**
+ * Join objects with an associative operation, as a balanced tree
+ */
+fn balanced(operation: CsgOperation, mut objects: Vec<ObjectEnum>) -> ObjectEnum {
+    if objects.len() == 1 {
+        return objects.pop().unwrap();
+    }
+
+    let right = objects.split_off(objects.len() / 2);
+    ObjectEnum::Csg(Csg::new(operation, balanced(operation, objects), balanced(operation, right)))
+}
+
+impl Object for Csg {
+
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        for interval in self.intervals(ray) {
+            if interval.entry.distance > 0.0 {
+                return Some(interval.entry);
+            }
+
+            // starting inside, so the hit is on the way out (unless the
+            // inside never ends)
+            if interval.exit.distance > 0.0 {
+                return if interval.exit.distance.is_finite() { Some(interval.exit) } else { None };
+            }
+        }
+
+        None
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+
+        // every boundary crossed, with which child it belongs to and
+        // whether it's going into that child
+        let mut events: Vec<(Intersection,bool,bool)> = Vec::new();
+        for &(child, from_right) in [(&self.left, false), (&self.right, true)].iter() {
+            for interval in child.intervals(ray) {
+                events.push((interval.entry, from_right, true));
+                events.push((interval.exit, from_right, false));
+            }
+        }
+        events.sort_by(|a, b| a.0.distance.total_cmp(&b.0.distance));
+
+        // how many of each child's intervals the ray is currently inside
+        let mut depth = [0, 0];
+        let mut entry: Option<Intersection> = None;
+        let mut intervals = Vec::new();
+
+        for (mut intersection, from_right, entering) in events {
+            let was_inside = self.operation.contains(depth[0] > 0, depth[1] > 0);
+            depth[from_right as usize] += if entering { 1 } else { -1 };
+            let is_inside = self.operation.contains(depth[0] > 0, depth[1] > 0);
+
+            if was_inside == is_inside {
+                continue;
+            }
+
+            intersection.csg_path = (intersection.csg_path << 1) | from_right as u64;
+
+            // the surface of a hole faces into the hole, which is out of
+            // what's left
+            if from_right && self.operation == CsgOperation::Difference && intersection.distance.is_finite() {
+                intersection.position = &intersection.position - &(&intersection.geometric_normal * 0.002); // keep the offset on the outside
+                intersection.normal = &intersection.normal * -1.0;
+                intersection.geometric_normal = &intersection.geometric_normal * -1.0;
+            }
+
+            if is_inside {
+                entry = Some(intersection);
+            } else if let Some(entry) = entry.take() {
+                intervals.push(Interval::new(entry, intersection));
+            }
+        }
+
+        intervals
+    }
+
+    /**
+     * Hits already carry their texture coordinates from whichever child
+     * they're on; for an arbitrary point this can only guess, so it asks
+     * the first child.
+     */
+    fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
+        self.left.texture_coordinate(point)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        match self.operation {
+            CsgOperation::Union => Some(self.left.bounds()?.union(&self.right.bounds()?)),
+            CsgOperation::Intersection => match (self.left.bounds(), self.right.bounds()) {
+                (Some(left), Some(right)) => Some(left.overlap(&right)),
+                (Some(bounds), None) | (None, Some(bounds)) => Some(bounds),
+                (None, None) => None,
+            },
+            CsgOperation::Difference => self.left.bounds(),
+        }
+    }
+
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+        let from_right = intersection.csg_path & 1 == 1;
+        intersection.csg_path >>= 1;
+
+        if from_right {
+            self.right.shade(intersection, world, settings, rng, bounces_remaining)
+        } else {
+            self.left.shade(intersection, world, settings, rng, bounces_remaining)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Vec3;
+    use crate::ray::Ray;
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+    use crate::cuboid::Cuboid;
+    use crate::object::{Object,ObjectEnum};
+    use crate::csg::{Csg,CsgOperation};
+
+    fn sphere(x: f32) -> ObjectEnum {
+        ObjectEnum::Sphere(Sphere::new(Vec3 { x, y: 0.0, z: -10.0 }, 1.0, Material::new()))
+    }
+
+    fn along_x(origin_x: f32) -> Ray {
+        Ray {
+            origin: Vec3 { x: origin_x, y: 0.0, z: -10.0 },
+            direction: Vec3 { x: 1.0, y: 0.0, z: 0.0 },
+        }
+    }
+
+    #[test]
+    fn test_operations() {
+        // two spheres overlapping between x = -0.5 and x = 0.5
+        let union = Csg::new(CsgOperation::Union, sphere(-0.5), sphere(0.5));
+        let intervals = union.intervals(&along_x(-5.0));
+        assert_eq!(intervals.len(), 1);
+        assert!((intervals[0].entry.distance - 3.5).abs() < 0.0001);
+        assert!((intervals[0].exit.distance - 6.5).abs() < 0.0001);
+
+        let intersection = Csg::new(CsgOperation::Intersection, sphere(-0.5), sphere(0.5));
+        let hit = intersection.intersection(&along_x(-5.0)).unwrap();
+        assert!((hit.distance - 4.5).abs() < 0.0001);
+        assert_eq!(hit.csg_path, 1); // the right sphere's surface
+
+        let difference = Csg::new(CsgOperation::Difference, sphere(-0.5), sphere(0.5));
+        let intervals = difference.intervals(&along_x(-5.0));
+        assert_eq!(intervals.len(), 1);
+        assert!((intervals[0].exit.distance - 4.5).abs() < 0.0001);
+        assert!((&intervals[0].exit.normal - &Vec3 { x: 1.0, y: 0.0, z: 0.0 }).len() < 0.0001);
+
+        // from inside the carved-out part, the first hit is the hole's wall
+        let hit = difference.intersection(&Ray {
+            origin: Vec3 { x: 0.0, y: 0.0, z: -10.0 },
+            direction: Vec3 { x: -1.0, y: 0.0, z: 0.0 },
+        }).unwrap();
+        assert!((hit.distance - 0.5).abs() < 0.0001);
+        assert!((&hit.normal - &Vec3 { x: 1.0, y: 0.0, z: 0.0 }).len() < 0.0001);
+    }
+
+    #[test]
+    fn test_from_objects() {
+        // a box with two holes through it
+        let cuboid = ObjectEnum::Cuboid(Cuboid::new(Vec3 { x: -3.0, y: -1.0, z: -11.0 }, Vec3 { x: 3.0, y: 1.0, z: -9.0 }, Material::new()));
+        let csg = Csg::from_objects(CsgOperation::Difference, vec![cuboid, sphere(-1.5), sphere(1.5)]).unwrap();
+
+        let intervals = csg.intervals(&along_x(-5.0));
+        assert_eq!(intervals.len(), 3);
+        assert!((intervals[1].entry.distance - 4.5).abs() < 0.0001);
+        assert!((intervals[1].exit.distance - 5.5).abs() < 0.0001);
+
+        let bounds = csg.bounds().unwrap();
+        assert_eq!(bounds.max, Vec3 { x: 3.0, y: 1.0, z: -9.0 });
+
+        assert!(Csg::from_objects(CsgOperation::Union, vec![sphere(0.0)]).is_none());
+    }
+}
@@ -0,0 +1,159 @@
+use rand::rngs::SmallRng;
+
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::object::Object;
+use crate::intersection::{Intersection,Interval};
+use crate::material::Material;
+use crate::illumination::Illumination;
+use crate::render_settings::RenderSettings;
+use crate::bounding_box::{BoundingBox,inverse_direction};
+use crate::world::World;
+
+/**
+ * A solid axis-aligned box (rotate it by wrapping it in a `Transformed`).
+ * Each face is textured with the full (0, 0) to (1, 1) range.
+ */
+pub struct Cuboid {
+    min: Vec3,
+    max: Vec3,
+    material: Material,
+}
+
+impl Cuboid {
+
+    pub fn new(min: Vec3, max: Vec3, material: Material) -> Self {
+        Self {
+            min: Vec3 { x: f32::min(min.x, max.x), y: f32::min(min.y, max.y), z: f32::min(min.z, max.z) },
+            max: Vec3 { x: f32::max(min.x, max.x), y: f32::max(min.y, max.y), z: f32::max(min.z, max.z) },
+            material,
+        }
+    }
+
+    /**
+     * Distances along the ray to where it enters and leaves the box
+     */
+    fn entry_and_exit(&self, ray: &Ray) -> Option<(f32,f32)> {
+        BoundingBox { min: self.min, max: self.max }.ray_span(ray, &inverse_direction(ray))
+    }
+
+    fn hit_at(&self, ray: &Ray, distance: f32) -> Intersection {
+        let position = &ray.origin + &(&ray.direction * distance);
+        let normal = self.normal_at(&position);
+
+        let mut intersection = Intersection::new(
+            distance,
+            &position + &(&normal * 0.001), // offset to avoid floating-point error
+            normal,
+            ray.direction,
+        );
+        intersection.uv = self.texture_coordinate(&position);
+
+        intersection
+    }
+
+    /**
+     * The outward normal of whichever face `point` is (nearly) on
+     */
+    fn normal_at(&self, point: &Vec3) -> Vec3 {
+        let center = &(&self.min + &self.max) * 0.5;
+        let half_size = &(&self.max - &self.min) * 0.5;
+        let relative = point - &center;
+
+        // how close to each face the point is, relative to the box's size
+        let x = relative.x / half_size.x;
+        let y = relative.y / half_size.y;
+        let z = relative.z / half_size.z;
+
+        if x.abs() >= y.abs() && x.abs() >= z.abs() {
+            Vec3 { x: x.signum(), y: 0.0, z: 0.0 }
+        } else if y.abs() >= z.abs() {
+            Vec3 { x: 0.0, y: y.signum(), z: 0.0 }
+        } else {
+            Vec3 { x: 0.0, y: 0.0, z: z.signum() }
+        }
+    }
+}
+
+impl Object for Cuboid {
+
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        let (near, far) = self.entry_and_exit(ray)?;
+        if far <= 0.0 {
+            return None;
+        }
+
+        // from inside, the hit is on the way out
+        Some(self.hit_at(ray, if near > 0.0 { near } else { far }))
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        match self.entry_and_exit(ray) {
+            Some((near, far)) => vec![Interval::new(self.hit_at(ray, near), self.hit_at(ray, far))],
+            None => Vec::new(),
+        }
+    }
+
+    fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
+        let normal = self.normal_at(point);
+        let size = &self.max - &self.min;
+        let relative = point - &self.min;
+
+        // the two axes along the face
+        if normal.x != 0.0 {
+            (relative.z / size.z, relative.y / size.y)
+        } else if normal.y != 0.0 {
+            (relative.x / size.x, relative.z / size.z)
+        } else {
+            (relative.x / size.x, relative.y / size.y)
+        }
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        Some(BoundingBox { min: self.min, max: self.max })
+    }
+
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+        self.material.shade(
+            intersection,
+            world,
+            settings,
+            rng,
+            bounces_remaining
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Vec3;
+    use crate::ray::Ray;
+    use crate::material::Material;
+    use crate::object::Object;
+    use crate::cuboid::Cuboid;
+
+    #[test]
+    fn test_intersection() {
+        let cuboid = Cuboid::new(Vec3 { x: -1.0, y: -1.0, z: -6.0 }, Vec3 { x: 1.0, y: 2.0, z: -4.0 }, Material::new());
+
+        let front = cuboid.intersection(&Ray {
+            origin: Vec3 { x: 0.5, y: 0.5, z: 0.0 },
+            direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+        }).unwrap();
+        assert!((front.distance - 4.0).abs() < 0.0001);
+        assert_eq!(front.normal, Vec3 { x: 0.0, y: 0.0, z: 1.0 });
+        assert!((front.uv.0 - 0.75).abs() < 0.0001 && (front.uv.1 - 0.5).abs() < 0.0001);
+
+        let inside = cuboid.intersection(&Ray {
+            origin: Vec3 { x: 0.0, y: 0.0, z: -5.0 },
+            direction: Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+        }).unwrap();
+        assert!((inside.distance - 2.0).abs() < 0.0001);
+        assert_eq!(inside.normal, Vec3 { x: 0.0, y: 1.0, z: 0.0 });
+
+        assert!(cuboid.intersection(&Ray {
+            origin: Vec3 { x: 2.0, y: 0.0, z: 0.0 },
+            direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+        }).is_none());
+    }
+}
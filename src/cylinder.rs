@@ -0,0 +1,195 @@
+use std::f32::consts::PI;
+
+use rand::rngs::SmallRng;
+
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::object::Object;
+use crate::intersection::{Intersection,Interval};
+use crate::material::Material;
+use crate::illumination::Illumination;
+use crate::render_settings::RenderSettings;
+use crate::bounding_box::BoundingBox;
+use crate::utils::{TWO_PI,solve_quadratic};
+use crate::world::World;
+
+// how close to a cap a point has to be to count as being on it
+const CAP_EPSILON: f32 = 0.002;
+
+/**
+ * A solid, capped cylinder standing upright (along +Y) on its base
+ * position. The side is textured with u going once around and v going from
+ * the bottom (0) to the top (1); the caps are mapped flat.
+ */
+pub struct Cylinder {
+    position: Vec3,
+    radius: f32,
+    height: f32,
+    material: Material,
+}
+
+impl Cylinder {
+
+    pub fn new(position: Vec3, radius: f32, height: f32, material: Material) -> Self {
+        Self {
+            position,
+            radius,
+            height,
+            material,
+        }
+    }
+
+    /**
+     * Everywhere the ray crosses the surface (at any distance), with the
+     * normal there
+     */
+    fn surface_hits(&self, ray: &Ray) -> Vec<(f32,Vec3)> {
+        let origin = &ray.origin - &self.position;
+        let direction = &ray.direction;
+
+        let mut hits: Vec<(f32,Vec3)> = Vec::new();
+
+        // the side
+        let a = direction.x * direction.x + direction.z * direction.z;
+        if a > 1e-12 {
+            let b = 2.0 * (origin.x * direction.x + origin.z * direction.z);
+            let c = origin.x * origin.x + origin.z * origin.z - self.radius * self.radius;
+
+            if let Some((t0, t1)) = solve_quadratic(a, b, c) {
+                for &t in [t0, t1].iter() {
+                    let y = origin.y + direction.y * t;
+                    if y >= 0.0 && y <= self.height {
+                        let x = origin.x + direction.x * t;
+                        let z = origin.z + direction.z * t;
+                        hits.push((t, Vec3 { x: x / self.radius, y: 0.0, z: z / self.radius }));
+                    }
+                }
+            }
+        }
+
+        // the caps
+        if direction.y != 0.0 {
+            for &(y, normal_y) in [(0.0, -1.0), (self.height, 1.0)].iter() {
+                let t = (y - origin.y) / direction.y;
+                let x = origin.x + direction.x * t;
+                let z = origin.z + direction.z * t;
+
+                if x * x + z * z <= self.radius * self.radius {
+                    hits.push((t, Vec3 { x: 0.0, y: normal_y, z: 0.0 }));
+                }
+            }
+        }
+
+        hits
+    }
+
+    fn hit_at(&self, ray: &Ray, distance: f32, normal: Vec3) -> Intersection {
+        let position = &ray.origin + &(&ray.direction * distance);
+
+        let mut intersection = Intersection::new(
+            distance,
+            &position + &(&normal * 0.001), // offset to avoid floating-point error
+            normal,
+            ray.direction,
+        );
+        intersection.uv = self.texture_coordinate(&position);
+
+        intersection
+    }
+}
+
+impl Object for Cylinder {
+
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        self.surface_hits(ray).into_iter()
+            .filter(|(distance, _)| *distance > 0.0)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(distance, normal)| self.hit_at(ray, distance, normal))
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let hits = self.surface_hits(ray);
+        if hits.len() < 2 {
+            return Vec::new();
+        }
+
+        // it's convex, so everything between the first and last hits is inside
+        let (entry_distance, entry_normal) = hits.iter().min_by(|a, b| a.0.total_cmp(&b.0)).unwrap();
+        let (exit_distance, exit_normal) = hits.iter().max_by(|a, b| a.0.total_cmp(&b.0)).unwrap();
+
+        vec![Interval::new(
+            self.hit_at(ray, *entry_distance, *entry_normal),
+            self.hit_at(ray, *exit_distance, *exit_normal),
+        )]
+    }
+
+    fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
+        let relative = point - &self.position;
+        let on_side = relative.x * relative.x + relative.z * relative.z >= (self.radius - CAP_EPSILON) * (self.radius - CAP_EPSILON);
+
+        if on_side && relative.y > CAP_EPSILON && relative.y < self.height - CAP_EPSILON {
+            (
+                (relative.z.atan2(relative.x) + PI) / TWO_PI,
+                relative.y / self.height,
+            )
+        } else {
+            (
+                (relative.x / self.radius + 1.0) / 2.0,
+                (relative.z / self.radius + 1.0) / 2.0,
+            )
+        }
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        Some(BoundingBox {
+            min: &self.position - &Vec3 { x: self.radius, y: 0.0, z: self.radius },
+            max: &self.position + &Vec3 { x: self.radius, y: self.height, z: self.radius },
+        })
+    }
+
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+        self.material.shade(
+            intersection,
+            world,
+            settings,
+            rng,
+            bounces_remaining
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Vec3;
+    use crate::ray::Ray;
+    use crate::material::Material;
+    use crate::object::Object;
+    use crate::cylinder::Cylinder;
+
+    #[test]
+    fn test_side_and_caps() {
+        let cylinder = Cylinder::new(Vec3 { x: 0.0, y: -1.0, z: -5.0 }, 1.0, 2.0, Material::new());
+
+        let side = cylinder.intersection(&Ray {
+            origin: Vec3 { x: 0.0, y: 0.5, z: 0.0 },
+            direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+        }).unwrap();
+        assert!((side.distance - 4.0).abs() < 0.0001);
+        assert!((&side.normal - &Vec3 { x: 0.0, y: 0.0, z: 1.0 }).len() < 0.0001);
+        assert!((side.uv.1 - 0.75).abs() < 0.0001);
+
+        let top = cylinder.intersection(&Ray {
+            origin: Vec3 { x: 0.5, y: 5.0, z: -5.0 },
+            direction: Vec3 { x: 0.0, y: -1.0, z: 0.0 },
+        }).unwrap();
+        assert!((top.distance - 4.0).abs() < 0.0001);
+        assert_eq!(top.normal, Vec3 { x: 0.0, y: 1.0, z: 0.0 });
+        assert!((top.uv.0 - 0.75).abs() < 0.0001);
+
+        // passes over the top
+        assert!(cylinder.intersection(&Ray {
+            origin: Vec3 { x: 0.0, y: 1.5, z: 0.0 },
+            direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+        }).is_none());
+    }
+}
@@ -0,0 +1,128 @@
+use std::f32::consts::PI;
+
+use rand::rngs::SmallRng;
+
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::object::Object;
+use crate::intersection::{Intersection,Interval};
+use crate::material::Material;
+use crate::illumination::Illumination;
+use crate::render_settings::RenderSettings;
+use crate::bounding_box::BoundingBox;
+use crate::utils::{TWO_PI,plane_intersection,perpendicular};
+use crate::world::World;
+
+/**
+ * A flat, filled circle. Texture coordinates are polar: u goes once around
+ * the center, and v goes from the center (0) out to the rim (1).
+ */
+pub struct Disk {
+    position: Vec3,
+    normal: Vec3,
+    radius: f32,
+    material: Material,
+
+    // which way u = 0 points
+    tangent: Vec3,
+    bitangent: Vec3,
+}
+
+impl Disk {
+
+    pub fn new(position: Vec3, normal: Vec3, radius: f32, material: Material) -> Self {
+        let normal = normal.normalized();
+        let tangent = perpendicular(&normal);
+
+        Self {
+            position,
+            radius,
+            material,
+            bitangent: normal.cross(&tangent),
+            tangent,
+            normal,
+        }
+    }
+}
+
+impl Object for Disk {
+
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        plane_intersection(&self.position, &self.normal, ray)
+            .and_then(|mut intersection| {
+                let hit = &ray.origin + &(&ray.direction * intersection.distance);
+
+                if (&hit - &self.position).len_squared() <= self.radius * self.radius {
+                    intersection.uv = self.texture_coordinate(&hit);
+                    Some(intersection)
+                } else {
+                    None
+                }
+            })
+    }
+
+    fn intervals(&self, _ray: &Ray) -> Vec<Interval> {
+        // flat, so there's no inside
+        Vec::new()
+    }
+
+    fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
+        let relative = point - &self.position;
+        let x = relative.dot(&self.tangent);
+        let y = relative.dot(&self.bitangent);
+
+        (
+            (y.atan2(x) + PI) / TWO_PI,
+            f32::min((x * x + y * y).sqrt() / self.radius, 1.0),
+        )
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+
+        // how far the rim reaches along each axis
+        let extent = Vec3 {
+            x: self.radius * (1.0 - self.normal.x * self.normal.x).max(0.0).sqrt(),
+            y: self.radius * (1.0 - self.normal.y * self.normal.y).max(0.0).sqrt(),
+            z: self.radius * (1.0 - self.normal.z * self.normal.z).max(0.0).sqrt(),
+        };
+
+        Some(BoundingBox {
+            min: &self.position - &extent,
+            max: &self.position + &extent,
+        })
+    }
+
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+        self.material.shade(
+            intersection,
+            world,
+            settings,
+            rng,
+            bounces_remaining
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Vec3;
+    use crate::ray::Ray;
+    use crate::material::Material;
+    use crate::object::Object;
+    use crate::disk::Disk;
+
+    #[test]
+    fn test_intersection_and_bounds() {
+        let disk = Disk::new(Vec3 { x: 0.0, y: 0.0, z: -5.0 }, Vec3 { x: 0.0, y: 0.0, z: 1.0 }, 2.0, Material::new());
+        let forward = Vec3 { x: 0.0, y: 0.0, z: -1.0 };
+
+        let hit = disk.intersection(&Ray { origin: Vec3 { x: 1.0, y: 0.0, z: 0.0 }, direction: forward }).unwrap();
+        assert!((hit.distance - 5.0).abs() < 0.0001);
+        assert!((hit.uv.1 - 0.5).abs() < 0.0001);
+
+        assert!(disk.intersection(&Ray { origin: Vec3 { x: 1.5, y: 1.5, z: 0.0 }, direction: forward }).is_none());
+
+        let bounds = disk.bounds().unwrap();
+        assert!((&bounds.max - &Vec3 { x: 2.0, y: 2.0, z: -5.0 }).len() < 0.0001);
+    }
+}
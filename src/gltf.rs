@@ -0,0 +1,1132 @@
+/*!
+ * Loader for glTF 2.0 files (https://www.khronos.org/gltf/), either as
+ * JSON (`.gltf`, with buffers and images embedded as data URIs or in
+ * files next to it) or binary (`.glb`).
+ *
+ * Each node with a mesh becomes its own `Mesh`, with the node's transform
+ * (and its parents') baked in. Metallic-roughness materials are mapped
+ * onto `Material` as closely as it allows:
+ * - the base color becomes the albedo
+ * - metals and glossy surfaces get a specular texture of 1 - roughness
+ * - the normal texture becomes a tangent-space normal map
+ * - emissive materials become lights
+ * - `BLEND` and `MASK` alpha, and `KHR_materials_transmission` (with
+ *   `KHR_materials_ior`), become opacity and index of refraction
+ *
+ * Cameras are placed by their nodes, and `KHR_lights_punctual` point and
+ * spot lights become small glowing spheres (so spot lights shine every
+ * way). Directional lights, sparse accessors, skins and morph targets
+ * aren't supported.
+ */
+
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::collections::{HashMap,HashSet};
+use std::f32::consts::PI;
+
+use serde_json::Value;
+use image::{DynamicImage,GenericImageView,ImageBuffer,Rgb,Rgba};
+
+use crate::vec3::Vec3;
+use crate::matrix::{Matrix,IDENTITY};
+use crate::quaternion::Quaternion;
+use crate::mesh::{Mesh,Face,triangle_normal};
+use crate::material::Material;
+use crate::texture::Texture;
+use crate::color::Color;
+use crate::camera::{Camera,Projection};
+use crate::object::ObjectEnum;
+use crate::sphere::Sphere;
+use crate::load_error::LoadError;
+
+// binary files are a header and then chunks, all little-endian
+const GLB_MAGIC: &[u8] = b"glTF";
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+// point lights need some surface to glow from
+const LIGHT_RADIUS: f32 = 0.25;
+
+// accessors without a buffer view are all zeros, so nothing in the file
+// limits how many elements they claim to have
+const MAX_ZERO_ACCESSOR_COUNT: usize = 1 << 24;
+
+/**
+ * Everything in a glTF file's scene that can be rendered
+ */
+pub struct Gltf {
+
+    // one for each node with a mesh, named after the node (or its mesh)
+    pub meshes: Vec<(String,Mesh)>,
+    pub cameras: Vec<Camera>,
+    pub lights: Vec<ObjectEnum>,
+}
+
+impl Gltf {
+
+    /**
+     * Load a `.gltf` or `.glb` file. Primitives without a material get
+     * `default_material`, like faces in an OBJ file without one.
+     */
+    pub fn load(path: &str, default_material: Option<Material>) -> Result<Self,LoadError> {
+        let data = fs::read(path).map_err(|err| LoadError::Io {
+            path: String::from(path),
+            message: err.to_string(),
+        })?;
+
+        println!("Loading gltf...");
+
+        let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+
+        Self::parse(&data, base_dir, default_material).map_err(|err| err.in_file(path))
+    }
+
+    /**
+     * Either kind of file, already read. External buffers and images are
+     * loaded relative to `base_dir`.
+     */
+    pub fn parse(data: &[u8], base_dir: &Path, default_material: Option<Material>) -> Result<Self,LoadError> {
+        let (json, bin) = if data.starts_with(GLB_MAGIC) {
+            split_glb(data)?
+        } else {
+            (data, None)
+        };
+        let json: Value = serde_json::from_slice(json)
+            .map_err(|err| LoadError::invalid(&format!("not valid JSON: {}", err)))?;
+
+        let version = json.pointer("/asset/version").and_then(Value::as_str).unwrap_or("");
+        if !version.starts_with("2.") {
+            return Err(LoadError::invalid(&format!("only glTF 2.0 is supported, not \"{}\"", version)));
+        }
+
+        let mut document = Document::new(&json, bin, base_dir)?;
+
+        let mut meshes = Vec::new();
+        let mut cameras = Vec::new();
+        let mut lights = Vec::new();
+        for (node_index, transform) in placed_nodes(&json)? {
+            let node = element(&json, "nodes", node_index)?;
+
+            if let Some(mesh_index) = index(node, "mesh")? {
+                let name = node.get("name")
+                    .or_else(|| element(&json, "meshes", mesh_index).ok().and_then(|mesh| mesh.get("name")))
+                    .and_then(Value::as_str)
+                    .map(String::from)
+                    .unwrap_or_else(|| format!("mesh {}", mesh_index));
+
+                println!("Building BVH for {}...", name);
+
+                if let Some(mesh) = document.build_mesh(mesh_index, &transform, &default_material)? {
+                    meshes.push((name, mesh));
+                }
+            }
+
+            if let Some(camera_index) = index(node, "camera")? {
+                cameras.push(build_camera(element(&json, "cameras", camera_index)?, &transform)?);
+            }
+
+            if let Some(light_index) = node.pointer("/extensions/KHR_lights_punctual/light").and_then(Value::as_u64) {
+                let light = json.pointer(&format!("/extensions/KHR_lights_punctual/lights/{}", light_index))
+                    .ok_or_else(|| LoadError::invalid(&format!("there's no light {}", light_index)))?;
+
+                lights.extend(build_light(light, &transform));
+            }
+        }
+
+        Ok(Self { meshes, cameras, lights })
+    }
+}
+
+/**
+ * The JSON and binary chunks of a `.glb` file
+ */
+fn split_glb(data: &[u8]) -> Result<(&[u8],Option<&[u8]>),LoadError> {
+    let word = |offset: usize| data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+
+    if word(4) != Some(2) {
+        return Err(LoadError::invalid("only version 2 of the binary format is supported"));
+    }
+    let length = (word(8).unwrap_or(0) as usize).min(data.len());
+
+    let mut json = None;
+    let mut bin = None;
+    let mut offset = 12;
+    while offset + 8 <= length {
+        let chunk_length = word(offset).unwrap_or(0) as usize;
+        let chunk_type = word(offset + 4).unwrap_or(0);
+        let chunk = data.get(offset + 8..offset + 8 + chunk_length)
+            .ok_or_else(|| LoadError::invalid("a chunk runs past the end of the file"))?;
+
+        // any other kinds of chunk are meant to be skipped
+        match chunk_type {
+            CHUNK_JSON if json.is_none() => json = Some(chunk),
+            CHUNK_BIN if bin.is_none() => bin = Some(chunk),
+            _ => (),
+        }
+
+        offset += 8 + chunk_length;
+    }
+
+    let json = json.ok_or_else(|| LoadError::invalid("the file has no JSON chunk"))?;
+
+    Ok((json, bin))
+}
+
+/**
+ * Every node in the scene, with its transform into world space, parents
+ * before their children
+ */
+fn placed_nodes(json: &Value) -> Result<Vec<(usize,Matrix)>,LoadError> {
+    let nodes = array(json, "nodes");
+
+    let roots: Vec<usize> = if array(json, "scenes").is_empty() {
+
+        // without any scenes, every node that isn't a child is shown
+        let children: HashSet<u64> = nodes.iter()
+            .flat_map(|node| array(node, "children"))
+            .filter_map(Value::as_u64)
+            .collect();
+
+        (0..nodes.len()).filter(|&node| !children.contains(&(node as u64))).collect()
+    } else {
+        let scene = element(json, "scenes", index(json, "scene")?.unwrap_or(0))?;
+
+        indices(scene, "nodes")?
+    };
+
+    let mut placed = Vec::new();
+    let mut open: Vec<(usize,Matrix,usize)> = roots.into_iter().rev()
+        .map(|node| (node, IDENTITY, 0))
+        .collect();
+    while let Some((node_index, parent, depth)) = open.pop() {
+        if depth > nodes.len() {
+            return Err(LoadError::invalid("the node hierarchy has a cycle in it"));
+        }
+
+        let node = element(json, "nodes", node_index)?;
+        let transform = local_transform(node).then(&parent);
+        for child in indices(node, "children")?.into_iter().rev() {
+            open.push((child, transform, depth + 1));
+        }
+
+        placed.push((node_index, transform));
+    }
+
+    Ok(placed)
+}
+
+/**
+ * A node's transform relative to its parent: either a matrix, or a
+ * scale, then rotation, then translation
+ */
+fn local_transform(node: &Value) -> Matrix {
+    if let Some(values) = node.get("matrix").and_then(Value::as_array).filter(|values| values.len() == 16) {
+        let mut matrix = Matrix::new();
+
+        // stored a column at a time
+        for col in 0..4 {
+            for row in 0..4 {
+                matrix.set(row, col, values[col * 4 + row].as_f64().unwrap_or(0.0) as f32);
+            }
+        }
+
+        return matrix;
+    }
+
+    let translation = numbers(node, "translation", &[0.0, 0.0, 0.0]);
+    let rotation = numbers(node, "rotation", &[0.0, 0.0, 0.0, 1.0]);
+    let scale = numbers(node, "scale", &[1.0, 1.0, 1.0]);
+
+    Matrix::scale(&Vec3 { x: scale[0], y: scale[1], z: scale[2] })
+        .then(&Matrix::from_quaternion(&Quaternion { x: rotation[0], y: rotation[1], z: rotation[2], w: rotation[3] }))
+        .then(&Matrix::translation(&Vec3 { x: translation[0], y: translation[1], z: translation[2] }))
+}
+
+/**
+ * glTF cameras look down their node's -Z, with +Y up
+ */
+fn build_camera(camera: &Value, transform: &Matrix) -> Result<Camera,LoadError> {
+    let position = Vec3::new().transform_point(transform);
+    let forward = Vec3 { x: 0.0, y: 0.0, z: -1.0 }.transform_direction(transform).normalized();
+    let up = Vec3 { x: 0.0, y: 1.0, z: 0.0 }.transform_direction(transform).normalized();
+
+    let mut result = Camera::look_at(position, &position + &forward, up, 60.0);
+    match camera.get("type").and_then(Value::as_str) {
+        Some("perspective") => {
+            let perspective = camera.get("perspective").unwrap_or(&Value::Null);
+
+            result.fov = number(perspective, "yfov", result.fov);
+            result.aspect_ratio = perspective.get("aspectRatio").and_then(Value::as_f64).map(|aspect| aspect as f32);
+        },
+        Some("orthographic") => {
+            let orthographic = camera.get("orthographic").unwrap_or(&Value::Null);
+
+            // magnifications are half the view's width and height
+            let ymag = number(orthographic, "ymag", 1.0);
+            let xmag = number(orthographic, "xmag", ymag);
+            result.projection = Projection::Orthographic { height: 2.0 * ymag };
+            result.aspect_ratio = Some(xmag / ymag).filter(|aspect| aspect.is_finite() && *aspect > 0.0);
+        },
+        _ => return Err(LoadError::invalid("a camera isn't perspective or orthographic")),
+    }
+
+    Ok(result)
+}
+
+/**
+ * A sphere that looks as bright, from outside, as a point light of the
+ * light's intensity (in candela)
+ */
+fn build_light(light: &Value, transform: &Matrix) -> Option<ObjectEnum> {
+    let kind = light.get("type").and_then(Value::as_str).unwrap_or("unknown");
+    if kind != "point" && kind != "spot" {
+        println!("WARNING: Skipping a {} light, which isn't supported", kind);
+        return None;
+    }
+
+    let color = numbers(light, "color", &[1.0, 1.0, 1.0]);
+    let intensity = number(light, "intensity", 1.0);
+
+    let mut material = Material::new();
+    material.texture_emission_color = Some(Texture::Solid(Color(color[0], color[1], color[2])));
+    material.texture_emission_intensity = Some(Texture::Solid(Color::gray(intensity / (PI * LIGHT_RADIUS * LIGHT_RADIUS))));
+
+    Some(ObjectEnum::Sphere(Sphere::new(Vec3::new().transform_point(transform), LIGHT_RADIUS, material)))
+}
+
+/**
+ * A glTF file's JSON, with its buffers loaded, and the images and
+ * materials that have been converted so far (to share them between
+ * meshes)
+ */
+struct Document<'a> {
+    json: &'a Value,
+    base_dir: &'a Path,
+    buffers: Vec<Vec<u8>>,
+
+    // images that couldn't be loaded are `None`, having been warned about
+    images: HashMap<usize,Option<Arc<DynamicImage>>>,
+    materials: HashMap<usize,Material>,
+}
+
+impl<'a> Document<'a> {
+
+    /**
+     * Loads every buffer up front; a buffer without a URI is the binary
+     * chunk of a `.glb` file
+     */
+    fn new(json: &'a Value, bin: Option<&[u8]>, base_dir: &'a Path) -> Result<Self,LoadError> {
+        let buffers = array(json, "buffers").iter().enumerate()
+            .map(|(buffer_index, buffer)| {
+                let data = match buffer.get("uri").and_then(Value::as_str) {
+                    Some(uri) => load_uri(uri, base_dir)?,
+                    None if buffer_index == 0 => bin.map(<[u8]>::to_vec)
+                        .ok_or_else(|| LoadError::invalid("buffer 0 has no uri, and there's no binary chunk"))?,
+                    None => return Err(LoadError::invalid(&format!("buffer {} has no uri", buffer_index))),
+                };
+
+                let byte_length = buffer.get("byteLength").and_then(Value::as_u64).unwrap_or(0) as usize;
+                if data.len() < byte_length {
+                    return Err(LoadError::invalid(&format!("buffer {} is shorter than its byteLength", buffer_index)));
+                }
+
+                Ok(data)
+            })
+            .collect::<Result<Vec<Vec<u8>>,LoadError>>()?;
+
+        Ok(Self {
+            json,
+            base_dir,
+            buffers,
+            images: HashMap::new(),
+            materials: HashMap::new(),
+        })
+    }
+
+    fn buffer_view(&self, view_index: usize) -> Result<&[u8],LoadError> {
+        let view = element(self.json, "bufferViews", view_index)?;
+        let buffer_index = index(view, "buffer")?
+            .ok_or_else(|| LoadError::invalid(&format!("buffer view {} has no buffer", view_index)))?;
+        let buffer = self.buffers.get(buffer_index)
+            .ok_or_else(|| LoadError::invalid(&format!("there's no buffers {}", buffer_index)))?;
+
+        let offset = view.get("byteOffset").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let length = view.get("byteLength").and_then(Value::as_u64)
+            .map(|length| length as usize)
+            .unwrap_or_else(|| buffer.len().saturating_sub(offset));
+
+        offset.checked_add(length)
+            .and_then(|end| buffer.get(offset..end))
+            .ok_or_else(|| LoadError::invalid(&format!("buffer view {} runs past the end of its buffer", view_index)))
+    }
+
+    /**
+     * An accessor's elements, each of `components` numbers, one after
+     * another. Normalized integers come out as 0 to 1 (or -1 to 1).
+     */
+    fn read_accessor(&self, accessor_index: usize, components: usize) -> Result<Vec<f64>,LoadError> {
+        let accessor = element(self.json, "accessors", accessor_index)?;
+        let error = |message: &str| LoadError::invalid(&format!("accessor {} {}", accessor_index, message));
+
+        if accessor.get("sparse").is_some() {
+            return Err(error("is sparse, which isn't supported"));
+        }
+
+        let actual_components = match accessor.get("type").and_then(Value::as_str) {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            _ => return Err(error("doesn't have a scalar or vector type")),
+        };
+        if actual_components != components {
+            return Err(error(&format!("should have {} components, not {}", components, actual_components)));
+        }
+
+        let component_type = accessor.get("componentType").and_then(Value::as_u64).unwrap_or(0);
+        let component_size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            _ => return Err(error("has an unknown componentType")),
+        };
+        let normalized = accessor.get("normalized").and_then(Value::as_bool).unwrap_or(false);
+        let count = accessor.get("count").and_then(Value::as_u64)
+            .ok_or_else(|| error("has no count"))? as usize;
+
+        // without a buffer view, every element is zero
+        let view_index = match index(accessor, "bufferView")? {
+            Some(view_index) => view_index,
+            None if count > MAX_ZERO_ACCESSOR_COUNT => return Err(error("has too many elements for one without a buffer view")),
+            None => return Ok(vec![0.0; count * components]),
+        };
+        let data = self.buffer_view(view_index)?;
+
+        let element_size = components * component_size;
+        let stride = element(self.json, "bufferViews", view_index)?
+            .get("byteStride").and_then(Value::as_u64)
+            .map(|stride| stride as usize)
+            .unwrap_or(element_size);
+        if stride < element_size {
+            return Err(error("has elements that overlap (its byteStride is too small)"));
+        }
+        let offset = accessor.get("byteOffset").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let end = match count {
+            0 => Some(offset),
+            _ => stride.checked_mul(count - 1)
+                .and_then(|last| last.checked_add(offset))
+                .and_then(|last| last.checked_add(element_size)),
+        };
+        if end.is_none_or(|end| end > data.len()) {
+            return Err(error("runs past the end of its buffer view"));
+        }
+
+        let mut values = Vec::with_capacity(count * components);
+        for element_index in 0..count {
+            for component in 0..components {
+                let start = offset + element_index * stride + component * component_size;
+                values.push(read_component(&data[start..start + component_size], component_type, normalized));
+            }
+        }
+
+        Ok(values)
+    }
+
+    /**
+     * One of a primitive's attributes, if it has it, checking that there's
+     * one for each vertex
+     */
+    fn read_attribute(&self, attributes: &Value, name: &str, components: usize, vertex_count: usize) -> Result<Option<Vec<f64>>,LoadError> {
+        let accessor_index = match index(attributes, name)? {
+            Some(accessor_index) => accessor_index,
+            None => return Ok(None),
+        };
+
+        let values = self.read_accessor(accessor_index, components)?;
+        if values.len() != vertex_count * components {
+            return Err(LoadError::invalid(&format!("{} has a different number of elements than POSITION", name)));
+        }
+
+        Ok(Some(values))
+    }
+
+    /**
+     * One node's mesh, moved into place. Meshes that don't have any
+     * triangles (only points or lines) are `None`.
+     */
+    fn build_mesh(&mut self, mesh_index: usize, transform: &Matrix, default_material: &Option<Material>) -> Result<Option<Mesh>,LoadError> {
+        let json = self.json;
+        let mesh = element(json, "meshes", mesh_index)?;
+
+        // normals don't move, and stretch the opposite way to the surface
+        let normal_transform = transform.normal_matrix().unwrap_or(*transform);
+
+        // mirroring turns faces inside out, and their tangent frames with them
+        let mirrored = transform.determinant() < 0.0;
+
+        let mut vertices = Vec::new();
+        let mut normals = Vec::new();
+        let mut uv_coords = Vec::new();
+        let mut tangents = Vec::new();
+        let mut faces = Vec::new();
+
+        // just the materials this mesh uses
+        let mut materials: Vec<Material> = Vec::new();
+        let mut material_indices: HashMap<usize,usize> = HashMap::new();
+
+        for (primitive_index, primitive) in array(mesh, "primitives").iter().enumerate() {
+            let mode = primitive.get("mode").and_then(Value::as_u64).unwrap_or(4);
+            if !(4..=6).contains(&mode) {
+                println!("WARNING: Skipping primitive {} of mesh {}, which isn't made of triangles", primitive_index, mesh_index);
+                continue;
+            }
+
+            let attributes = primitive.get("attributes").unwrap_or(&Value::Null);
+            let position_accessor = index(attributes, "POSITION")?
+                .ok_or_else(|| LoadError::invalid(&format!("primitive {} of mesh {} has no POSITION", primitive_index, mesh_index)))?;
+            let positions: Vec<Vec3> = self.read_accessor(position_accessor, 3)?
+                .chunks_exact(3)
+                .map(|p| Vec3 { x: p[0] as f32, y: p[1] as f32, z: p[2] as f32 })
+                .collect();
+            let count = positions.len();
+
+            let primitive_normals: Option<Vec<Vec3>> = self.read_attribute(attributes, "NORMAL", 3, count)?
+                .map(|values| values.chunks_exact(3)
+                    .map(|n| Vec3 { x: n[0] as f32, y: n[1] as f32, z: n[2] as f32 })
+                    .collect());
+
+            // meshes count v up from the bottom of the image, like OBJ files
+            let primitive_uvs: Option<Vec<(f32,f32)>> = self.read_attribute(attributes, "TEXCOORD_0", 2, count)?
+                .map(|values| values.chunks_exact(2)
+                    .map(|uv| (uv[0] as f32, 1.0 - uv[1] as f32))
+                    .collect());
+
+            let primitive_tangents: Option<Vec<(Vec3,f32)>> = self.read_attribute(attributes, "TANGENT", 4, count)?
+                .map(|values| values.chunks_exact(4)
+                    .map(|t| (Vec3 { x: t[0] as f32, y: t[1] as f32, z: t[2] as f32 }, if t[3] < 0.0 { -1.0 } else { 1.0 }))
+                    .collect());
+
+            let indices: Vec<usize> = match index(primitive, "indices")? {
+                Some(accessor_index) => self.read_accessor(accessor_index, 1)?.into_iter().map(|i| i as usize).collect(),
+                None => (0..count).collect(),
+            };
+            if indices.iter().any(|&i| i >= count) {
+                return Err(LoadError::invalid(&format!("primitive {} of mesh {} has an index out of range", primitive_index, mesh_index)));
+            }
+            let triangles = triangles(&indices, mode);
+
+            let material = match index(primitive, "material")? {
+                Some(material_index) => Some(match material_indices.get(&material_index) {
+                    Some(&local_index) => local_index,
+                    None => {
+                        materials.push(self.material(material_index)?);
+                        material_indices.insert(material_index, materials.len() - 1);
+                        materials.len() - 1
+                    }
+                }),
+                None => None,
+            };
+
+            // normal maps need tangents, even if the file doesn't have them
+            let has_normal_map = material.is_some_and(|material| materials[material].texture_normal.is_some());
+            let primitive_tangents = match (primitive_tangents, &primitive_normals, &primitive_uvs) {
+                (Some(primitive_tangents), _, _) => Some(primitive_tangents),
+                (None, Some(primitive_normals), Some(primitive_uvs)) if has_normal_map =>
+                    Some(generate_tangents(&positions, primitive_normals, primitive_uvs, &triangles)),
+                _ => None,
+            };
+
+            let vertex_offset = vertices.len();
+            let normal_offset = normals.len();
+            let uv_offset = uv_coords.len();
+            let tangent_offset = tangents.len();
+
+            vertices.extend(positions.iter().map(|position| position.transform_point(transform)));
+            if let Some(primitive_normals) = &primitive_normals {
+                normals.extend(primitive_normals.iter().map(|normal| normal.transform_direction(&normal_transform).normalized()));
+            }
+            if let Some(primitive_uvs) = &primitive_uvs {
+                uv_coords.extend(primitive_uvs);
+            }
+            if let Some(primitive_tangents) = &primitive_tangents {
+                tangents.extend(primitive_tangents.iter().map(|(tangent, w)| (
+                    tangent.transform_direction(transform).normalized(),
+                    if mirrored { -w } else { *w },
+                )));
+            }
+
+            for (a, b, c) in triangles {
+                let (a, b, c) = if mirrored { (a, c, b) } else { (a, b, c) };
+                let corners = |offset: usize| (offset + a, offset + b, offset + c);
+
+                faces.push(Face {
+                    v0: vertex_offset + a,
+                    v1: vertex_offset + b,
+                    v2: vertex_offset + c,
+
+                    mat: material,
+                    normal: triangle_normal(&vertices[vertex_offset + a], &vertices[vertex_offset + b], &vertices[vertex_offset + c]),
+                    vertex_normals: primitive_normals.as_ref().map(|_| corners(normal_offset)),
+                    uvs: primitive_uvs.as_ref().map(|_| corners(uv_offset)),
+                    smoothing_group: None,
+                    vertex_tangents: primitive_tangents.as_ref().map(|_| corners(tangent_offset)),
+                });
+            }
+        }
+
+        if faces.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Mesh::new(materials, default_material.clone(), vertices, normals, faces, uv_coords, tangents)))
+    }
+
+    fn material(&mut self, material_index: usize) -> Result<Material,LoadError> {
+        if let Some(material) = self.materials.get(&material_index) {
+            return Ok(material.clone());
+        }
+
+        let material = self.convert_material(material_index)?;
+        self.materials.insert(material_index, material.clone());
+
+        Ok(material)
+    }
+
+    fn convert_material(&mut self, material_index: usize) -> Result<Material,LoadError> {
+        let json = self.json;
+        let material = element(json, "materials", material_index)?;
+        let pbr = material.get("pbrMetallicRoughness").unwrap_or(&Value::Null);
+        let extensions = material.get("extensions").unwrap_or(&Value::Null);
+
+        // textures are multiplied by their factors
+        let base_color = numbers(pbr, "baseColorFactor", &[1.0, 1.0, 1.0, 1.0]);
+        let base_color_image = self.texture_image(pbr.get("baseColorTexture"))?;
+        let texture_albedo = match &base_color_image {
+            Some(image) if base_color[..3] == [1.0, 1.0, 1.0] => Texture::Image(image.clone()),
+            Some(image) => mapped_image(image, |pixel| Color(
+                u8_to_f32(pixel[0]) * base_color[0],
+                u8_to_f32(pixel[1]) * base_color[1],
+                u8_to_f32(pixel[2]) * base_color[2],
+            )),
+            None => Texture::Solid(Color(base_color[0], base_color[1], base_color[2])),
+        };
+
+        // roughness is in the green channel, and metalness in the blue
+        let metallic = number(pbr, "metallicFactor", 1.0);
+        let roughness = number(pbr, "roughnessFactor", 1.0);
+        let texture_specular = match self.texture_image(pbr.get("metallicRoughnessTexture"))? {
+            Some(image) => Some(mapped_image(&image, |pixel| Color::gray(specularity(
+                u8_to_f32(pixel[2]) * metallic,
+                u8_to_f32(pixel[1]) * roughness,
+            )))),
+            None => Some(specularity(metallic, roughness))
+                .filter(|&specularity| specularity > 0.0)
+                .map(|specularity| Texture::Solid(Color::gray(specularity))),
+        };
+
+        let texture_normal = self.texture_image(material.get("normalTexture"))?.map(Texture::Image);
+
+        // emission is split into a color and how bright it is
+        let emissive = numbers(material, "emissiveFactor", &[0.0, 0.0, 0.0]);
+        let emissive_strength = extensions.get("KHR_materials_emissive_strength")
+            .map_or(1.0, |extension| number(extension, "emissiveStrength", 1.0));
+        let brightest = emissive[0].max(emissive[1]).max(emissive[2]);
+        let (texture_emission_color, texture_emission_intensity) = if brightest > 0.0 {
+            let color = match self.texture_image(material.get("emissiveTexture"))? {
+                Some(image) => Texture::Image(image),
+                None => Texture::Solid(Color(emissive[0] / brightest, emissive[1] / brightest, emissive[2] / brightest)),
+            };
+
+            (Some(color), Some(Texture::Solid(Color::gray(brightest * emissive_strength))))
+        } else {
+            (None, None)
+        };
+
+        let transmission = extensions.get("KHR_materials_transmission")
+            .map_or(0.0, |extension| number(extension, "transmissionFactor", 0.0));
+        let (texture_opacity, index_of_refraction) = if transmission > 0.0 {
+            let index_of_refraction = extensions.get("KHR_materials_ior")
+                .map_or(1.5, |extension| number(extension, "ior", 1.5));
+
+            (Some(Texture::Solid(Color::gray(1.0 - transmission))), Some(index_of_refraction))
+        } else {
+            let opacity = match material.get("alphaMode").and_then(Value::as_str) {
+                Some("BLEND") => alpha_opacity(&base_color_image, base_color[3], None),
+                Some("MASK") => alpha_opacity(&base_color_image, base_color[3], Some(number(material, "alphaCutoff", 0.5))),
+                _ => None,
+            };
+
+            (opacity, None)
+        };
+
+        Ok(Material {
+            texture_albedo: Some(texture_albedo),
+            texture_specular,
+            texture_normal,
+            texture_emission_color,
+            texture_emission_intensity,
+            texture_opacity,
+            index_of_refraction,
+        })
+    }
+
+    /**
+     * The image a material's texture refers to, if it has one that could
+     * be loaded
+     */
+    fn texture_image(&mut self, texture_info: Option<&Value>) -> Result<Option<Arc<DynamicImage>>,LoadError> {
+        let texture_info = match texture_info {
+            Some(texture_info) => texture_info,
+            None => return Ok(None),
+        };
+
+        let texture_index = index(texture_info, "index")?
+            .ok_or_else(|| LoadError::invalid("a material's texture has no index"))?;
+
+        // textures can also come from extensions (like KTX2), which aren't
+        // supported
+        let image_index = match index(element(self.json, "textures", texture_index)?, "source")? {
+            Some(image_index) => image_index,
+            None => return Ok(None),
+        };
+
+        if !self.images.contains_key(&image_index) {
+            let image = self.load_image(image_index)?;
+            self.images.insert(image_index, image);
+        }
+
+        Ok(self.images[&image_index].clone())
+    }
+
+    /**
+     * An image that can't be read or decoded only gets a warning, like a
+     * missing texture in an MTL file
+     */
+    fn load_image(&self, image_index: usize) -> Result<Option<Arc<DynamicImage>>,LoadError> {
+        let image = element(self.json, "images", image_index)?;
+        let uri = image.get("uri").and_then(Value::as_str);
+
+        let data = match (uri, index(image, "bufferView")?) {
+            (Some(uri), _) => load_uri(uri, self.base_dir),
+            (None, Some(view_index)) => self.buffer_view(view_index).map(<[u8]>::to_vec),
+            (None, None) => return Err(LoadError::invalid(&format!("image {} has no uri or bufferView", image_index))),
+        };
+
+        let decoded = data.and_then(|data| image::load_from_memory(&data).map_err(|err| LoadError::Image {
+            path: match uri {
+                Some(uri) if !uri.starts_with("data:") => String::from(uri),
+                _ => format!("image {}", image_index),
+            },
+            message: err.to_string(),
+        }));
+
+        match decoded {
+            Ok(decoded) => Ok(Some(Arc::new(decoded))),
+            Err(err) => {
+                println!("WARNING: {}", err);
+                Ok(None)
+            }
+        }
+    }
+}
+
+/**
+ * The data a buffer or image URI points to: either embedded in a base64
+ * data URI, or a file relative to the glTF file
+ */
+fn load_uri(uri: &str, base_dir: &Path) -> Result<Vec<u8>,LoadError> {
+    if uri.starts_with("data:") {
+        let comma = uri.find(',').ok_or_else(|| LoadError::invalid("a data URI has no data"))?;
+        if !uri[..comma].ends_with(";base64") {
+            return Err(LoadError::invalid("only base64 data URIs are supported"));
+        }
+
+        decode_base64(&uri[comma + 1..]).ok_or_else(|| LoadError::invalid("a data URI isn't valid base64"))
+    } else {
+        let path = base_dir.join(percent_decode(uri));
+
+        fs::read(&path).map_err(|err| LoadError::Io {
+            path: path.to_string_lossy().into_owned(),
+            message: err.to_string(),
+        })
+    }
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+
+    // bits that haven't made up a whole byte yet
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            _ => return None,
+        };
+
+        bits = (bits << 6) | value as u32;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+            bits &= (1 << bit_count) - 1;
+        }
+    }
+
+    Some(bytes)
+}
+
+/**
+ * URIs can have escaped characters, like `%20` for a space
+ */
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = if bytes[i] == b'%' {
+            uri.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        } else {
+            None
+        };
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            },
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn read_component(bytes: &[u8], component_type: u64, normalized: bool) -> f64 {
+    let (value, max) = match component_type {
+        5120 => (bytes[0] as i8 as f64, 127.0),
+        5121 => (bytes[0] as f64, 255.0),
+        5122 => (i16::from_le_bytes([bytes[0], bytes[1]]) as f64, 32767.0),
+        5123 => (u16::from_le_bytes([bytes[0], bytes[1]]) as f64, 65535.0),
+        5125 => (u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64, 4_294_967_295.0),
+        _ => return f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+    };
+
+    if normalized {
+        (value / max).max(-1.0)
+    } else {
+        value
+    }
+}
+
+/**
+ * The corners of each triangle in a list, strip or fan of them. Strips
+ * use degenerate triangles to jump between runs, which are left out.
+ */
+fn triangles(indices: &[usize], mode: u64) -> Vec<(usize,usize,usize)> {
+    let triangles: Vec<(usize,usize,usize)> = match mode {
+
+        // every other triangle in a strip is wound backwards
+        5 => (0..indices.len().saturating_sub(2))
+            .map(|i| if i % 2 == 0 {
+                (indices[i], indices[i + 1], indices[i + 2])
+            } else {
+                (indices[i + 1], indices[i], indices[i + 2])
+            })
+            .collect(),
+        6 => (1..indices.len().saturating_sub(1))
+            .map(|i| (indices[0], indices[i], indices[i + 1]))
+            .collect(),
+        _ => indices.chunks_exact(3)
+            .map(|corners| (corners[0], corners[1], corners[2]))
+            .collect(),
+    };
+
+    triangles.into_iter()
+        .filter(|&(a, b, c)| a != b && b != c && a != c)
+        .collect()
+}
+
+/**
+ * Tangents worked out from which way the texture coordinates run across
+ * each triangle, averaged at each vertex, for normal maps on meshes that
+ * don't come with them
+ */
+fn generate_tangents(positions: &[Vec3], normals: &[Vec3], uvs: &[(f32,f32)], triangles: &[(usize,usize,usize)]) -> Vec<(Vec3,f32)> {
+    let mut tangents = vec![Vec3::new(); positions.len()];
+    let mut bitangents = vec![Vec3::new(); positions.len()];
+
+    for &(a, b, c) in triangles {
+        let edge1 = &positions[b] - &positions[a];
+        let edge2 = &positions[c] - &positions[a];
+        let (du1, dv1) = (uvs[b].0 - uvs[a].0, uvs[b].1 - uvs[a].1);
+        let (du2, dv2) = (uvs[c].0 - uvs[a].0, uvs[c].1 - uvs[a].1);
+
+        let determinant = du1 * dv2 - du2 * dv1;
+        if determinant.abs() < 1e-12 {
+            continue;
+        }
+
+        let tangent = &(&(&edge1 * dv2) - &(&edge2 * dv1)) * (1.0 / determinant);
+        let bitangent = &(&(&edge2 * du1) - &(&edge1 * du2)) * (1.0 / determinant);
+        for &corner in &[a, b, c] {
+            tangents[corner] = &tangents[corner] + &tangent;
+            bitangents[corner] = &bitangents[corner] + &bitangent;
+        }
+    }
+
+    normals.iter().enumerate()
+        .map(|(i, normal)| {
+            let tangent = &tangents[i] - &(normal * normal.dot(&tangents[i]));
+
+            // vertices on triangles without any texture stretch get any
+            // tangent, as long as it's perpendicular to the normal
+            let tangent = if tangent.len_squared() > 0.0 {
+                tangent.normalized()
+            } else if normal.x.abs() < 0.9 {
+                normal.cross(&Vec3 { x: 1.0, y: 0.0, z: 0.0 }).normalized()
+            } else {
+                normal.cross(&Vec3 { x: 0.0, y: 1.0, z: 0.0 }).normalized()
+            };
+
+            let w = if normal.cross(&tangent).dot(&bitangents[i]) < 0.0 { -1.0 } else { 1.0 };
+
+            (tangent, w)
+        })
+        .collect()
+}
+
+/**
+ * How sharp reflections are for a metallic-roughness material: metals
+ * reflect even when they're rough, but other surfaces only once they're
+ * glossy
+ */
+fn specularity(metallic: f32, roughness: f32) -> f32 {
+    if metallic >= 0.5 || roughness < 0.5 {
+        1.0 - roughness
+    } else {
+        0.0
+    }
+}
+
+/**
+ * Opacity from the base color's alpha, either blended or (with a cutoff)
+ * all-or-nothing. Fully opaque materials don't get any.
+ */
+fn alpha_opacity(base_color_image: &Option<Arc<DynamicImage>>, alpha_factor: f32, cutoff: Option<f32>) -> Option<Texture> {
+    let opacity = |alpha: f32| match cutoff {
+        Some(cutoff) => if alpha >= cutoff { 1.0 } else { 0.0 },
+        None => alpha,
+    };
+
+    match base_color_image {
+        Some(image) => Some(mapped_image(image, |pixel| Color::gray(opacity(u8_to_f32(pixel[3]) * alpha_factor)))),
+        None => Some(opacity(alpha_factor))
+            .filter(|&opacity| opacity < 1.0)
+            .map(|opacity| Texture::Solid(Color::gray(opacity))),
+    }
+}
+
+/**
+ * A new image texture, made from an existing image one pixel at a time
+ */
+fn mapped_image<F: Fn(Rgba<u8>) -> Color>(image: &DynamicImage, map: F) -> Texture {
+    let (width, height) = image.dimensions();
+    let mapped = ImageBuffer::from_fn(width, height, |x, y| {
+        let Color(r, g, b) = map(image.get_pixel(x, y));
+
+        Rgb([f32_to_u8(r), f32_to_u8(g), f32_to_u8(b)])
+    });
+
+    Texture::Image(Arc::new(DynamicImage::ImageRgb8(mapped)))
+}
+
+fn u8_to_f32(val: u8) -> f32 {
+    val as f32 / 255.0
+}
+
+fn f32_to_u8(val: f32) -> u8 {
+    (val.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/**
+ * An array property, or nothing if it's missing
+ */
+fn array<'v>(value: &'v Value, key: &str) -> &'v [Value] {
+    value.get(key).and_then(Value::as_array).map_or(&[], Vec::as_slice)
+}
+
+fn element<'v>(json: &'v Value, collection: &str, element_index: usize) -> Result<&'v Value,LoadError> {
+    array(json, collection).get(element_index)
+        .ok_or_else(|| LoadError::invalid(&format!("there's no {} {}", collection, element_index)))
+}
+
+/**
+ * An optional property that refers to something else by its index
+ */
+fn index(value: &Value, key: &str) -> Result<Option<usize>,LoadError> {
+    match value.get(key) {
+        Some(property) => property.as_u64()
+            .map(|property| Some(property as usize))
+            .ok_or_else(|| LoadError::invalid(&format!("`{}` isn't a valid index", key))),
+        None => Ok(None),
+    }
+}
+
+fn indices(value: &Value, key: &str) -> Result<Vec<usize>,LoadError> {
+    array(value, key).iter()
+        .map(|property| property.as_u64()
+            .map(|property| property as usize)
+            .ok_or_else(|| LoadError::invalid(&format!("`{}` has an invalid index in it", key))))
+        .collect()
+}
+
+fn number(value: &Value, key: &str, default: f32) -> f32 {
+    value.get(key).and_then(Value::as_f64).map_or(default, |number| number as f32)
+}
+
+/**
+ * A fixed-size array of numbers, like a color or translation
+ */
+fn numbers(value: &Value, key: &str, default: &[f32]) -> Vec<f32> {
+    match value.get(key).and_then(Value::as_array) {
+        Some(values) if values.len() == default.len() => values.iter()
+            .zip(default)
+            .map(|(number, default)| number.as_f64().map_or(*default, |number| number as f32))
+            .collect(),
+        _ => default.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::Value;
+
+    use crate::vec3::Vec3;
+    use crate::ray::Ray;
+    use crate::color::Color;
+    use crate::camera::Projection;
+    use crate::object::Object;
+    use crate::gltf::{Gltf,Document};
+
+    // a triangle with normals, texture coordinates and 16-bit indices
+    const BUFFER: &str = "data:application/octet-stream;base64,\
+        AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/\
+        AAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAAAAAAAAAAAAIA/AAABAAIAAAA=";
+
+    fn document(nodes: &str) -> String {
+        format!(r#"{{
+            "asset": {{ "version": "2.0" }},
+            "scene": 0,
+            "scenes": [ {{ "nodes": [0] }} ],
+            "nodes": {},
+            "meshes": [ {{ "name": "triangle", "primitives": [ {{
+                "attributes": {{ "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 }},
+                "indices": 3,
+                "material": 0
+            }} ] }} ],
+            "materials": [ {{
+                "pbrMetallicRoughness": {{ "baseColorFactor": [1, 0, 0, 1], "metallicFactor": 1, "roughnessFactor": 0.25 }},
+                "alphaMode": "BLEND"
+            }} ],
+            "cameras": [ {{ "type": "perspective", "perspective": {{ "yfov": 0.8, "znear": 0.1 }} }} ],
+            "extensions": {{ "KHR_lights_punctual": {{ "lights": [ {{ "type": "point", "intensity": 2 }} ] }} }},
+            "buffers": [ {{ "uri": "{}", "byteLength": 104 }} ],
+            "bufferViews": [ {{ "buffer": 0, "byteLength": 104 }} ],
+            "accessors": [
+                {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                {{ "bufferView": 0, "byteOffset": 36, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                {{ "bufferView": 0, "byteOffset": 72, "componentType": 5126, "count": 3, "type": "VEC2" }},
+                {{ "bufferView": 0, "byteOffset": 96, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+            ]
+        }}"#, nodes, BUFFER)
+    }
+
+    #[test]
+    fn test_parse() {
+        let gltf = document(r#"[
+            { "name": "root", "translation": [0, 0, -5], "children": [1, 2, 3] },
+            { "mesh": 0, "scale": [2, 2, 2] },
+            { "camera": 0, "translation": [0, 0, 7] },
+            { "translation": [0, 3, 0], "extensions": { "KHR_lights_punctual": { "light": 0 } } }
+        ]"#);
+        let parsed = Gltf::parse(gltf.as_bytes(), Path::new(""), None).unwrap();
+        assert_eq!(parsed.meshes.len(), 1);
+
+        // the same file in a binary container, with its JSON padded to
+        // four bytes
+        let mut json = gltf.into_bytes();
+        json.resize(json.len().div_ceil(4) * 4, b' ');
+        let mut glb = b"glTF".to_vec();
+        glb.extend(&2u32.to_le_bytes());
+        glb.extend(&(20 + json.len() as u32).to_le_bytes());
+        glb.extend(&(json.len() as u32).to_le_bytes());
+        glb.extend(b"JSON");
+        glb.extend(json);
+        let gltf = Gltf::parse(&glb, Path::new(""), None).unwrap();
+
+        // the triangle is scaled and then moved with its parent
+        assert_eq!(gltf.meshes.len(), 1);
+        let (name, mesh) = &gltf.meshes[0];
+        assert_eq!(name, "triangle");
+
+        let hit = mesh.intersection(&Ray {
+            origin: Vec3 { x: 0.5, y: 0.5, z: 0.0 },
+            direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+        }).unwrap();
+        assert!((hit.distance - 5.0).abs() < 0.0001);
+        assert!((&hit.normal - &Vec3 { x: 0.0, y: 0.0, z: 1.0 }).len() < 0.0001);
+        assert!((hit.uv.0 - 0.25).abs() < 0.0001 && (hit.uv.1 - 0.25).abs() < 0.0001);
+
+        assert_eq!(gltf.cameras.len(), 1);
+        let camera = &gltf.cameras[0];
+        assert!((&camera.position - &Vec3 { x: 0.0, y: 0.0, z: 2.0 }).len() < 0.0001);
+        assert!(camera.target.z < camera.position.z);
+        assert_eq!(camera.fov, 0.8);
+        assert_eq!(camera.projection, Projection::Perspective);
+
+        assert_eq!(gltf.lights.len(), 1);
+        assert!((gltf.lights[0].bounds().unwrap().centroid().y - 3.0).abs() < 0.0001);
+
+        let broken = document(r#"[ { "mesh": 1 } ]"#);
+        let err = Gltf::parse(broken.as_bytes(), Path::new(""), None).err().unwrap().to_string();
+        assert_eq!(err, "there's no meshes 1");
+        assert!(Gltf::parse(b"{ \"asset\": ", Path::new(""), None).is_err());
+
+        // sizes that would be too big to allocate, or would overflow
+        let triangle = document(r#"[ { "mesh": 0 } ]"#);
+        let huge = triangle.replace(r#""bufferView": 0, "byteOffset": 72, "componentType": 5126, "count": 3"#, r#""componentType": 5126, "count": 18446744073709551615"#);
+        let err = Gltf::parse(huge.as_bytes(), Path::new(""), None).err().unwrap().to_string();
+        assert!(err.ends_with("accessor 2 has too many elements for one without a buffer view"));
+        let overflowing = triangle.replace(r#""byteOffset": 36"#, r#""byteOffset": 18446744073709551615"#);
+        let err = Gltf::parse(overflowing.as_bytes(), Path::new(""), None).err().unwrap().to_string();
+        assert!(err.ends_with("accessor 1 runs past the end of its buffer view"));
+    }
+
+    #[test]
+    fn test_material() {
+        let json: Value = serde_json::from_str(&document("[]")).unwrap();
+        let mut document = Document::new(&json, None, Path::new("")).unwrap();
+        let material = document.material(0).unwrap();
+
+        assert_eq!(material.texture_albedo.unwrap().color_at((0.0, 0.0)), Color(1.0, 0.0, 0.0));
+        assert_eq!(material.texture_specular.unwrap().color_at((0.0, 0.0)), Color::gray(0.75));
+        assert!(material.texture_emission_intensity.is_none());
+
+        // fully opaque, even though it's blended
+        assert!(material.texture_opacity.is_none());
+    }
+}
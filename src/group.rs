@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use crate::object::ObjectEnum;
+use crate::matrix::{Matrix,IDENTITY};
+use crate::transformed::Transformed;
+
+/**
+ * A node in the scene graph: either an object (with the line of the scene
+ * file it came from), or a group of other nodes.
+ */
+pub enum SceneNode {
+    Object(Box<ObjectEnum>, usize),
+    Group(Group),
+}
+
+/**
+ * A set of objects (and other groups) which are positioned together by a
+ * shared transform. The graph is only for authoring; before rendering it's
+ * flattened into a plain list of objects with their full transforms, so
+ * that everything ends up in a single top-level BVH.
+ */
+pub struct Group {
+    transform: Matrix,
+    children: Vec<SceneNode>,
+}
+
+impl Group {
+
+    pub fn new() -> Self {
+        Self {
+            transform: IDENTITY,
+            children: Vec::new(),
+        }
+    }
+
+    /**
+     * Returns `None` if the transform can't be inverted, in which case
+     * nothing in the group could be rendered.
+     */
+    pub fn with_transform(transform: &Matrix) -> Option<Self> {
+        transform.inverse()?;
+
+        Some(Self {
+            transform: *transform,
+            children: Vec::new(),
+        })
+    }
+
+    pub fn transform(&self) -> &Matrix {
+        &self.transform
+    }
+
+    pub fn children(&self) -> &[SceneNode] {
+        &self.children
+    }
+
+    pub fn push(&mut self, node: SceneNode) {
+        self.children.push(node);
+    }
+
+    pub fn push_object(&mut self, object: ObjectEnum, line: usize) {
+        self.children.push(SceneNode::Object(Box::new(object), line));
+    }
+
+    pub fn push_group(&mut self, group: Group) {
+        self.children.push(SceneNode::Group(group));
+    }
+
+    /**
+     * Turn the graph into a list of objects, each with the combined
+     * transform of every group it's nested in. Objects that were already
+     * transformed have their transforms merged rather than nested, and
+     * keep sharing whatever they wrapped.
+     *
+     * Every transform is invertible on its own, but they can still add up
+     * to one that isn't (say, scaling down again and again), in which case
+     * this returns the line of the first object that can't be placed.
+     */
+    pub fn flatten(self) -> Result<Vec<ObjectEnum>,usize> {
+        let mut objects = Vec::new();
+        self.flatten_into(&IDENTITY, &mut objects)?;
+        Ok(objects)
+    }
+
+    fn flatten_into(self, parent_transform: &Matrix, objects: &mut Vec<ObjectEnum>) -> Result<(),usize> {
+        let transform = self.transform.then(parent_transform);
+
+        for child in self.children {
+            match child {
+                SceneNode::Group(group) => group.flatten_into(&transform, objects)?,
+                SceneNode::Object(object, line) => objects.push(placed(*object, &transform).ok_or(line)?),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+ * The object with `transform` applied on top of its own, or `None` if the
+ * two together can't be inverted
+ */
+fn placed(object: ObjectEnum, transform: &Matrix) -> Option<ObjectEnum> {
+    if *transform == IDENTITY {
+        return Some(object);
+    }
+
+    let (inner, combined) = match object {
+        ObjectEnum::Transformed(transformed) => (transformed.object().clone(), transformed.transform().then(transform)),
+        object => (Arc::new(object), *transform),
+    };
+
+    Transformed::new(inner, &combined).map(ObjectEnum::Transformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::vec3::Vec3;
+    use crate::matrix::Matrix;
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+    use crate::object::ObjectEnum;
+    use crate::transformed::Transformed;
+    use crate::group::Group;
+
+    #[test]
+    fn test_flatten_merges_transforms() {
+        let shared = Arc::new(ObjectEnum::Sphere(Sphere::new(Vec3::new(), 1.0, Material::new())));
+        let scale = Matrix::scale(&Vec3::from_scalar(2.0));
+        let translation = Matrix::translation(&Vec3 { x: 1.0, y: 2.0, z: 3.0 });
+
+        let mut inner = Group::with_transform(&translation).unwrap();
+        inner.push_object(ObjectEnum::Transformed(Transformed::new(shared.clone(), &scale).unwrap()), 1);
+        let mut outer = Group::new();
+        outer.push_group(inner);
+
+        let objects = outer.flatten().unwrap();
+        assert_eq!(objects.len(), 1);
+        match &objects[0] {
+            ObjectEnum::Transformed(transformed) => {
+                assert!(Arc::ptr_eq(transformed.object(), &shared));
+                assert_eq!(*transformed.transform(), scale.then(&translation));
+            },
+            _ => panic!("expected a transformed object"),
+        }
+
+        assert!(Group::with_transform(&Matrix::scale(&Vec3::new())).is_none());
+    }
+
+    #[test]
+    fn test_flatten_small_scales() {
+        let sphere = Arc::new(ObjectEnum::Sphere(Sphere::new(Vec3::new(), 1.0, Material::new())));
+        let nested = |group_scale: f32, object_scale: f32| {
+            let mut group = Group::with_transform(&Matrix::scale(&Vec3::from_scalar(group_scale))).unwrap();
+            group.push_object(ObjectEnum::Transformed(Transformed::new(sphere.clone(), &Matrix::scale(&Vec3::from_scalar(object_scale))).unwrap()), 7);
+            group.flatten()
+        };
+
+        assert_eq!(nested(0.01, 0.001).map(|objects| objects.len()), Ok(1));
+
+        // each can be inverted, but not both together
+        assert_eq!(nested(1e-13, 1e-13).err(), Some(7));
+    }
+}
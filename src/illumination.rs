@@ -1,6 +1,5 @@
 
 use crate::color::Color;
-use crate::fidelity_consts::{SAMPLE_COUNT};
 use crate::utils::lerp;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -34,14 +33,13 @@ impl Illumination {
 }
 
 //pub fn integrate<'a,I: Iterator<Item = &'a Illumination>>(samples: I) -> Illumination {
-pub fn integrate(samples: &[Illumination;SAMPLE_COUNT]) -> Illumination {
+pub fn integrate(samples: &[Illumination]) -> Illumination {
     let mut lum = Illumination::new();
 
     // HACK: Do a true weighted-average on colors eventually (scale by brightness)
     let mut samples_with_illum = 0.0;
 
-    for index in 0..SAMPLE_COUNT {
-        let sample = samples[index];
+    for sample in samples {
 
         //if sample.intensity > 0.001 {
             lum.color.0 += sample.color.0;
@@ -58,7 +56,7 @@ pub fn integrate(samples: &[Illumination;SAMPLE_COUNT]) -> Illumination {
     lum.color.1 /= samples_with_illum;
     lum.color.2 /= samples_with_illum;
 
-    lum.intensity /= SAMPLE_COUNT as f32;
+    lum.intensity /= samples.len() as f32;
 
     return lum;
 }
\ No newline at end of file
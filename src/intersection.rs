@@ -1,13 +1,53 @@
 
 use crate::utils::clamp;
 use crate::vec3::Vec3;
+use crate::color::Color;
 use crate::utils::{PI_OVER_TWO};
 
+/**
+ * Everything found out about a ray hitting an object, recorded while 
+ * searching for the nearest hit and then handed straight to shading.
+ */
 pub struct Intersection {
     pub distance: f32,
     pub position: Vec3,
+
+    /**
+     * The normal used for shading; starts out the same as 
+     * `geometric_normal`, but may be perturbed by a normal map
+     */
     pub normal: Vec3,
+    pub geometric_normal: Vec3,
     pub direction: Vec3,
+
+    /**
+     * For meshes: which face was hit, and the barycentric weights of its 
+     * second and third vertices at the hit position
+     */
+    pub face_index: Option<usize>,
+    pub barycentric: Option<(f32,f32)>,
+
+    pub uv: (f32,f32),
+
+    /**
+     * For meshes with tangents: the tangent and bitangent at the hit, so
+     * that normal maps can be applied in tangent space
+     */
+    pub tangent_frame: Option<(Vec3,Vec3)>,
+
+    /**
+     * For meshes with vertex colors: the color blended at the hit, which
+     * tints the material's albedo
+     */
+    pub vertex_color: Option<Color>,
+
+    /**
+     * For hits on CSG objects: which child was hit at each level of 
+     * nesting, as a stack of bits (innermost pushed first). Shading pops 
+     * them back off on its way down to the child whose material is used.
+     */
+    pub csg_path: u64,
+
     reflected_direction: Option<Vec3>,
 }
 
@@ -18,11 +58,26 @@ impl Intersection {
             distance,
             position,
             normal,
+            geometric_normal: normal,
             direction,
+            face_index: None,
+            barycentric: None,
+            uv: (0.0, 0.0),
+            tangent_frame: None,
+            vertex_color: None,
+            csg_path: 0,
             reflected_direction: None,
         }
     }
 
+    /**
+     * Stand-in for the end of an interval that never ends, like the inside
+     * of a plane. Only its distance (infinite) is meaningful.
+     */
+    fn at_infinity(distance: f32, direction: Vec3) -> Self {
+        Self::new(distance, Vec3::new(), Vec3::new(), direction)
+    }
+
     /**
      * The angle between the intersecting ray and the normal at that point
      */
@@ -46,3 +101,51 @@ impl Intersection {
         return self.reflected_direction.as_ref().unwrap();
     }
 }
+
+/**
+ * A stretch of a ray that's inside of a solid object, from where the ray 
+ * enters it to where it leaves. Distances can be negative (if the ray 
+ * starts inside) or infinite (for unbounded objects), and both normals 
+ * point out of the object.
+ */
+pub struct Interval {
+    pub entry: Intersection,
+    pub exit: Intersection,
+}
+
+impl Interval {
+
+    pub fn new(entry: Intersection, exit: Intersection) -> Self {
+        Self { entry, exit }
+    }
+
+    /**
+     * Everything along the ray up to `exit`
+     */
+    pub fn ending_at(exit: Intersection) -> Self {
+        Self {
+            entry: Intersection::at_infinity(f32::NEG_INFINITY, exit.direction),
+            exit,
+        }
+    }
+
+    /**
+     * Everything along the ray from `entry` on
+     */
+    pub fn starting_at(entry: Intersection) -> Self {
+        Self {
+            exit: Intersection::at_infinity(f32::INFINITY, entry.direction),
+            entry,
+        }
+    }
+
+    /**
+     * The whole ray
+     */
+    pub fn everywhere(direction: Vec3) -> Self {
+        Self {
+            entry: Intersection::at_infinity(f32::NEG_INFINITY, direction),
+            exit: Intersection::at_infinity(f32::INFINITY, direction),
+        }
+    }
+}
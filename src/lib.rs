@@ -1,23 +1,43 @@
 
-#[macro_use]
-extern crate lazy_static;
-
+pub mod bounding_box;
+pub mod bvh;
+pub mod camera;
 pub mod color;
-pub mod fidelity_consts;
+pub mod cone;
+pub mod csg;
+pub mod cuboid;
+pub mod cylinder;
+pub mod disk;
 pub mod frame;
+pub mod gltf;
+pub mod group;
 pub mod illumination;
 pub mod intersection;
+pub mod load_error;
 pub mod material;
 pub mod matrix;
 pub mod mtl_parser;
 pub mod object;
 pub mod plane;
+pub mod quad;
+pub mod quaternion;
 pub mod ray;
+pub mod render_settings;
+pub mod scene;
+pub mod scene_parser;
 pub mod scenes;
+pub mod sdf;
 pub mod sphere;
 pub mod texture;
+pub mod torus;
+pub mod transformed;
+pub mod triangle;
 pub mod utils;
 pub mod vec3;
+pub mod world;
 pub mod mesh;
+pub mod mesh_cache;
 pub mod obj_parser;
+pub mod ply_parser;
+pub mod stl_parser;
 pub mod cast;
\ No newline at end of file
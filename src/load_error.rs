@@ -0,0 +1,97 @@
+use std::fmt;
+use std::str::FromStr;
+
+/**
+ * Why a mesh, material library or texture file couldn't be loaded
+ */
+#[derive(Debug)]
+pub enum LoadError {
+    Io { path: String, message: String },
+    Image { path: String, message: String },
+    Parse { path: String, line: usize, token: String, message: String },
+
+    // for files that aren't read line by line
+    Invalid { path: String, message: String },
+}
+
+impl LoadError {
+
+    /**
+     * A problem with one token; which file and line it's on are filled in
+     * by whatever is reading the file
+     */
+    pub fn parse(token: &str, message: &str) -> Self {
+        LoadError::Parse {
+            path: String::new(),
+            line: 0,
+            token: String::from(token),
+            message: String::from(message),
+        }
+    }
+
+    pub fn invalid(message: &str) -> Self {
+        LoadError::Invalid {
+            path: String::new(),
+            message: String::from(message),
+        }
+    }
+
+    pub fn at_line(mut self, number: usize) -> Self {
+        if let LoadError::Parse { line, .. } = &mut self {
+            *line = number;
+        }
+
+        self
+    }
+
+    pub fn in_file(mut self, file: &str) -> Self {
+        if let LoadError::Io { path, .. } | LoadError::Parse { path, .. } | LoadError::Invalid { path, .. } = &mut self {
+            *path = String::from(file);
+        }
+
+        self
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // errors that haven't been given a file yet (say, from parsing a
+        // string) leave it out rather than showing an empty one
+        match self {
+            LoadError::Io { path, message } if path.is_empty() =>
+                write!(f, "failed to read: {}", message),
+            LoadError::Io { path, message } =>
+                write!(f, "failed to read \"{}\": {}", path, message),
+            LoadError::Image { path, message } if path.is_empty() =>
+                write!(f, "failed to load image: {}", message),
+            LoadError::Image { path, message } =>
+                write!(f, "failed to load image \"{}\": {}", path, message),
+            LoadError::Parse { path, line, token, message } if path.is_empty() =>
+                write!(f, "line {}, `{}`: {}", line, token, message),
+            LoadError::Parse { path, line, token, message } =>
+                write!(f, "\"{}\", line {}, `{}`: {}", path, line, token, message),
+            LoadError::Invalid { path, message } if path.is_empty() =>
+                write!(f, "{}", message),
+            LoadError::Invalid { path, message } =>
+                write!(f, "\"{}\": {}", path, message),
+        }
+    }
+}
+
+impl std::error::Error for LoadError { }
+
+/**
+ * The argument after a line's keyword at `index` (counting the keyword
+ * as 0)
+ */
+pub fn arg<'a>(segments: &[&'a str], index: usize) -> Result<&'a str,LoadError> {
+    segments.get(index)
+        .copied()
+        .ok_or_else(|| LoadError::parse(segments[0], &format!("expected at least {} values", index)))
+}
+
+pub fn number_arg<T: FromStr>(segments: &[&str], index: usize) -> Result<T,LoadError> {
+    let token = arg(segments, index)?;
+
+    token.parse().map_err(|_| LoadError::parse(token, "not a valid number"))
+}
@@ -9,20 +9,27 @@ use crate::color::Color;
 use crate::vec3::Vec3;
 use crate::intersection::Intersection;
 use crate::cast::{cast_ray};
-use crate::fidelity_consts::{SAMPLE_COUNT,PREVIEW_MODE};
+use crate::render_settings::RenderSettings;
 use crate::ray::Ray;
-use crate::utils::{PI_OVER_TWO};
-use crate::object::{ObjectEnum};
+use crate::utils::{PI_OVER_TWO,adjusted_for_normal,color_to_normal,refract};
+use crate::world::World;
 
 
 const BACKGROUND_ILLUMINATION: Illumination = Illumination { color: Color(0.0, 0.0, 0.0), intensity: 0.0 };
 
+#[derive(Clone)]
 pub struct Material {
     pub texture_albedo: Option<Texture>,
     pub texture_specular: Option<Texture>,
     pub texture_normal: Option<Texture>,
     pub texture_emission_color: Option<Texture>,
     pub texture_emission_intensity: Option<Texture>,
+
+    // how much light is stopped rather than let through (1 is opaque)
+    pub texture_opacity: Option<Texture>,
+
+    // how much light let through is bent; `None` lets it through straight
+    pub index_of_refraction: Option<f32>,
 }
 
 const PREVIEW_DIRECTION: Vec3 = Vec3 { x: 1.0, y: 1.0, z: 1.0 };
@@ -36,11 +43,33 @@ impl Material {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     }
 
+    /**
+     * Compute the illumination reaching the camera from a hit on a surface
+     * with this material. Applies the normal map (if any) to the hit's
+     * shading normal first.
+     */
 //    #[flame("Material")]
-    pub fn shade(&self, intersection: &mut Intersection, uv: (f32,f32), objs: &Vec<ObjectEnum>, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+    pub fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+        let uv = intersection.uv;
+
+        if let Some(texture_normal) = self.texture_normal.as_ref() {
+            let normal_color = texture_normal.color_at(uv);
+            intersection.normal = match intersection.tangent_frame {
+
+                // a tangent-space normal map, as in glTF
+                Some((tangent, bitangent)) => (
+                    &(&(&tangent * (normal_color.0 * 2.0 - 1.0)) + &(&bitangent * (normal_color.1 * 2.0 - 1.0)))
+                    + &(&intersection.normal * (normal_color.2 * 2.0 - 1.0))
+                ).normalized(),
+                None => adjusted_for_normal(&intersection.normal, &color_to_normal(&normal_color)),
+            };
+        }
+
         match &self.texture_emission_intensity {
             Some(tex) => Illumination {
                 color: self.texture_emission_color.as_ref().map(|col| col.color_at(uv))
@@ -50,8 +79,9 @@ impl Material {
             None => {
                 if bounces_remaining == 0 {
                     BACKGROUND_ILLUMINATION
-                } else if PREVIEW_MODE {
-                    let base_color = self.texture_albedo.as_ref().map(|texture| texture.color_at(uv)).unwrap_or(Color(1.0, 1.0, 1.0));
+                } else if settings.preview_mode {
+                    let base_color = self.texture_albedo.as_ref().map(|texture| texture.color_at(uv)).unwrap_or(Color(1.0, 1.0, 1.0))
+                        * intersection.vertex_color.unwrap_or(Color(1.0, 1.0, 1.0));
                     let adjustment = 1.0 - (intersection.normal.angle(&PREVIEW_DIRECTION) / PI);
                     Illumination {
                         color: base_color * adjustment,
@@ -59,13 +89,12 @@ impl Material {
                     }
                 } else {
                     let diffuse_illumination: Option<Illumination> = self.texture_albedo.as_ref().map(|texture| {
-                        let surface_color = texture.color_at(uv);
-                        let sample_rays = get_sample_rays(intersection.position, &intersection.normal, rng, PI_OVER_TWO);
+                        let surface_color = texture.color_at(uv) * intersection.vertex_color.unwrap_or(Color(1.0, 1.0, 1.0));
+                        let sample_rays = get_sample_rays(intersection.position, &intersection.normal, rng, PI_OVER_TWO, settings.sample_count);
 
-                        let mut samples = [Illumination::new();SAMPLE_COUNT];
-                        for i in 0..SAMPLE_COUNT {
-                            samples[i] = cast_ray(&sample_rays[i], objs, rng, bounces_remaining - 1);
-                        }
+                        let samples: Vec<Illumination> = sample_rays.iter()
+                            .map(|ray| cast_ray(ray, world, settings, rng, bounces_remaining - 1))
+                            .collect();
 
                         let illumination = integrate(&samples);
                         
@@ -84,20 +113,19 @@ impl Material {
                             cast_ray(&Ray {
                                 origin: intersection.position,
                                 direction: reflected
-                            }, objs, rng, bounces_remaining - 1)
+                            }, world, settings, rng, bounces_remaining - 1)
                         } else {
-                            let sample_rays = get_sample_rays(intersection.position, &reflected, rng, (1.0 - specularity) * PI_OVER_TWO);
+                            let sample_rays = get_sample_rays(intersection.position, &reflected, rng, (1.0 - specularity) * PI_OVER_TWO, settings.sample_count);
 
-                            let mut samples = [Illumination::new();SAMPLE_COUNT];
-                            for i in 0..SAMPLE_COUNT {
-                                samples[i] = cast_ray(&sample_rays[i], objs, rng, bounces_remaining - 1);
-                            }
+                            let samples: Vec<Illumination> = sample_rays.iter()
+                                .map(|ray| cast_ray(ray, world, settings, rng, bounces_remaining - 1))
+                                .collect();
                             
                             integrate(&samples)
                         }
                     });
 
-                    match diffuse_illumination {
+                    let surface_illumination = match diffuse_illumination {
                         Some(diffuse) => {
                             match specular_illumination {
                                 Some(specular_illumination) => 
@@ -111,24 +139,67 @@ impl Material {
                                 None => BACKGROUND_ILLUMINATION
                             }
                         }
-                    }
+                    };
+
+                    self.with_transmission(surface_illumination, intersection, world, settings, rng, bounces_remaining)
                 }
             }
         }
     }
+
+    /**
+     * Mix in the light coming through the surface, if it isn't opaque. It
+     * goes straight through, unless there's an index of refraction to bend
+     * it by.
+     */
+    fn with_transmission(&self, surface: Illumination, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+        let opacity = match &self.texture_opacity {
+            Some(texture) => texture.color_at(intersection.uv).0,
+            None => return surface,
+        };
+        if opacity >= 1.0 {
+            return surface;
+        }
+
+        let direction = match self.index_of_refraction {
+            Some(index_of_refraction) => refract(&intersection.direction, &intersection.normal, index_of_refraction)
+                .unwrap_or(*intersection.reflected_direction()),
+            None => intersection.direction,
+        };
+
+        // the hit is offset to the front of the surface, so going in means
+        // starting from just behind it
+        let origin = if direction.dot(&intersection.geometric_normal) < 0.0 {
+            &intersection.position - &(&intersection.geometric_normal * 0.002)
+        } else {
+            intersection.position
+        };
+        let transmitted = cast_ray(&Ray { origin, direction }, world, settings, rng, bounces_remaining - 1);
+
+        // each color counts for as much as the light it brings
+        let surface_intensity = surface.intensity * opacity;
+        let transmitted_intensity = transmitted.intensity * (1.0 - opacity);
+        let intensity = surface_intensity + transmitted_intensity;
+        if intensity <= 0.0 {
+            return BACKGROUND_ILLUMINATION;
+        }
+
+        Illumination {
+            color: surface.color * (surface_intensity / intensity) + transmitted.color * (transmitted_intensity / intensity),
+            intensity,
+        }
+    }
 }
 
-fn get_sample_rays(position: Vec3, direction: &Vec3, rng: &mut SmallRng, range: f32) -> [Ray;SAMPLE_COUNT] {
-    let mut rays = [Ray::new();SAMPLE_COUNT];
+fn get_sample_rays(position: Vec3, direction: &Vec3, rng: &mut SmallRng, range: f32, count: usize) -> Vec<Ray> {
+    let mut rays = Vec::with_capacity(count);
     
-    let mut i = 0;
-    while i < SAMPLE_COUNT {
+    while rays.len() < count {
         let ray = Ray::random_direction(position, rng);
 
         // HACK: Figure out a way to *generate* rays that are already within our desired area
         if ray.direction.angle(direction) < range {
-            rays[i] = ray;
-            i += 1;
+            rays.push(ray);
         }
     }
 
@@ -1,5 +1,6 @@
 
 use crate::vec3::Vec3;
+use crate::quaternion::Quaternion;
 
 const SIZE: usize = 4;
 const BUFFER_SIZE: usize = SIZE * SIZE;
@@ -83,6 +84,53 @@ impl Matrix {
         return &(&IDENTITY + &(&k * theta.sin())) + &(&(&k * &k) * (1.0 - theta.cos()));
     }
 
+    /**
+     * A rotation of `theta` radians around `axis` (which doesn't need to
+     * be normalized), counter-clockwise when looking down the axis
+     */
+    pub fn rotation(axis: &Vec3, theta: f32) -> Self {
+        let a = axis.normalized();
+        let cos = theta.cos();
+        let sin = theta.sin();
+        let t = 1.0 - cos;
+
+        Self([
+            t * a.x * a.x + cos,       t * a.x * a.y - sin * a.z, t * a.x * a.z + sin * a.y, 0.0,
+            t * a.x * a.y + sin * a.z, t * a.y * a.y + cos,       t * a.y * a.z - sin * a.x, 0.0,
+            t * a.x * a.z - sin * a.y, t * a.y * a.z + sin * a.x, t * a.z * a.z + cos,       0.0,
+            0.0,                       0.0,                       0.0,                       1.0
+        ])
+    }
+
+    pub fn from_quaternion(quaternion: &Quaternion) -> Self {
+        let Quaternion { x, y, z, w } = quaternion.normalized();
+
+        Self([
+            1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - z * w),       2.0 * (x * z + y * w),       0.0,
+            2.0 * (x * y + z * w),       1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - x * w),       0.0,
+            2.0 * (x * z - y * w),       2.0 * (y * z + x * w),       1.0 - 2.0 * (x * x + y * y), 0.0,
+            0.0,                         0.0,                         0.0,                         1.0
+        ])
+    }
+
+    /**
+     * The transform from world space into the space of a camera sitting at
+     * `eye` and looking at `target`, where the camera looks down -Z with
+     * +Y up. Its inverse places an object at `eye`, facing `target`.
+     */
+    pub fn look_at(eye: &Vec3, target: &Vec3, up: &Vec3) -> Self {
+        let forward = (target - eye).normalized();
+        let right = forward.cross(up).normalized();
+        let up = right.cross(&forward);
+
+        Self([
+            right.x,    right.y,    right.z,    -right.dot(eye),
+            up.x,       up.y,       up.z,       -up.dot(eye),
+            -forward.x, -forward.y, -forward.z, forward.dot(eye),
+            0.0,        0.0,        0.0,        1.0
+        ])
+    }
+
     // core ops
     fn index_for(&self, row: usize, col: usize) -> usize {
         row * SIZE + col
@@ -101,14 +149,92 @@ impl Matrix {
     }
 
     // other ops
+
+    /**
+     * Apply this transform, then `next` (i.e. `next * self`). Lets a chain 
+     * of transforms be read in the order they happen.
+     */
+    pub fn then(&self, next: &Matrix) -> Matrix {
+        next * self
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let mut result = 0.0;
+
+        for c in 0..SIZE {
+            let sign = if c % 2 == 0 { 1.0 } else { -1.0 };
+            result += sign * self.get(0, c) * self.minor(0, c);
+        }
+
+        result
+    }
+
+    /**
+     * Invert in place. Panics if the matrix isn't invertible; use 
+     * `inverse()` when that's a possibility.
+     */
     pub fn invert(&mut self) {
-        unimplemented!();
+        *self = self.inverse().expect("Matrix is not invertible");
     }
 
-    pub fn inverse(&self) -> Matrix {
-        let mut result = self.clone();
-        result.invert();
-        return result;
+    /**
+     * The inverse of this matrix, or `None` if it's singular (like a
+     * scale by zero). Tiny determinants are fine, since a scale that's
+     * small but not zero can still be undone, as long as the inverse
+     * doesn't overflow.
+     */
+    pub fn inverse(&self) -> Option<Matrix> {
+        let determinant = self.determinant();
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+
+        // adjugate over determinant
+        let mut result = Matrix::new();
+        for r in 0..SIZE {
+            for c in 0..SIZE {
+                let sign = if (r + c) % 2 == 0 { 1.0 } else { -1.0 };
+                result.set(c, r, sign * self.minor(r, c) / determinant);
+            }
+        }
+
+        if !result.0.iter().all(|value| value.is_finite()) {
+            return None;
+        }
+
+        Some(result)
+    }
+
+    /**
+     * The matrix to transform normals with, so that they stay 
+     * perpendicular to surfaces transformed by this matrix (which 
+     * otherwise breaks under non-uniform scaling). Use it with 
+     * `Vec3::transform_direction()`, and re-normalize the result.
+     */
+    pub fn normal_matrix(&self) -> Option<Matrix> {
+        let mut linear = *self;
+        for r in 0..3 {
+            linear.set(r, 3, 0.0);
+        }
+
+        linear.inverse().map(|inverse| inverse.transposition())
+    }
+
+    // determinant of the 3x3 matrix left after removing a row and column
+    fn minor(&self, row: usize, col: usize) -> f32 {
+        let mut m = [0.0; 9];
+        let mut i = 0;
+
+        for r in (0..SIZE).filter(|r| *r != row) {
+            for c in (0..SIZE).filter(|c| *c != col) {
+                m[i] = self.get(r, c);
+                i += 1;
+            }
+        }
+
+        m[0] * (m[4] * m[8] - m[5] * m[7])
+            - m[1] * (m[3] * m[8] - m[5] * m[6])
+            + m[2] * (m[3] * m[7] - m[4] * m[6])
     }
 
     pub fn transpose(&mut self) {
@@ -224,10 +350,10 @@ fn test_mul() {
     ]);
 
     assert_eq!(&mat_1 * &mat_2, Matrix([
-        210.0, 93.0, 171.0, 105.0,
-        267.0, 149.0, 146.0, 169.0,
-        236.0, 104.0, 172.0, 128.0,
-        271.0, 149.0, 268.0, 169.0
+        185.0, 121.0, 167.0, 101.0,
+        225.0, 154.0, 219.0, 127.0,
+        153.0, 155.0, 143.0, 111.0,
+        290.0, 212.0, 222.0, 218.0
     ]));
 }
 
@@ -240,7 +366,7 @@ fn test_from_to() {
 
     let rotation = Matrix::from_to_rotation(&from, &to);
 
-    let transformed = from.transformed(&rotation);
+    let transformed = from.transform_direction(&rotation);
 
     let to = Vec3 { x: round_3_decimals(to.x), y: round_3_decimals(to.y), z: round_3_decimals(to.z) };
     let transformed = Vec3 { x: round_3_decimals(transformed.x), y: round_3_decimals(transformed.y), z: round_3_decimals(transformed.z) };
@@ -248,6 +374,92 @@ fn test_from_to() {
     assert_eq!(&to, &transformed);
 }
 
+#[test]
+fn test_inverse() {
+    let transform = Matrix::translation(&Vec3 { x: 1.0, y: -2.0, z: 3.0 })
+        * Matrix::rotation(&Vec3 { x: 1.0, y: 1.0, z: 0.0 }, 0.7)
+        * Matrix::scale(&Vec3 { x: 2.0, y: 0.5, z: 3.0 });
+
+    assert_matrix_eq(&(transform * transform.inverse().unwrap()), &IDENTITY);
+    assert_matrix_eq(&(transform.inverse().unwrap() * transform), &IDENTITY);
+
+    let mut inverted = transform;
+    inverted.invert();
+    assert_matrix_eq(&inverted, &transform.inverse().unwrap());
+
+    assert!(Matrix::scale(&Vec3 { x: 1.0, y: 0.0, z: 1.0 }).inverse().is_none());
+
+    let tiny = Matrix::scale(&Vec3::from_scalar(0.00002));
+    assert_matrix_eq(&(tiny * tiny.inverse().unwrap()), &IDENTITY);
+    assert_matrix_eq(&(tiny.inverse().unwrap() * tiny), &IDENTITY);
+}
+
+#[test]
+fn test_normal_matrix() {
+    // squash a 45 degree slope; its normal should tilt the other way
+    let transform = Matrix::translation(&Vec3 { x: 5.0, y: 5.0, z: 5.0 })
+        * Matrix::scale(&Vec3 { x: 1.0, y: 0.5, z: 1.0 });
+    let surface = Vec3 { x: 1.0, y: 1.0, z: 0.0 }.transform_direction(&transform);
+    let normal = Vec3 { x: 1.0, y: -1.0, z: 0.0 }.transform_direction(&transform.normal_matrix().unwrap());
+
+    assert!(surface.dot(&normal).abs() < 0.0001);
+    assert!(normal.x > 0.0 && normal.y < -normal.x);
+}
+
+#[test]
+fn test_look_at() {
+    let eye = Vec3 { x: 1.0, y: 2.0, z: 3.0 };
+    let target = Vec3 { x: 4.0, y: 2.0, z: 3.0 };
+    let view = Matrix::look_at(&eye, &target, &Vec3 { x: 0.0, y: 1.0, z: 0.0 });
+
+    assert_vec_eq(&eye.transform_point(&view), &Vec3::new());
+    assert_vec_eq(&target.transform_point(&view), &Vec3 { x: 0.0, y: 0.0, z: -3.0 });
+    assert_vec_eq(&Vec3 { x: 1.0, y: 5.0, z: 3.0 }.transform_point(&view), &Vec3 { x: 0.0, y: 3.0, z: 0.0 });
+}
+
+#[test]
+fn test_rotations() {
+    let axis = Vec3 { x: 0.3, y: -1.0, z: 0.5 };
+    let theta = 1.2;
+    let point = Vec3 { x: 2.0, y: 1.0, z: -1.0 };
+
+    // all of these should agree
+    let axis_angle = Matrix::rotation(&axis, theta);
+    let quaternion = Quaternion::from_axis_angle(&axis, theta);
+    let from_quaternion = Matrix::from_quaternion(&quaternion);
+    assert_matrix_eq(&axis_angle, &from_quaternion);
+    assert_vec_eq(&point.transform_point(&axis_angle), &quaternion.rotate(&point));
+    assert_matrix_eq(&Matrix::rotation(&Vec3 { x: 0.0, y: 1.0, z: 0.0 }, theta), &Matrix::rotation_y(theta));
+
+    // composing quaternions composes the rotations, in the same order as matrices
+    let other = Quaternion::from_axis_angle(&Vec3 { x: 1.0, y: 0.0, z: 0.0 }, 0.4);
+    assert_matrix_eq(
+        &Matrix::from_quaternion(&(&other * &quaternion)),
+        &from_quaternion.then(&Matrix::from_quaternion(&other)));
+}
+
+#[test]
+fn test_transform_point_and_direction() {
+    let transform = Matrix::translation(&Vec3 { x: 1.0, y: 2.0, z: 3.0 })
+        * Matrix::scale(&Vec3::from_scalar(2.0));
+    let vec = Vec3 { x: 1.0, y: 0.0, z: 0.0 };
+
+    assert_vec_eq(&vec.transform_point(&transform), &Vec3 { x: 3.0, y: 2.0, z: 3.0 });
+    assert_vec_eq(&vec.transform_direction(&transform), &Vec3 { x: 2.0, y: 0.0, z: 0.0 });
+}
+
 fn round_3_decimals(x: f32) -> f32 {
     (x * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+fn assert_matrix_eq(a: &Matrix, b: &Matrix) {
+    for i in 0..BUFFER_SIZE {
+        assert!((a.0[i] - b.0[i]).abs() < 0.0001, "{:?} != {:?}", a, b);
+    }
+}
+
+#[cfg(test)]
+fn assert_vec_eq(a: &Vec3, b: &Vec3) {
+    assert!((a - b).len() < 0.0001, "{:?} != {:?}", a, b);
 }
\ No newline at end of file
@@ -1,19 +1,27 @@
 use rand::rngs::SmallRng;
 //use flamer::flame;
-use std::fs;
+use std::fs::{self,File};
+use std::io::BufReader;
+use std::path::Path;
 use std::collections::HashMap;
 
 use crate::vec3::Vec3;
 use crate::ray::Ray;
-use crate::object::{Object,ObjectEnum};
-use crate::intersection::Intersection;
+use crate::object::Object;
+use crate::world::World;
+use crate::intersection::{Intersection,Interval};
 use crate::material::Material;
-use crate::utils::{plane_intersection,adjusted_for_normal,color_to_normal};
-use crate::sphere::Sphere;
-use crate::obj_parser::{parse,LineType};
+use crate::utils::plane_intersection;
+use crate::bounding_box::BoundingBox;
+use crate::bvh::Bvh;
+use crate::mesh_cache::{self,CacheWriter,CacheReader};
+use crate::obj_parser::{ObjReader,LineType};
 use crate::mtl_parser::{load_and_parse};
+use crate::{ply_parser,stl_parser};
+use crate::load_error::LoadError;
 use crate::matrix::Matrix;
 use crate::illumination::Illumination;
+use crate::render_settings::RenderSettings;
 use crate::texture::Texture;
 use crate::color::Color;
 
@@ -23,6 +31,8 @@ const DEFAULT_MATERIAL: Material = Material {
     texture_normal: None,
     texture_emission_color: None,
     texture_emission_intensity: None,
+    texture_opacity: None,
+    index_of_refraction: None,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -33,6 +43,27 @@ pub struct Face {
 
     pub mat: Option<usize>,
     pub normal: Vec3,
+
+    /**
+     * Indices into the mesh's normals for each corner, for smooth shading
+     */
+    pub vertex_normals: Option<(usize,usize,usize)>,
+
+    /**
+     * Indices into the mesh's texture coordinates for each corner
+     */
+    pub uvs: Option<(usize,usize,usize)>,
+
+    /**
+     * Faces in the same smoothing group are shaded smoothly across shared
+     * vertices (when the file doesn't give them normals)
+     */
+    pub smoothing_group: Option<u32>,
+
+    /**
+     * Indices into the mesh's tangents for each corner, for normal maps
+     */
+    pub vertex_tangents: Option<(usize,usize,usize)>,
 }
 
 pub struct Mesh {
@@ -40,46 +71,562 @@ pub struct Mesh {
     default_material: Material,
 
     vertices: Vec<Vec3>,
+    normals: Vec<Vec3>,
     faces: Vec<Face>,
     uv_coords: Vec<(f32,f32)>,
 
-    bounding_sphere: Sphere,
+    // which way u increases across the surface, and (in `w`, as 1 or -1)
+    // whether v increases along the normal crossed with it or against it
+    tangents: Vec<(Vec3,f32)>,
+
+    // one for each vertex, if the file gave them colors
+    colors: Vec<Color>,
+
+    bvh: Bvh,
 }
 
 impl Mesh {
 
-    pub fn from_obj(path: &str, transform: &Matrix, default_material: Option<Material>) -> Self {
-        let data = fs::read_to_string(path).expect("Failed to open mesh file");
+    /**
+     * Load every face in an OBJ file as one mesh
+     */
+    pub fn from_obj(path: &str, transform: &Matrix, default_material: Option<Material>) -> Result<Self,LoadError> {
+        Ok(ObjContents::load(path, transform)?.into_mesh(default_material))
+    }
+
+    /**
+     * Like `from_obj()`, but the mesh and its BVH are kept in a binary
+     * cache beside the file (see `mesh_cache`), which is read instead of
+     * the file for as long as the file stays the same. Materials are
+     * still loaded from their MTL files, since textures aren't cached.
+     */
+    pub fn from_obj_cached(path: &str, transform: &Matrix, default_material: Option<Material>) -> Result<Self,LoadError> {
+        match Self::read_cache(path, transform, default_material.clone()) {
+            Ok(Some(mesh)) => return Ok(mesh),
+            Ok(None) => (),
+            Err(err) => println!("WARNING: Ignoring mesh cache: {}", err),
+        }
+
+        let contents = ObjContents::load(path, transform)?;
+        let material_sources = contents.material_sources.clone();
+        let mesh = contents.into_mesh(default_material);
+
+        if let Err(err) = mesh.write_cache(path, transform, &material_sources) {
+            println!("WARNING: Failed to write mesh cache: {}", err);
+        }
+
+        Ok(mesh)
+    }
+
+    /**
+     * Load each object (`o`) in an OBJ file as its own mesh, with its name.
+     * Files without any objects are split by group (`g`) instead. Faces
+     * before the first object or group are named "default".
+     */
+    pub fn from_obj_objects(path: &str, transform: &Matrix, default_material: Option<Material>) -> Result<Vec<(String,Self)>,LoadError> {
+        let contents = ObjContents::load(path, transform)?;
+
+        println!("Building BVHs for {} faces...", contents.faces.len());
+
+        Ok((0..contents.names.len())
+            .filter_map(|name_index| {
+                let face_indices: Vec<usize> = (0..contents.faces.len())
+                    .filter(|&face_index| contents.face_names[face_index] == name_index)
+                    .collect();
+                if face_indices.is_empty() {
+                    return None;
+                }
+
+                Some((contents.names[name_index].clone(), contents.extract(&face_indices, default_material.clone())))
+            })
+            .collect())
+    }
+
+    /**
+     * Load an OBJ, PLY or STL file, going by its extension (anything else
+     * is read as OBJ). OBJ files are cached, as in `from_obj_cached()`.
+     */
+    pub fn from_file(path: &str, transform: &Matrix, default_material: Option<Material>) -> Result<Self,LoadError> {
+        let extension = Path::new(path).extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+
+        match extension.as_deref() {
+            Some("ply") => Self::from_ply(path, transform, default_material),
+            Some("stl") => Self::from_stl(path, transform, default_material),
+            _ => Self::from_obj_cached(path, transform, default_material),
+        }
+    }
+
+    /**
+     * Load a PLY file, ASCII or binary. Its vertex normals (if it has any)
+     * are used for smooth shading, and its vertex colors tint the material.
+     */
+    pub fn from_ply(path: &str, transform: &Matrix, default_material: Option<Material>) -> Result<Self,LoadError> {
+        let data = read_file(path)?;
+
+        println!("Loading ply...");
+
+        let ply = ply_parser::parse(&data).map_err(|err| err.in_file(path))?;
+
+        // normals don't move, and stretch the opposite way to the surface
+        let normal_transform = transform.normal_matrix().unwrap_or(*transform);
+
+        let vertices: Vec<Vec3> = ply.vertices.iter().map(|vertex| vertex.transform_point(transform)).collect();
+        let normals: Vec<Vec3> = ply.normals.unwrap_or_default().iter()
+            .map(|normal| normal.transform_direction(&normal_transform).normalized())
+            .collect();
+        let faces = polygon_faces(&vertices, ply.faces.iter().map(Vec::as_slice), !normals.is_empty());
+
+        println!("Building BVH for {} faces...", faces.len());
+
+        let mut mesh = Self::new(Vec::new(), default_material, vertices, normals, faces, Vec::new(), Vec::new());
+        mesh.colors = ply.colors.unwrap_or_default();
+
+        Ok(mesh)
+    }
+
+    /**
+     * Load an STL file, ASCII or binary. Triangles' corners that are in
+     * the same place become one vertex, so that `generate_smooth_normals()`
+     * can smooth across them.
+     */
+    pub fn from_stl(path: &str, transform: &Matrix, default_material: Option<Material>) -> Result<Self,LoadError> {
+        let data = read_file(path)?;
+
+        println!("Loading stl...");
+
+        let triangles = stl_parser::parse(&data).map_err(|err| err.in_file(path))?;
+
+        let mut vertices = Vec::new();
+        let mut vertex_indices: HashMap<(u32,u32,u32),usize> = HashMap::new();
+        let polygons: Vec<[usize;3]> = triangles.iter()
+            .map(|corners| corners.map(|corner| {
+
+                // adding zero turns -0 into 0, which have different bits
+                let key = ((corner.x + 0.0).to_bits(), (corner.y + 0.0).to_bits(), (corner.z + 0.0).to_bits());
+
+                *vertex_indices.entry(key).or_insert_with(|| {
+                    vertices.push(corner.transform_point(transform));
+                    vertices.len() - 1
+                })
+            }))
+            .collect();
+        let faces = polygon_faces(&vertices, polygons.iter().map(|polygon| &polygon[..]), false);
+
+        println!("Building BVH for {} faces...", faces.len());
+
+        Ok(Self::new(Vec::new(), default_material, vertices, Vec::new(), faces, Vec::new(), Vec::new()))
+    }
+
+    pub(crate) fn new(materials: Vec<Material>, default_material: Option<Material>, vertices: Vec<Vec3>, normals: Vec<Vec3>, faces: Vec<Face>, uv_coords: Vec<(f32,f32)>, tangents: Vec<(Vec3,f32)>) -> Self {
+        let face_bounds: Vec<BoundingBox> = faces.iter()
+            .map(|face| BoundingBox::from_points(&[vertices[face.v0], vertices[face.v1], vertices[face.v2]]))
+            .collect();
+        let bvh = Bvh::build(&face_bounds);
+
+        let mut mesh = Self {
+            materials,
+            default_material: default_material.unwrap_or(DEFAULT_MATERIAL),
+            vertices,
+            normals,
+            faces,
+            uv_coords,
+            tangents,
+            colors: Vec::new(),
+            bvh,
+        };
+
+        // faces in the same smoothing group are smooth where they meet
+        let smoothing_groups: Vec<Option<u32>> = mesh.faces.iter().map(|face| face.smoothing_group).collect();
+        if smoothing_groups.iter().any(Option::is_some) {
+            mesh.average_vertex_normals(
+                |face| smoothing_groups[face].is_some(),
+                |face, other| smoothing_groups[face] == smoothing_groups[other],
+            );
+        }
+
+        mesh
+    }
+
+    /**
+     * Write everything but the materials themselves (which are written as
+     * the library and name each one came from) to the cache for `path`
+     */
+    fn write_cache(&self, path: &str, transform: &Matrix, material_sources: &[(String,String)]) -> Result<(),LoadError> {
+        let mut writer = mesh_cache::write(path, transform)?;
+
+        writer.list(material_sources, |writer, (library, name)| {
+            writer.string(library);
+            writer.string(name);
+        });
+        writer.list(&self.vertices, CacheWriter::vec3);
+        writer.list(&self.normals, CacheWriter::vec3);
+        writer.list(&self.uv_coords, |writer, &(u, v)| {
+            writer.f32(u);
+            writer.f32(v);
+        });
+        writer.list(&self.tangents, |writer, (tangent, w)| {
+            writer.vec3(tangent);
+            writer.f32(*w);
+        });
+        writer.list(&self.colors, |writer, color| {
+            writer.f32(color.0);
+            writer.f32(color.1);
+            writer.f32(color.2);
+        });
+
+        let corners = |writer: &mut CacheWriter, (a, b, c): (usize,usize,usize)| {
+            writer.index(a);
+            writer.index(b);
+            writer.index(c);
+        };
+        writer.list(&self.faces, |writer, face| {
+            corners(writer, (face.v0, face.v1, face.v2));
+            writer.option(face.mat, CacheWriter::index);
+            writer.vec3(&face.normal);
+            writer.option(face.vertex_normals, corners);
+            writer.option(face.uvs, corners);
+            writer.option(face.smoothing_group, CacheWriter::u32);
+            writer.option(face.vertex_tangents, corners);
+        });
+
+        self.bvh.write_cache(&mut writer);
+
+        writer.save(path)
+    }
+
+    /**
+     * The mesh from the cache for `path`, or `None` if it's out of date
+     * (including when a material it uses is no longer in its library)
+     */
+    fn read_cache(path: &str, transform: &Matrix, default_material: Option<Material>) -> Result<Option<Self>,LoadError> {
+        let mut reader = match mesh_cache::read(path, transform)? {
+            Some(reader) => reader,
+            None => return Ok(None),
+        };
+
+        println!("Loading cached obj...");
+
+        let material_sources = reader.list(|reader| Ok((reader.string()?, reader.string()?)))?;
+        let mut libraries: HashMap<String,HashMap<String,Material>> = HashMap::new();
+        let mut materials = Vec::with_capacity(material_sources.len());
+        for (library, name) in material_sources {
+            if !libraries.contains_key(&library) {
+                libraries.insert(library.clone(), load_and_parse(&library)?);
+            }
+
+            match libraries[&library].get(&name) {
+                Some(material) => materials.push(material.clone()),
+                None => return Ok(None),
+            }
+        }
+
+        let vertices = reader.list(CacheReader::vec3)?;
+        let normals = reader.list(CacheReader::vec3)?;
+        let uv_coords = reader.list(|reader| Ok((reader.f32()?, reader.f32()?)))?;
+        let tangents = reader.list(|reader| Ok((reader.vec3()?, reader.f32()?)))?;
+        let colors = reader.list(|reader| Ok(Color(reader.f32()?, reader.f32()?, reader.f32()?)))?;
+
+        let corners = |reader: &mut CacheReader, len: usize| Ok((reader.index(len)?, reader.index(len)?, reader.index(len)?));
+        let faces = reader.list(|reader| {
+            let (v0, v1, v2) = corners(reader, vertices.len())?;
+
+            Ok(Face {
+                v0,
+                v1,
+                v2,
+
+                mat: reader.option(|reader| reader.index(materials.len()))?,
+                normal: reader.vec3()?,
+                vertex_normals: reader.option(|reader| corners(reader, normals.len()))?,
+                uvs: reader.option(|reader| corners(reader, uv_coords.len()))?,
+                smoothing_group: reader.option(CacheReader::u32)?,
+                vertex_tangents: reader.option(|reader| corners(reader, tangents.len()))?,
+            })
+        })?;
+
+        let bvh = Bvh::read_cache(&mut reader, faces.len())?;
+        reader.finish()?;
+
+        Ok(Some(Self {
+            materials,
+            default_material: default_material.unwrap_or(DEFAULT_MATERIAL),
+            vertices,
+            normals,
+            faces,
+            uv_coords,
+            tangents,
+            colors,
+            bvh,
+        }))
+    }
+
+    /**
+     * Give faces that have no normals from the file smooth ones, made by
+     * averaging the normals of the faces around each vertex. Faces meeting
+     * at more than `crease_angle` (in radians) aren't averaged together, so
+     * hard edges stay sharp.
+     */
+    pub fn generate_smooth_normals(&mut self, crease_angle: f32) {
+        let min_cos = crease_angle.cos();
+        let unit_normals: Vec<Vec3> = self.faces.iter().map(|face| face.normal.normalized()).collect();
+
+        self.average_vertex_normals(
+            |_| true,
+            |face, other| unit_normals[other].dot(&unit_normals[face]) >= min_cos,
+        );
+    }
+
+    /**
+     * Give each face that `fills` picks (and that has no normals yet) a
+     * normal at each corner, averaged from the faces around that vertex
+     * that it `joins` with
+     */
+    fn average_vertex_normals<F: Fn(usize) -> bool, J: Fn(usize, usize) -> bool>(&mut self, fills: F, joins: J) {
+        let mut faces_at_vertex: Vec<Vec<usize>> = vec![Vec::new(); self.vertices.len()];
+        for (face_index, face) in self.faces.iter().enumerate() {
+            faces_at_vertex[face.v0].push(face_index);
+            faces_at_vertex[face.v1].push(face_index);
+            faces_at_vertex[face.v2].push(face_index);
+        }
+
+        for face_index in 0..self.faces.len() {
+            if self.faces[face_index].vertex_normals.is_some() || !fills(face_index) {
+                continue;
+            }
+
+            let face = &self.faces[face_index];
+            let corner_normals: Vec<Vec3> = [face.v0, face.v1, face.v2].iter()
+                .map(|&vertex| {
+                    let mut sum = Vec3::new();
+
+                    // a face's unnormalized normal is as long as it is big,
+                    // so bigger faces count for more
+                    for &other in &faces_at_vertex[vertex] {
+                        if other == face_index || joins(face_index, other) {
+                            sum = &sum + &self.faces[other].normal;
+                        }
+                    }
+
+                    sum.normalized()
+                })
+                .collect();
+
+            let first = self.normals.len();
+            self.normals.extend(corner_normals);
+            let vertex_normals = (first, first + 1, first + 2);
+            self.faces[face_index].vertex_normals = Some(vertex_normals);
+        }
+    }
+    
+    /**
+     * The nearest face hit by the ray, found by walking the BVH
+     */
+    pub fn nearest_face_intersection(&self, ray: &Ray) -> Option<(Intersection,usize)> {
+        self.bvh.nearest_intersection(ray, |face_index| self.face_intersection(face_index, ray))
+    }
+
+    /**
+     * Test the ray against every face, without going through the BVH.
+     * Much slower; used as a reference for `nearest_face_intersection()` in tests and benchmarks.
+     */
+    pub fn brute_force_intersection(&self, ray: &Ray) -> Option<(Intersection,usize)> {
+        let mut nearest_intersection: Option<(Intersection,usize)> = None;
+
+        for face_index in 0..self.faces.len() {
+            if let Some(intersection) = self.face_intersection(face_index, ray) {
+                if nearest_intersection.as_ref().map(|nearest| intersection.distance < nearest.0.distance).unwrap_or(true) {
+                    nearest_intersection = Some((intersection, face_index));
+                }
+            }
+        }
+
+        nearest_intersection
+    }
+
+    fn face_intersection(&self, face_index: usize, ray: &Ray) -> Option<Intersection> {
+        let face = &self.faces[face_index];
+
+        let mut intersection = plane_intersection(&self.vertices[face.v0], &face.normal, ray)?;
+        let (b1, b2) = self.barycentric(face, &intersection.position)?;
+
+        intersection.face_index = Some(face_index);
+        intersection.barycentric = Some((b1, b2));
+        intersection.uv = self.interpolated_uv(face, b1, b2);
+
+        if let Some((n0, n1, n2)) = face.vertex_normals {
+            let normal = &(&(&self.normals[n0] * (1.0 - b1 - b2)) + &(&self.normals[n1] * b1)) + &(&self.normals[n2] * b2);
+            intersection.normal = normal.normalized();
+        }
+
+        if !self.colors.is_empty() {
+            let (c0, c1, c2) = (self.colors[face.v0], self.colors[face.v1], self.colors[face.v2]);
+            intersection.vertex_color = Some(c0 * (1.0 - b1 - b2) + c1 * b1 + c2 * b2);
+        }
+
+        if let Some((t0, t1, t2)) = face.vertex_tangents {
+            let tangent = &(&(&self.tangents[t0].0 * (1.0 - b1 - b2)) + &(&self.tangents[t1].0 * b1)) + &(&self.tangents[t2].0 * b2);
+
+            // kept perpendicular to the (interpolated) normal
+            let normal = &intersection.normal;
+            let tangent = (&tangent - &(normal * normal.dot(&tangent))).normalized();
+            let bitangent = &normal.cross(&tangent) * self.tangents[t0].1;
+            intersection.tangent_frame = Some((tangent, bitangent));
+        }
+
+        Some(intersection)
+    }
+
+    /**
+     * The barycentric coordinates of a point in the face's plane, or `None`
+     * if it's outside of the face
+     */
+    fn barycentric(&self, face: &Face, point: &Vec3) -> Option<(f32,f32)> {
+        let vert0 = &self.vertices[face.v0];
+        let vert1 = &self.vertices[face.v1];
+        let vert2 = &self.vertices[face.v2];
+
+        let edge0 = vert1 - vert0;
+        let edge1 = vert2 - vert1;
+        let edge2 = vert0 - vert2;
+        let c0 = point - vert0;
+        let c1 = point - vert1;
+        let c2 = point - vert2;
+        let area0 = face.normal.dot(&edge0.cross(&c0));
+        let area1 = face.normal.dot(&edge1.cross(&c1));
+        let area2 = face.normal.dot(&edge2.cross(&c2));
+        if area0 > 0.0 && area1 > 0.0 && area2 > 0.0 {
+
+            // P is inside the triangle; the sub-triangle opposite each 
+            // vertex gives its weight
+            let total_area = area0 + area1 + area2;
+            Some((area2 / total_area, area0 / total_area))
+        } else {
+            None
+        }
+    }
+
+    /**
+     * The face's texture coordinates blended at a point, wrapped to repeat
+     * the texture. OBJ puts v = 0 at the bottom of the image, where textures
+     * start from the top, so v is flipped.
+     */
+    fn interpolated_uv(&self, face: &Face, b1: f32, b2: f32) -> (f32,f32) {
+        let (t0, t1, t2) = match face.uvs {
+            Some(uvs) => uvs,
+            None => return (0.0, 0.0),
+        };
+        let (uv0, uv1, uv2) = (self.uv_coords[t0], self.uv_coords[t1], self.uv_coords[t2]);
+        let b0 = 1.0 - b1 - b2;
+
+        let u = b0 * uv0.0 + b1 * uv1.0 + b2 * uv2.0;
+        let v = 1.0 - (b0 * uv0.1 + b1 * uv1.1 + b2 * uv2.1);
+
+        (u - u.floor(), v - v.floor())
+    }
+
+    fn material_for_face_index(&self, index: usize) -> &Material {
+        let face = &self.faces[index];
+        return face.mat.map(|i| self.materials.get(i).unwrap_or(&self.default_material)).unwrap_or(&self.default_material);
+    }
+}
+
+/**
+ * Everything in an OBJ file, before it's made into meshes
+ */
+struct ObjContents {
+    materials: Vec<Material>,
+    vertices: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    faces: Vec<Face>,
+    uv_coords: Vec<(f32,f32)>,
+
+    // the objects (or groups) faces can be in, and which one each face is in
+    names: Vec<String>,
+    face_names: Vec<usize>,
+
+    // the library each material was loaded from, and its name there
+    material_sources: Vec<(String,String)>,
+}
+
+impl ObjContents {
+
+    fn load(path: &str, transform: &Matrix) -> Result<Self,LoadError> {
+        let file = File::open(path).map_err(|err| LoadError::Io {
+            path: String::from(path),
+            message: err.to_string(),
+        })?;
+        let mut reader = ObjReader::new(BufReader::new(file));
 
         println!("Loading obj...");
 
         let mut vertices = Vec::new();
+        let mut normals = Vec::new();
         let mut faces = Vec::new();
-        let uv_coords = Vec::new();
+        let mut uv_coords = Vec::new();
 
         let mut materials: Vec<Material> = Vec::new();
         let mut material_names: HashMap<String,usize> = HashMap::new();
+        let mut material_sources = Vec::new();
+
+        // faces are named after their object, or their group if there are no
+        // objects, which isn't known until the end
+        let mut object_names = vec![String::from("default")];
+        let mut group_names = vec![String::from("default")];
+        let mut face_objects = Vec::new();
+        let mut face_groups = Vec::new();
+
+        // normals don't move, and stretch the opposite way to the surface
+        let normal_transform = transform.normal_matrix().unwrap_or(*transform);
 
         let mut current_mat: Option<usize> = None;
-        for line in parse(&data) {
+        let mut smoothing_group: Option<u32> = None;
+        while let Some(line) = reader.next_line().map_err(|err| err.in_file(path))? {
             match line {
-                LineType::Vertex(x, y, z) => vertices.push(Vec3 { x, y, z }.transformed(transform)),
-                LineType::Face(v0, v1, v2) => {
-
-                    let vert0 = &vertices[v0.0];
-                    let vert1 = &vertices[v1.0];
-                    let vert2 = &vertices[v2.0];
-                    let normal = triangle_normal(&vert0, &vert1, &vert2);
-
-                    faces.push(Face {
-                        v0: v0.0, 
-                        v1: v1.0,
-                        v2: v2.0,
-
-                        mat: current_mat,
-                        normal,
-                    });
+                LineType::Vertex(x, y, z) => vertices.push(Vec3 { x, y, z }.transform_point(transform)),
+                LineType::VertexNormal(x, y, z) => normals.push(Vec3 { x, y, z }.transform_direction(&normal_transform).normalized()),
+                LineType::VertexTexture(u, v) => uv_coords.push((u, v)),
+                LineType::Face(corners) => {
+
+                    // polygons are split into a fan of triangles around their
+                    // first corner
+                    for i in 1..corners.len().saturating_sub(1) {
+                        let (v0, v1, v2) = (corners[0], corners[i], corners[i + 1]);
+
+                        let vert0 = &vertices[v0.0];
+                        let vert1 = &vertices[v1.0];
+                        let vert2 = &vertices[v2.0];
+                        let normal = triangle_normal(&vert0, &vert1, &vert2);
+
+                        // only used if every corner has one
+                        let vertex_normals = match (v0.2, v1.2, v2.2) {
+                            (Some(n0), Some(n1), Some(n2)) if n0.max(n1).max(n2) < normals.len() => Some((n0, n1, n2)),
+                            _ => None,
+                        };
+                        let uvs = match (v0.1, v1.1, v2.1) {
+                            (Some(t0), Some(t1), Some(t2)) if t0.max(t1).max(t2) < uv_coords.len() => Some((t0, t1, t2)),
+                            _ => None,
+                        };
+
+                        faces.push(Face {
+                            v0: v0.0, 
+                            v1: v1.0,
+                            v2: v2.0,
+
+                            mat: current_mat,
+                            normal,
+                            vertex_normals,
+                            uvs,
+                            smoothing_group,
+                            vertex_tangents: None,
+                        });
+                        face_objects.push(object_names.len() - 1);
+                        face_groups.push(group_names.len() - 1);
+                    }
                 },
+                LineType::Object(name) => object_names.push(String::from(name)),
+                LineType::Group(name) => group_names.push(String::from(name)),
+                LineType::SmoothingGroup(group) => smoothing_group = group,
                 LineType::MTLib(file) => {
                     let segments: Vec<&str> = path.split("/").collect();
                     let mut local_dir = String::new();
@@ -88,149 +635,445 @@ impl Mesh {
                         local_dir += "/";
                     }
 
-                    for (name, mat) in load_and_parse(&(local_dir + &file)) {
-                        material_names.insert(name, materials.len());
+                    let library = local_dir + file;
+                    for (name, mat) in load_and_parse(&library)? {
+                        material_names.insert(name.clone(), materials.len());
                         materials.push(mat);
+                        material_sources.push((library.clone(), name));
                     }
                 },
-                LineType::UseMaterial(name) => current_mat = material_names.get(&name).map(|x| *x),
+                LineType::UseMaterial(name) => current_mat = material_names.get(name).copied(),
                 _ => ()
             }
         }
 
-        let bounding_sphere = get_bounding_sphere(&vertices);
-        
-        return Self {
+        let (names, face_names) = if object_names.len() > 1 {
+            (object_names, face_objects)
+        } else {
+            (group_names, face_groups)
+        };
+
+        Ok(Self {
             materials,
-            default_material: default_material.unwrap_or(DEFAULT_MATERIAL),
             vertices,
+            normals,
             faces,
             uv_coords,
-            bounding_sphere,
-        }
+            names,
+            face_names,
+            material_sources,
+        })
     }
-    
-    fn inner_intersection(&self, ray: &Ray) -> Option<(Intersection,usize)> {
-//        flame::start("Mesh::sphere_intersect");
-        //let sphere_intersect = self.bounding_sphere.intersection(ray);
-//        flame::end("Mesh::sphere_intersect");
 
-        if self.bounding_sphere.intersection(ray).is_none() {
-            return None;
-        } else {
-            let mut nearest_intersection: Option<(Intersection,usize)> = None;
+    fn into_mesh(self, default_material: Option<Material>) -> Mesh {
+        println!("Building BVH for {} faces...", self.faces.len());
 
-            for face_index in 0..self.faces.len() {
+        Mesh::new(self.materials, default_material, self.vertices, self.normals, self.faces, self.uv_coords, Vec::new())
+    }
+
+    /**
+     * Make a mesh out of some of the faces, with only the vertices, normals
+     * and texture coordinates they use
+     */
+    fn extract(&self, face_indices: &[usize], default_material: Option<Material>) -> Mesh {
+        let mut vertices = Vec::new();
+        let mut normals = Vec::new();
+        let mut uv_coords = Vec::new();
+        let mut vertex_map = HashMap::new();
+        let mut normal_map = HashMap::new();
+        let mut uv_map = HashMap::new();
+
+        fn remap<T: Copy>(index: usize, from: &[T], to: &mut Vec<T>, map: &mut HashMap<usize,usize>) -> usize {
+            *map.entry(index).or_insert_with(|| {
+                to.push(from[index]);
+                to.len() - 1
+            })
+        }
+
+        let faces = face_indices.iter()
+            .map(|&face_index| {
                 let face = &self.faces[face_index];
 
-                let vert0 = &self.vertices[face.v0];
-                let vert1 = &self.vertices[face.v1];
-                let vert2 = &self.vertices[face.v2];
-
-                let distance_squared = nearest_intersection.as_ref().map(|i| i.0.distance * i.0.distance);
-                if distance_squared.is_none() ||
-                        (vert0 - &ray.origin).len_squared() < distance_squared.unwrap() ||
-                        (vert1 - &ray.origin).len_squared() < distance_squared.unwrap() ||
-                        (vert2 - &ray.origin).len_squared() < distance_squared.unwrap() {
-
-//                    flame::start("Mesh::plane_intersection");
-                    let plane_intersect = plane_intersection(&vert0, &face.normal, ray);
-//                    flame::end("Mesh::plane_intersection");
-                    match plane_intersect {
-                        Some(intersection) => {
-                            if intersection.distance > 0.0 && nearest_intersection.as_ref().map(|nearest| intersection.distance < nearest.0.distance).unwrap_or(true) {
-                                let edge0 = vert1 - vert0; 
-                                let edge1 = vert2 - vert1; 
-                                let edge2 = vert0 - vert2; 
-                                let c0 = &intersection.position - vert0; 
-                                let c1 = &intersection.position - vert1; 
-                                let c2 = &intersection.position - vert2; 
-                                if  face.normal.dot(&edge0.cross(&c0)) > 0.0 && 
-                                    face.normal.dot(&edge1.cross(&c1)) > 0.0 && 
-                                    face.normal.dot(&edge2.cross(&c2)) > 0.0 {
-    
-                                    nearest_intersection = Some((intersection, face_index)); // P is inside the triangle
-                                }
-                            }
-                        },
-                        None => ()
-                    };
+                Face {
+                    v0: remap(face.v0, &self.vertices, &mut vertices, &mut vertex_map),
+                    v1: remap(face.v1, &self.vertices, &mut vertices, &mut vertex_map),
+                    v2: remap(face.v2, &self.vertices, &mut vertices, &mut vertex_map),
+                    vertex_normals: face.vertex_normals.map(|(n0, n1, n2)| (
+                        remap(n0, &self.normals, &mut normals, &mut normal_map),
+                        remap(n1, &self.normals, &mut normals, &mut normal_map),
+                        remap(n2, &self.normals, &mut normals, &mut normal_map),
+                    )),
+                    uvs: face.uvs.map(|(t0, t1, t2)| (
+                        remap(t0, &self.uv_coords, &mut uv_coords, &mut uv_map),
+                        remap(t1, &self.uv_coords, &mut uv_coords, &mut uv_map),
+                        remap(t2, &self.uv_coords, &mut uv_coords, &mut uv_map),
+                    )),
+                    ..face.clone()
                 }
+            })
+            .collect();
+
+        Mesh::new(self.materials.clone(), default_material, vertices, normals, faces, uv_coords, Vec::new())
+    }
+}
+
+impl Object for Mesh {
+
+//    #[flame("Mesh")]
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        self.nearest_face_intersection(ray)
+            .map(|(intersection, _)| intersection)
+    }
 
+    /**
+     * Meshes are assumed to be closed, so that every other face crossed is
+     * on the way out
+     */
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let mut hits: Vec<Intersection> = Vec::new();
+        self.bvh.for_each_candidate(ray, |face_index| {
+            if let Some(intersection) = self.face_intersection(face_index, ray) {
+                hits.push(intersection);
             }
+        });
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
 
-            return nearest_intersection;
+        let mut intervals = Vec::with_capacity(hits.len() / 2 + 1);
+        let starts_inside = hits.len() % 2 == 1;
+        let mut hits = hits.into_iter();
+
+        // only hits ahead of the ray are found, so an odd number of them 
+        // means it started inside
+        if starts_inside {
+            intervals.push(Interval::ending_at(hits.next().unwrap()));
+        }
+        while let (Some(entry), Some(exit)) = (hits.next(), hits.next()) {
+            intervals.push(Interval::new(entry, exit));
         }
+
+        intervals
     }
 
-    fn material_for_face_index(&self, index: usize) -> &Material {
-        let face = &self.faces[index];
-        return face.mat.map(|i| self.materials.get(i).unwrap_or(&self.default_material)).unwrap_or(&self.default_material);
+    /**
+     * Hits get their texture coordinates from the face they're on; for an
+     * arbitrary point, this looks for the nearest face it lies over
+     */
+//    #[flame("Mesh")]
+    fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
+        let mut nearest: Option<(f32,(f32,f32))> = None;
+
+        for face in &self.faces {
+            if let Some((b1, b2)) = self.barycentric(face, point) {
+                let distance = face.normal.dot(&(point - &self.vertices[face.v0])).abs() / face.normal.len();
+                if nearest.map(|(nearest_distance, _)| distance < nearest_distance).unwrap_or(true) {
+                    nearest = Some((distance, self.interpolated_uv(face, b1, b2)));
+                }
+            }
+        }
+
+        nearest.map(|(_, uv)| uv).unwrap_or((0.0, 0.0))
     }
-}
 
-fn get_bounding_sphere(vertices: &Vec<Vec3>) -> Sphere {
-    let mut min = Vec3::new();
-    let mut max = Vec3::new();
+    fn bounds(&self) -> Option<BoundingBox> {
+        Some(self.bvh.bounds())
+    }
 
-    for v in vertices {
-        min.x = f32::min(min.x, v.x);
-        min.y = f32::min(min.y, v.y);
-        min.z = f32::min(min.z, v.z);
+//    #[flame("Mesh")]
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+        let material = intersection.face_index
+            .map(|face_index| self.material_for_face_index(face_index))
+            .unwrap_or(&self.default_material);
 
-        max.x = f32::max(max.x, v.x);
-        max.y = f32::max(max.y, v.y);
-        max.z = f32::max(max.z, v.z);
+        material.shade(intersection, world, settings, rng, bounces_remaining)
     }
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>,LoadError> {
+    fs::read(path).map_err(|err| LoadError::Io {
+        path: String::from(path),
+        message: err.to_string(),
+    })
+}
+
+/**
+ * Faces for polygons given as indices into `vertices`, each split into a
+ * fan of triangles around its first corner. Normals, if there are any,
+ * line up with the vertices.
+ */
+fn polygon_faces<'a, I: IntoIterator<Item = &'a [usize]>>(vertices: &[Vec3], polygons: I, has_normals: bool) -> Vec<Face> {
+    let mut faces = Vec::new();
 
-    let center = Vec3 {
-        x: (min.x + max.x) / 2.0,
-        y: (min.y + max.y) / 2.0,
-        z: (min.z + max.z) / 2.0,
-    };
+    for polygon in polygons {
+        for i in 1..polygon.len().saturating_sub(1) {
+            let (v0, v1, v2) = (polygon[0], polygon[i], polygon[i + 1]);
 
-    let mut radius = 0.0;
-    for v in vertices {
-        radius = f32::max(radius, (v - &center).len());
+            faces.push(Face {
+                v0,
+                v1,
+                v2,
+
+                mat: None,
+                normal: triangle_normal(&vertices[v0], &vertices[v1], &vertices[v2]),
+                vertex_normals: if has_normals { Some((v0, v1, v2)) } else { None },
+                uvs: None,
+                smoothing_group: None,
+                vertex_tangents: None,
+            });
+        }
     }
 
-    return Sphere::new(center, radius + 0.001, Material::new());
+    faces
 }
 
-impl Object for Mesh {
+pub(crate) fn triangle_normal(vert0: &Vec3, vert1: &Vec3, vert2: &Vec3) -> Vec3 {
+    (vert1 - vert0).cross(&(vert2 - vert0))
+}
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+    use rand::rngs::SmallRng;
 
-//    #[flame("Mesh")]
-    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
-        self.inner_intersection(ray)
-            .map(|mut intersection| {
-
-                self.material_for_face_index(intersection.1).texture_normal.as_ref().map(|texture_normal| {
-                    let normal_color = texture_normal.color_at(self.texture_coordinate(&intersection.0.position));
-                    intersection.0.normal = adjusted_for_normal(&intersection.0.normal, &color_to_normal(&normal_color));
-                });
-                
-                intersection.0
-            })
+    use std::fs;
+    use std::path::Path;
+
+    use crate::vec3::Vec3;
+    use crate::ray::Ray;
+    use crate::matrix::Matrix;
+    use crate::mesh::Mesh;
+    use crate::mesh_cache;
+    use crate::object::Object;
+
+    #[test]
+    fn test_bvh_matches_brute_force() {
+        let mesh = Mesh::from_obj(concat!(env!("CARGO_MANIFEST_DIR"), "/test.obj"), &Matrix::new(), None).unwrap();
+        let bounds = mesh.bounds().unwrap();
+        let center = bounds.centroid();
+        let radius = bounds.size().len();
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut hits = 0;
+
+        for _ in 0..2000 {
+            // from somewhere around the mesh, towards somewhere inside its bounds
+            let origin = &center + &(&Ray::random_direction(Vec3::new(), &mut rng).direction * radius);
+            let target = Vec3 {
+                x: bounds.min.x + rng.gen::<f32>() * (bounds.max.x - bounds.min.x),
+                y: bounds.min.y + rng.gen::<f32>() * (bounds.max.y - bounds.min.y),
+                z: bounds.min.z + rng.gen::<f32>() * (bounds.max.z - bounds.min.z),
+            };
+            let ray = Ray { origin, direction: (&target - &origin).normalized() };
+
+            let expected = mesh.brute_force_intersection(&ray);
+            let actual = mesh.nearest_face_intersection(&ray);
+
+            assert_eq!(expected.is_some(), actual.is_some());
+            if let (Some(expected), Some(actual)) = (expected, actual) {
+                assert!((expected.0.distance - actual.0.distance).abs() < 0.0001);
+                assert_eq!(actual.0.face_index, Some(actual.1));
+                hits += 1;
+            }
+        }
+
+        assert!(hits > 0);
     }
 
-//    #[flame("Mesh")]
-    fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
-        // TODO
-        (0.0, 0.0)
+    #[test]
+    fn test_barycentric_coordinates() {
+        let mesh = Mesh::from_obj(concat!(env!("CARGO_MANIFEST_DIR"), "/test.obj"), &Matrix::new(), None).unwrap();
+
+        for face_index in 0..mesh.faces.len() {
+            let face = &mesh.faces[face_index];
+            let vert0 = &mesh.vertices[face.v0];
+            let vert1 = &mesh.vertices[face.v1];
+            let vert2 = &mesh.vertices[face.v2];
+
+            // aim at a known point inside the face
+            let target = &(&(vert0 * 0.2) + &(vert1 * 0.3)) + &(vert2 * 0.5);
+            let origin = &target + &face.normal;
+            let ray = Ray { origin, direction: (&target - &origin).normalized() };
+
+            if let Some(intersection) = mesh.face_intersection(face_index, &ray) {
+                let (b1, b2) = intersection.barycentric.unwrap();
+                assert!((b1 - 0.3).abs() < 0.001 && (b2 - 0.5).abs() < 0.001);
+            }
+        }
     }
 
-//    #[flame("Mesh")]
-    fn shade(&self, ray: &Ray, objs: &Vec<ObjectEnum>, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
-        let mut intersection = self.inner_intersection(ray).unwrap();
+    #[test]
+    fn test_smooth_normals() {
+        let mut mesh = Mesh::from_obj(concat!(env!("CARGO_MANIFEST_DIR"), "/test.obj"), &Matrix::new(), None).unwrap();
+
+        // the file's normals are blended across each face
+        for face_index in 0..mesh.faces.len() {
+            let face = &mesh.faces[face_index];
+            let (n0, n1, n2) = face.vertex_normals.unwrap();
+            let target = &(&(&mesh.vertices[face.v0] * 0.2) + &(&mesh.vertices[face.v1] * 0.3)) + &(&mesh.vertices[face.v2] * 0.5);
+            let origin = &target + &face.normal;
+            let ray = Ray { origin, direction: (&target - &origin).normalized() };
+
+            if let Some(intersection) = mesh.face_intersection(face_index, &ray) {
+                let expected = (&(&(&mesh.normals[n0] * 0.2) + &(&mesh.normals[n1] * 0.3)) + &(&mesh.normals[n2] * 0.5)).normalized();
+                assert!((&intersection.normal - &expected).len() < 0.001);
+            }
+        }
+
+        // with no crease, every face around a vertex gets the same normal there
+        for face in mesh.faces.iter_mut() {
+            face.vertex_normals = None;
+        }
+        mesh.generate_smooth_normals(std::f32::consts::PI);
 
-        let material = self.material_for_face_index(intersection.1);
+        let mut normal_at_vertex = vec![None; mesh.vertices.len()];
+        for face in &mesh.faces {
+            let (n0, n1, n2) = face.vertex_normals.unwrap();
+            for &(vertex, normal) in [(face.v0, n0), (face.v1, n1), (face.v2, n2)].iter() {
+                let normal = mesh.normals[normal];
+                let expected = *normal_at_vertex[vertex].get_or_insert(normal);
+                assert!((&normal - &expected).len() < 0.001);
+            }
+        }
 
-        let uv = self.texture_coordinate(&intersection.0.position);
+        // and with a tiny crease, every face stays flat
+        for face in mesh.faces.iter_mut() {
+            face.vertex_normals = None;
+        }
+        mesh.generate_smooth_normals(0.001);
 
-        material.shade(&mut intersection.0, uv, objs, rng, bounces_remaining)
+        for face in &mesh.faces {
+            let (n0, _, _) = face.vertex_normals.unwrap();
+            assert!((&mesh.normals[n0] - &face.normal.normalized()).len() < 0.01);
+        }
     }
-}
 
-fn triangle_normal(vert0: &Vec3, vert1: &Vec3, vert2: &Vec3) -> Vec3 {
-    (vert1 - vert0).cross(&(vert2 - vert0))
-}
\ No newline at end of file
+    #[test]
+    fn test_texture_coordinates() {
+        let mesh = Mesh::from_obj(concat!(env!("CARGO_MANIFEST_DIR"), "/Geometric.obj"), &Matrix::new(), None).unwrap();
+
+        let mut hits = 0;
+        for face_index in 0..mesh.faces.len() {
+            let face = &mesh.faces[face_index];
+            let (t0, t1, t2) = face.uvs.unwrap();
+            let target = &(&(&mesh.vertices[face.v0] * 0.2) + &(&mesh.vertices[face.v1] * 0.3)) + &(&mesh.vertices[face.v2] * 0.5);
+            let origin = &target + &face.normal;
+            let ray = Ray { origin, direction: (&target - &origin).normalized() };
+
+            if let Some(intersection) = mesh.face_intersection(face_index, &ray) {
+                let u = mesh.uv_coords[t0].0 * 0.2 + mesh.uv_coords[t1].0 * 0.3 + mesh.uv_coords[t2].0 * 0.5;
+                let v = 1.0 - (mesh.uv_coords[t0].1 * 0.2 + mesh.uv_coords[t1].1 * 0.3 + mesh.uv_coords[t2].1 * 0.5);
+                assert!((intersection.uv.0 - (u - u.floor())).abs() < 0.001);
+                assert!((intersection.uv.1 - (v - v.floor())).abs() < 0.001);
+                hits += 1;
+            }
+        }
+
+        assert!(hits > 0);
+    }
+
+    #[test]
+    fn test_objects() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/test.obj");
+        let whole = Mesh::from_obj(path, &Matrix::new(), None).unwrap();
+        let objects = Mesh::from_obj_objects(path, &Matrix::new(), None).unwrap();
+
+        let names: Vec<&str> = objects.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["Book_Plane.001", "Page_Plane.002", "Shine_Plane", "Fox_Cube.003", "Flower_Sphere.002", "Box_Cube"]); // the curve has no faces
+        assert_eq!(objects.iter().map(|(_, mesh)| mesh.faces.len()).sum::<usize>(), whole.faces.len());
+
+        // each object only keeps the vertices it uses
+        let (_, fox) = &objects[3];
+        assert!(fox.vertices.len() < whole.vertices.len());
+        let ray = Ray {
+            origin: &fox.bounds().unwrap().centroid() + &Vec3 { x: 0.0, y: 0.0, z: 100.0 },
+            direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+        };
+        let expected = fox.brute_force_intersection(&ray);
+        assert_eq!(expected.is_some(), fox.intersection(&ray).is_some());
+    }
+
+    #[test]
+    fn test_cache() {
+
+        // a copy of the tree, so that its cache isn't written into the repo
+        let dir = std::env::temp_dir().join(format!("raytracer-test-cache-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for file in &["tree.obj", "tree.mtl"] {
+            fs::copy(Path::new(env!("CARGO_MANIFEST_DIR")).join(file), dir.join(file)).unwrap();
+        }
+        let path = dir.join("tree.obj");
+        let path = path.to_str().unwrap();
+
+        let transform = Matrix::scale(&Vec3::from_scalar(2.0));
+        let loaded = Mesh::from_obj_cached(path, &transform, None).unwrap();
+        let cached = Mesh::read_cache(path, &transform, None).unwrap().unwrap();
+
+        assert_eq!(cached.vertices, loaded.vertices);
+        assert_eq!(cached.normals, loaded.normals);
+        assert_eq!(cached.uv_coords, loaded.uv_coords);
+        assert_eq!(cached.faces, loaded.faces);
+
+        // faces keep the same materials, even though they're loaded again
+        let albedo = |mesh: &Mesh, face_index: usize| mesh.material_for_face_index(face_index).texture_albedo.as_ref().map(|texture| texture.color_at((0.0, 0.0)));
+        assert_eq!(cached.materials.len(), loaded.materials.len());
+        for face_index in (0..loaded.faces.len()).step_by(97) {
+            assert_eq!(albedo(&cached, face_index), albedo(&loaded, face_index));
+        }
+
+        let bounds = loaded.bounds().unwrap();
+        let ray = Ray {
+            origin: &bounds.centroid() + &Vec3 { x: 0.0, y: 0.0, z: bounds.size().len() },
+            direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+        };
+        let hit = |mesh: &Mesh| mesh.nearest_face_intersection(&ray).map(|(intersection, face_index)| (intersection.distance, face_index));
+        assert!(hit(&loaded).is_some());
+        assert_eq!(hit(&cached), hit(&loaded));
+
+        // loading it another way makes the cache out of date
+        assert!(Mesh::read_cache(path, &Matrix::new(), None).unwrap().is_none());
+
+        let cache_path = mesh_cache::cache_path(path);
+        let data = fs::read(&cache_path).unwrap();
+
+        // the BVH's nodes come last, each 33 bytes after their count; point
+        // the root back at itself
+        let node_size = 1 + 24 + 8;
+        let count_at = |offset: usize| u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]) as usize;
+        let node_count = (1..data.len() / node_size).find(|&count| count_at(data.len() - count * node_size - 4) == count).unwrap();
+        let root = data.len() - node_count * node_size;
+        let mut cyclic = data.clone();
+        cyclic[root + 25..root + 29].copy_from_slice(&0u32.to_le_bytes());
+        fs::write(&cache_path, &cyclic).unwrap();
+        let err = Mesh::read_cache(path, &transform, None).err().unwrap().to_string();
+        assert!(err.ends_with("the cache's BVH refers to nodes or faces that aren't there"));
+
+        fs::write(&cache_path, &data[..data.len() - 1]).unwrap();
+        let err = Mesh::read_cache(path, &transform, None).err().unwrap().to_string();
+        assert_eq!(err, format!("\"{}\": the cache ends early", cache_path));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_ply_and_stl() {
+        let ply = Mesh::from_ply(concat!(env!("CARGO_MANIFEST_DIR"), "/test.ply"), &Matrix::new(), None).unwrap();
+        let stl = Mesh::from_stl(concat!(env!("CARGO_MANIFEST_DIR"), "/test.stl"), &Matrix::new(), None).unwrap();
+
+        // the STL's corners are joined back up into the tetrahedron's four
+        assert_eq!(ply.vertices.len(), 4);
+        assert_eq!(stl.vertices.len(), 4);
+        assert_eq!(stl.faces.len(), 4);
+
+        // the slanted face is red, green and blue at its corners
+        let ray = Ray {
+            origin: Vec3::from_scalar(1.0),
+            direction: Vec3::from_scalar(-1.0).normalized(),
+        };
+        let hit = ply.intersection(&ray).unwrap();
+        let color = hit.vertex_color.unwrap();
+        assert!((color.0 - 1.0 / 3.0).abs() < 0.001 && (color.1 - 1.0 / 3.0).abs() < 0.001 && (color.2 - 1.0 / 3.0).abs() < 0.001);
+        assert!(hit.normal.dot(&ray.direction) < 0.0);
+
+        let hit = stl.intersection(&ray).unwrap();
+        assert!(hit.vertex_color.is_none());
+        assert!((&hit.position - &Vec3::from_scalar(1.0 / 3.0)).len() < 0.01);
+    }
+}
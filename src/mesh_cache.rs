@@ -0,0 +1,274 @@
+use std::fs;
+use std::time::UNIX_EPOCH;
+
+use crate::vec3::Vec3;
+use crate::matrix::Matrix;
+use crate::load_error::LoadError;
+
+// at the start of every cache file, followed by the format version, which
+// goes up whenever the layout changes so that old caches are rebuilt
+const MAGIC: &[u8; 8] = b"rtmesh\0\0";
+const VERSION: u32 = 1;
+
+/**
+ * Where the cache for a mesh file goes: right beside it
+ */
+pub fn cache_path(source: &str) -> String {
+    format!("{}.cache", source)
+}
+
+/**
+ * The contents of a source file's cache, past the header, or `None` if
+ * there isn't one or it's out of date: if the file has changed size or
+ * been modified since the cache was written, if it was loaded with a
+ * different transform, or if the cache is from another version.
+ */
+pub fn read(source: &str, transform: &Matrix) -> Result<Option<CacheReader>,LoadError> {
+    let stamp = match source_stamp(source) {
+        Some(stamp) => stamp,
+        None => return Ok(None),
+    };
+    let path = cache_path(source);
+    let data = match fs::read(&path) {
+        Ok(data) => data,
+        Err(_) => return Ok(None),
+    };
+
+    let mut reader = CacheReader { path, data, position: 0 };
+    if reader.bytes(MAGIC.len())? != MAGIC || reader.u32()? != VERSION {
+        return Ok(None);
+    }
+    if reader.u64()? != stamp.0 || reader.u64()? != stamp.1 || reader.u32()? != stamp.2 {
+        return Ok(None);
+    }
+    for (row, col) in matrix_cells() {
+        if reader.f32()? != transform.get(row, col) {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(reader))
+}
+
+/**
+ * A writer for a source file's cache, with the header already written.
+ * Nothing is written to disk until `save()`.
+ */
+pub fn write(source: &str, transform: &Matrix) -> Result<CacheWriter,LoadError> {
+    let stamp = source_stamp(source).ok_or_else(|| LoadError::invalid("can't tell when the file was last modified"))?;
+
+    let mut writer = CacheWriter { data: Vec::new() };
+    writer.bytes(MAGIC);
+    writer.u32(VERSION);
+    writer.u64(stamp.0);
+    writer.u64(stamp.1);
+    writer.u32(stamp.2);
+    for (row, col) in matrix_cells() {
+        writer.f32(transform.get(row, col));
+    }
+
+    Ok(writer)
+}
+
+fn matrix_cells() -> impl Iterator<Item = (usize,usize)> {
+    (0..4).flat_map(|row| (0..4).map(move |col| (row, col)))
+}
+
+/**
+ * The size of a file and when it was last modified (in seconds and
+ * nanoseconds), which together stand in for a hash of its contents
+ */
+fn source_stamp(source: &str) -> Option<(u64,u64,u32)> {
+    let metadata = fs::metadata(source).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+    Some((metadata.len(), modified.as_secs(), modified.subsec_nanos()))
+}
+
+/**
+ * Values in a cache file, in order, little-endian. Counts and indices are
+ * stored as 32 bits.
+ */
+pub struct CacheWriter {
+    data: Vec<u8>,
+}
+
+impl CacheWriter {
+
+    /**
+     * Write the cache next to `source`. It's written to a temporary file
+     * first, so that a half-written cache is never read back.
+     */
+    pub fn save(self, source: &str) -> Result<(),LoadError> {
+        let path = cache_path(source);
+        let temp_path = format!("{}.tmp", path);
+
+        fs::write(&temp_path, &self.data)
+            .and_then(|_| fs::rename(&temp_path, &path))
+            .map_err(|err| LoadError::Io {
+                path,
+                message: err.to_string(),
+            })
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+
+    pub fn flag(&mut self, flag: bool) {
+        self.data.push(flag as u8);
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn f32(&mut self, value: f32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub fn index(&mut self, index: usize) {
+        self.u32(index as u32);
+    }
+
+    pub fn vec3(&mut self, vec: &Vec3) {
+        self.f32(vec.x);
+        self.f32(vec.y);
+        self.f32(vec.z);
+    }
+
+    pub fn string(&mut self, string: &str) {
+        self.index(string.len());
+        self.bytes(string.as_bytes());
+    }
+
+    /**
+     * A flag for whether there's a value, then the value if there is one
+     */
+    pub fn option<T, F: FnOnce(&mut Self, T)>(&mut self, value: Option<T>, write: F) {
+        self.flag(value.is_some());
+        if let Some(value) = value {
+            write(self, value);
+        }
+    }
+
+    /**
+     * How many items there are, then each one
+     */
+    pub fn list<T, F: FnMut(&mut Self, &T)>(&mut self, items: &[T], mut write: F) {
+        self.index(items.len());
+        for item in items {
+            write(self, item);
+        }
+    }
+}
+
+/**
+ * Reads back what a `CacheWriter` wrote. Running out of data, or finding
+ * an index that's out of range, means the cache is corrupt.
+ */
+pub struct CacheReader {
+    path: String,
+    data: Vec<u8>,
+    position: usize,
+}
+
+impl CacheReader {
+
+    pub fn error(&self, message: &str) -> LoadError {
+        LoadError::invalid(message).in_file(&self.path)
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&[u8],LoadError> {
+        let start = self.position;
+        if start + count > self.data.len() {
+            return Err(self.error("the cache ends early"));
+        }
+        let bytes = &self.data[start..start + count];
+        self.position += count;
+
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8;N],LoadError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.bytes(N)?);
+
+        Ok(array)
+    }
+
+    pub fn flag(&mut self) -> Result<bool,LoadError> {
+        Ok(self.bytes(1)?[0] != 0)
+    }
+
+    pub fn u32(&mut self) -> Result<u32,LoadError> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    pub fn u64(&mut self) -> Result<u64,LoadError> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    pub fn f32(&mut self) -> Result<f32,LoadError> {
+        self.array().map(f32::from_le_bytes)
+    }
+
+    /**
+     * An index into something with `len` items
+     */
+    pub fn index(&mut self, len: usize) -> Result<usize,LoadError> {
+        let index = self.u32()? as usize;
+        if index >= len {
+            return Err(self.error(&format!("the cache has an index out of range ({} of {})", index, len)));
+        }
+
+        Ok(index)
+    }
+
+    pub fn vec3(&mut self) -> Result<Vec3,LoadError> {
+        Ok(Vec3 { x: self.f32()?, y: self.f32()?, z: self.f32()? })
+    }
+
+    pub fn string(&mut self) -> Result<String,LoadError> {
+        let len = self.u32()? as usize;
+
+        let bytes = self.bytes(len)?.to_vec();
+
+        String::from_utf8(bytes).map_err(|_| self.error("the cache has a string that isn't UTF-8"))
+    }
+
+    pub fn option<T, F: FnOnce(&mut Self) -> Result<T,LoadError>>(&mut self, read: F) -> Result<Option<T>,LoadError> {
+        if self.flag()? {
+            read(self).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn list<T, F: FnMut(&mut Self) -> Result<T,LoadError>>(&mut self, mut read: F) -> Result<Vec<T>,LoadError> {
+        let len = self.u32()? as usize;
+
+        // the count can't be trusted to reserve space up front
+        let mut items = Vec::with_capacity(len.min(self.data.len() - self.position));
+        for _ in 0..len {
+            items.push(read(self)?);
+        }
+
+        Ok(items)
+    }
+
+    /**
+     * Check that everything has been read
+     */
+    pub fn finish(&self) -> Result<(),LoadError> {
+        if self.position != self.data.len() {
+            return Err(self.error("the cache has more in it than expected"));
+        }
+
+        Ok(())
+    }
+}
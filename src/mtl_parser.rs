@@ -1,100 +1,340 @@
-
 // http://paulbourke.net/dataformats/mtl/
 
 use std::fs;
+use std::path::Path;
 use std::collections::HashMap;
 
 use crate::material::Material;
 use crate::color::Color;
 use crate::texture::Texture;
+use crate::load_error::{LoadError,arg,number_arg};
 
-pub fn load_and_parse(path: &str) -> HashMap<String,Material> {
+/**
+ * A missing file only gets a warning, since meshes still work without
+ * their materials, but a malformed one is an error
+ */
+pub fn load_and_parse(path: &str) -> Result<HashMap<String,Material>,LoadError> {
     let data = fs::read_to_string(path);
 
     println!("Loading mtl...");
 
+    let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
     let mats = match data {
-        Ok(contents) => parse(&contents),
+        Ok(contents) => parse(&contents, base_dir).map_err(|err| err.in_file(path))?,
         Err(_) => {
             println!("WARNING: Failed to open materials file \"{}\"", path);
             HashMap::new()
         }
     };
-        
-    return mats;
+
+    return Ok(mats);
 }
 
-pub fn parse(obj: &str) -> HashMap<String,Material> {
+/**
+ * Texture maps are loaded relative to `base_dir`
+ */
+pub fn parse(mtl: &str, base_dir: &Path) -> Result<HashMap<String,Material>,LoadError> {
     let mut materials = HashMap::new();
-    
-    let mut mat: Option<(String,Material)> = None;
-    for line in obj.split("\n") {
-        let segments: Vec<&str> = line.trim().split(" ").collect();
-
-        match segments[0] {
-            "newmtl" => {
-                mat.map(|material| {
-                    materials.insert(material.0, material.1);
-                });
-
-                mat = Some((
-                    String::from(segments[1]),
-                    Material {
-                        texture_albedo: None,
-                        texture_specular: None,
-                        texture_normal: None,
-                        texture_emission_color: None,
-                        texture_emission_intensity: None,
-                    }
-                ));
+
+    let mut mat: Option<(String,MtlProperties)> = None;
+    for (index, line) in mtl.split("\n").enumerate() {
+        parse_line(line, base_dir, &mut materials, &mut mat).map_err(|err| err.at_line(index + 1))?;
+    }
+
+    if let Some((name, properties)) = mat {
+        materials.insert(name, properties.into_material());
+    }
+
+    return Ok(materials);
+}
+
+fn parse_line(line: &str, base_dir: &Path, materials: &mut HashMap<String,Material>, mat: &mut Option<(String,MtlProperties)>) -> Result<(),LoadError> {
+    let segments: Vec<&str> = line.split_whitespace().collect();
+
+    let keyword = segments.first().copied().unwrap_or("");
+    if keyword == "newmtl" {
+        if let Some((name, properties)) = mat.take() {
+            materials.insert(name, properties.into_material());
+        }
+
+        *mat = Some((String::from(arg(&segments, 1)?), MtlProperties::default()));
+        return Ok(());
+    }
+
+    if keyword.is_empty() || keyword.starts_with('#') {
+        return Ok(());
+    }
+
+    let properties = match mat.as_mut() {
+        Some((_, properties)) => properties,
+        None => return Err(LoadError::parse(keyword, "comes before any newmtl")),
+    };
+
+    match keyword {
+        "Kd" => properties.diffuse = Some(color_arg(&segments)?),
+        "Ks" => properties.specular = Some(color_arg(&segments)?),
+        "Ke" => properties.emission = Some(color_arg(&segments)?),
+        "Ns" => properties.specular_exponent = Some(number_arg(&segments, 1)?),
+        "d" => properties.opacity = Some(number_arg(&segments, 1)?),
+        "Tr" => properties.opacity = Some(1.0 - number_arg::<f32>(&segments, 1)?),
+        "Ni" => properties.index_of_refraction = Some(number_arg(&segments, 1)?),
+        "illum" => properties.illumination_model = Some(number_arg(&segments, 1)?),
+
+        "map_Kd" => properties.diffuse_map = texture_map(&segments, base_dir, false)?,
+        "map_Ks" => properties.specular_map = texture_map(&segments, base_dir, false)?,
+        "map_Ke" => properties.emission_map = texture_map(&segments, base_dir, false)?,
+        "map_d" => properties.opacity_map = texture_map(&segments, base_dir, false)?,
+        "norm" => properties.normal_map = texture_map(&segments, base_dir, false)?,
+        "map_Bump" | "map_bump" | "bump" => properties.bump_map = texture_map(&segments, base_dir, true)?,
+
+        // ambient color, and anything else, doesn't have a use here
+        _ => ()
+    }
+
+    Ok(())
+}
+
+/**
+ * Everything given for one material, before it's turned into a `Material`
+ * (which needs all of it at once, since e.g. `Ks` and `Ns` together make
+ * one specular texture)
+ */
+#[derive(Default)]
+struct MtlProperties {
+    diffuse: Option<Color>,
+    specular: Option<Color>,
+    specular_exponent: Option<f32>,
+    emission: Option<Color>,
+    opacity: Option<f32>,
+    index_of_refraction: Option<f32>,
+    illumination_model: Option<u32>,
+
+    diffuse_map: Option<Texture>,
+    specular_map: Option<Texture>,
+    emission_map: Option<Texture>,
+    opacity_map: Option<Texture>,
+    normal_map: Option<Texture>,
+
+    // heights, already turned into normals
+    bump_map: Option<Texture>,
+}
+
+impl MtlProperties {
+
+    /**
+     * Maps take priority over colors, and normal maps over bump maps.
+     * `illum` models 0 and 1 have no highlights, and 4 and 9 are glass
+     * that doesn't refract.
+     */
+    fn into_material(self) -> Material {
+        let has_highlights = !matches!(self.illumination_model, Some(0) | Some(1));
+        let refracts = !matches!(self.illumination_model, Some(4) | Some(9));
+
+        // `Ns` is a Phong exponent, from 0 (dull) to 1000 (mirror-like),
+        // where a `Material`'s specularity goes from 0 to 1
+        let specular = self.specular
+            .filter(|color| max_component(color) > 0.0)
+            .map(|_| {
+                let roughness = (2.0 / (self.specular_exponent.unwrap_or(0.0).max(0.0) + 2.0)).sqrt();
+                Texture::Solid(Color::gray(1.0 - roughness))
+            });
+
+        // `Ke` can be brighter than 1, which is the emission intensity
+        let emission = self.emission.filter(|color| max_component(color) > 0.0);
+        let emission_intensity = emission.map(|color| max_component(&color));
+        let emission_color = emission.map(|color| color * (1.0 / max_component(&color)));
+
+        let texture_emission_color = self.emission_map.or_else(|| emission_color.map(Texture::Solid));
+        let texture_emission_intensity = texture_emission_color.as_ref()
+            .map(|_| Texture::Solid(Color::gray(emission_intensity.unwrap_or(1.0))));
+
+        let diffuse = self.diffuse;
+        let opacity = self.opacity.filter(|&opacity| opacity < 1.0);
+
+        Material {
+            texture_albedo: self.diffuse_map.or_else(|| diffuse.map(Texture::Solid)),
+            texture_specular: self.specular_map.or(specular).filter(|_| has_highlights),
+            texture_normal: self.normal_map.or(self.bump_map),
+            texture_emission_color,
+            texture_emission_intensity,
+            texture_opacity: self.opacity_map.or_else(|| opacity.map(|opacity| Texture::Solid(Color::gray(opacity)))),
+            index_of_refraction: self.index_of_refraction.filter(|_| refracts),
+        }
+    }
+}
+
+fn max_component(color: &Color) -> f32 {
+    color.0.max(color.1).max(color.2)
+}
+
+/**
+ * An `r g b` color; `Kd 0.5` is shorthand for a gray
+ */
+fn color_arg(segments: &[&str]) -> Result<Color,LoadError> {
+    let r = number_arg(segments, 1)?;
+
+    if segments.len() > 2 {
+        Ok(Color(r, number_arg(segments, 2)?, number_arg(segments, 3)?))
+    } else {
+        Ok(Color::gray(r))
+    }
+}
+
+/**
+ * `map_Xx [options] file`. The scale (`-s`), offset (`-o`) and `-clamp`
+ * options are applied; the rest are read but have no effect. A map that
+ * can't be loaded is skipped with a warning, like a missing MTL file.
+ * Bump maps are heights, so they're turned into normals, steepened by
+ * `-bm`.
+ */
+fn texture_map(segments: &[&str], base_dir: &Path, bump: bool) -> Result<Option<Texture>,LoadError> {
+    let mut scale = (1.0, 1.0);
+    let mut offset = (0.0, 0.0);
+    let mut clamp = false;
+    let mut bump_multiplier = 1.0;
+
+    let mut i = 1;
+    while i < segments.len() && segments[i].starts_with('-') {
+        let option = segments[i];
+
+        // options with up to three numbers (u, then optionally v and w)
+        let mut numbers = || {
+            let mut values: Vec<f32> = Vec::new();
+            while values.len() < 3 {
+                match segments.get(i + 1 + values.len()).and_then(|token| token.parse().ok()) {
+                    Some(value) => values.push(value),
+                    None => break,
+                }
+            }
+            if values.is_empty() {
+                return Err(LoadError::parse(option, "expected a number"));
+            }
+            i += values.len();
+
+            Ok(values)
+        };
+
+        match option {
+            "-s" => {
+                let values = numbers()?;
+                scale = (values[0], values.get(1).copied().unwrap_or(1.0));
+            },
+            "-o" => {
+                let values = numbers()?;
+                offset = (values[0], values.get(1).copied().unwrap_or(0.0));
+            },
+            "-t" => {
+                numbers()?;
             },
-            "Kd" => {
-                mat.as_mut().unwrap().1.texture_albedo = Some(Texture::Solid(Color(
-                    segments[1].parse().ok().unwrap(),
-                    segments[2].parse().ok().unwrap(),
-                    segments[3].parse().ok().unwrap()
-                )));
+            "-clamp" => {
+                clamp = arg(segments, i + 1)? == "on";
+                i += 1;
             },
-            
-            /* specular tint
-            "Ks" => {
-                segments[1].parse().ok(),
-                    segments[2].parse().ok(),
-                    segments[3].parse().ok()
-            },*/
-
-            /* specular angle
-            "Ns" => {
-                segments[1].parse().ok()
-            },*/
-
-            /*
-            "illum" => 
-
-            0		Color on and Ambient off
-            1		Color on and Ambient on
-            2		Highlight on
-            3		Reflection on and Ray trace on
-            4		Transparency: Glass on
-                    Reflection: Ray trace on
-            5		Reflection: Fresnel on and Ray trace on
-            6		Transparency: Refraction on
-                    Reflection: Fresnel off and Ray trace on
-            7		Transparency: Refraction on
-                    Reflection: Fresnel on and Ray trace on
-            8		Reflection on and Ray trace off
-            9		Transparency: Glass on
-                    Reflection: Ray trace off
-            10		Casts shadows onto invisible surfaces
-             */
-
-            _ => ()
+            "-bm" => {
+                bump_multiplier = numbers()?[0];
+            },
+            "-mm" => i += 2,
+            "-blendu" | "-blendv" | "-boost" | "-cc" | "-imfchan" | "-texres" => i += 1,
+            _ => return Err(LoadError::parse(option, "unknown texture option")),
         }
+        i += 1;
     }
 
-    mat.map(|material| {
-        materials.insert(material.0, material.1);
-    });
+    if i >= segments.len() {
+        return Err(LoadError::parse(segments[0], "missing a file name"));
+    }
 
-    return materials;
+    // file names can have spaces in them
+    let path = base_dir.join(segments[i..].join(" "));
+    let texture = match Texture::from_image(&path.to_string_lossy()) {
+        Ok(texture) if bump => texture.heights_to_normals(bump_multiplier),
+        Ok(texture) => texture,
+        Err(err) => {
+            println!("WARNING: {}", err);
+            return Ok(None);
+        }
+    };
+
+    if scale == (1.0, 1.0) && offset == (0.0, 0.0) && !clamp {
+        Ok(Some(texture))
+    } else {
+        Ok(Some(Texture::Mapped { texture: Box::new(texture), scale, offset, clamp }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::mtl_parser::parse;
+    use crate::color::Color;
+    use crate::texture::Texture;
+
+    #[test]
+    fn test_parse() {
+        let materials = parse("newmtl red\nKd 1 0 0\n\nnewmtl blank\n", Path::new("")).unwrap();
+        assert_eq!(materials.len(), 2);
+        assert!(materials["red"].texture_albedo.is_some());
+
+        let err = parse("newmtl red\nKd 1 0\n", Path::new("")).err().unwrap().to_string();
+        assert_eq!(err, "line 2, `Kd`: expected at least 3 values");
+        assert!(parse("Kd 1 0 0\n", Path::new("")).is_err());
+    }
+
+    #[test]
+    fn test_material_properties() {
+        let mtl = "
+            newmtl glass
+            Kd 0.9 0.9 0.9
+            Ks 1 1 1
+            Ns 1000
+            Tr 0.75
+            Ni 1.5
+            illum 7
+
+            newmtl lamp
+            Ke 4 2 0
+            illum 1
+            Ks 0.5 0.5 0.5
+
+            newmtl grid
+            map_Kd -s 2 2 1 -o 0.5 0 -clamp on grid.jpg
+            map_Bump -bm 0.5 missing.png
+        ";
+        let materials = parse(mtl, Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap();
+
+        let glass = &materials["glass"];
+        assert!(glass.texture_specular.as_ref().unwrap().color_at((0.0, 0.0)).0 > 0.95);
+        assert_eq!(glass.texture_opacity.as_ref().unwrap().color_at((0.0, 0.0)), Color::gray(0.25));
+        assert_eq!(glass.index_of_refraction, Some(1.5));
+
+        let lamp = &materials["lamp"];
+        assert!(lamp.texture_specular.is_none());
+        assert_eq!(lamp.texture_emission_color.as_ref().unwrap().color_at((0.0, 0.0)), Color(1.0, 0.5, 0.0));
+        assert_eq!(lamp.texture_emission_intensity.as_ref().unwrap().color_at((0.0, 0.0)), Color::gray(4.0));
+
+        let grid = &materials["grid"];
+        match grid.texture_albedo.as_ref().unwrap() {
+            Texture::Mapped { scale, offset, clamp, .. } => {
+                assert_eq!(*scale, (2.0, 2.0));
+                assert_eq!(*offset, (0.5, 0.0));
+                assert!(*clamp);
+            },
+            _ => panic!("expected the map's options to be applied"),
+        }
+        assert!(grid.texture_normal.is_none());
+
+        // a bump map's heights become normals, which point straight out
+        // where the grid is flat, where a normal map is used as it is
+        let maps = parse("newmtl bumpy\nbump -bm 2 grid.jpg\nnewmtl normal\nbump grid.jpg\nnorm grid.jpg\nmap_Kd grid.jpg\n", Path::new(env!("CARGO_MANIFEST_DIR"))).unwrap();
+        let bumpy = maps["bumpy"].texture_normal.as_ref().unwrap();
+        let normal = &maps["normal"];
+        for &uv in &[(0.1, 0.1), (0.25, 0.5), (0.5, 0.5), (0.9, 0.3)] {
+            let Color(x, y, z) = bumpy.color_at(uv);
+            assert!((x - 0.5).abs() < 0.02 && (y - 0.5).abs() < 0.02 && z > 0.99);
+            assert_eq!(normal.texture_normal.as_ref().unwrap().color_at(uv), normal.texture_albedo.as_ref().unwrap().color_at(uv));
+        }
+
+        assert!(parse("newmtl bad\nmap_Kd -q grid.jpg\n", Path::new("")).is_err());
+    }
 }
@@ -1,64 +1,267 @@
+use std::io::BufRead;
+use std::str::FromStr;
 
-pub fn parse(obj: &str) -> Vec<LineType> {
-    let mut lines = Vec::new();
-    
-    for line in obj.split("\n") {
-        lines.push(parse_line(line));
+use crate::load_error::LoadError;
+
+/**
+ * Reads an OBJ file one line at a time, so that meshes can be built as the
+ * file streams in rather than after it's all been held in memory. The
+ * line buffer (and the corners of the latest face) are reused from line to
+ * line, so each line hands back views into them.
+ */
+pub struct ObjReader<R> {
+    reader: R,
+    line: String,
+    line_number: usize,
+    counts: ElementCounts,
+    corners: Vec<FaceVertex>,
+}
+
+impl<R: BufRead> ObjReader<R> {
+
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+            line_number: 0,
+            counts: ElementCounts::default(),
+            corners: Vec::new(),
+        }
     }
 
-    return lines;
+    /**
+     * The next line, or `None` at the end of the file. Stops at the first
+     * line that's malformed.
+     */
+    pub fn next_line(&mut self) -> Result<Option<LineType<'_>>,LoadError> {
+        self.line.clear();
+        let read = self.reader.read_line(&mut self.line).map_err(|err| LoadError::Io {
+            path: String::new(),
+            message: err.to_string(),
+        })?;
+        if read == 0 {
+            return Ok(None);
+        }
+
+        self.line_number += 1;
+        let line_number = self.line_number;
+
+        let line = parse_line(&self.line, &self.counts, &mut self.corners).map_err(|err| err.at_line(line_number))?;
+        self.counts.add(&line);
+
+        Ok(Some(line))
+    }
 }
 
-pub fn parse_line(line: &str) -> LineType {
-    let segments: Vec<&str> = line.trim().split(" ").collect();
+/**
+ * Parse one line. Faces can refer back to vertices relative to the end of
+ * the file so far (-1 is the latest), so this needs to know how many of
+ * each there have been. A face's corners are put in `corners`.
+ */
+pub fn parse_line<'a>(line: &'a str, counts: &ElementCounts, corners: &'a mut Vec<FaceVertex>) -> Result<LineType<'a>,LoadError> {
+    let mut values = line.split_whitespace();
+    let keyword = values.next().unwrap_or("");
+
+    // everything after the keyword, for names
+    let rest = || line.trim_start()[keyword.len()..].trim();
 
-    match segments[0] {
-        "#" => LineType::Comment(String::from(segments[1])),
-        "o" => LineType::Object(String::from(segments[1])),
+    Ok(match keyword {
+        "#" => LineType::Comment(rest()),
+        "o" => LineType::Object(rest()),
+        "g" => LineType::Group(rest()),
+        "s" => LineType::SmoothingGroup(match values.next() {
+                    Some("off") => None,
+                    Some(group) => Some(parse_number(group)?).filter(|&group| group != 0),
+                    None => return Err(missing_value(keyword, 1)),
+                }),
         "v" => LineType::Vertex(
-                    segments[1].parse().ok().unwrap(),
-                    segments[2].parse().ok().unwrap(),
-                    segments[3].parse().ok().unwrap()),
+                    number(keyword, &mut values, 1)?,
+                    number(keyword, &mut values, 2)?,
+                    number(keyword, &mut values, 3)?),
         "vn" => LineType::VertexNormal(
-                    segments[1].parse().ok().unwrap(),
-                    segments[2].parse().ok().unwrap(),
-                    segments[3].parse().ok().unwrap()),
+                    number(keyword, &mut values, 1)?,
+                    number(keyword, &mut values, 2)?,
+                    number(keyword, &mut values, 3)?),
         "vt" => LineType::VertexTexture(
-                    segments[1].parse().ok().unwrap(),
-                    segments[2].parse().ok().unwrap()),
-        "f" => LineType::Face(
-                parse_face_vertex(segments[1]),
-                parse_face_vertex(segments[2]),
-                parse_face_vertex(segments[3])),
-        "mtllib" => LineType::MTLib(String::from(segments[1])),
-        "usemtl" => LineType::UseMaterial(String::from(segments[1])),
+                    number(keyword, &mut values, 1)?,
+                    values.next().map(parse_number).transpose()?.unwrap_or(0.0)),
+        "f" => {
+            corners.clear();
+            for segment in values {
+                corners.push(parse_face_vertex(segment, counts)?);
+            }
+
+            if corners.len() < 3 {
+                return Err(LoadError::parse(line.trim(), "a face needs at least three corners"));
+            }
+
+            LineType::Face(corners)
+        },
+        "mtllib" => LineType::MTLib(rest()),
+        "usemtl" => LineType::UseMaterial(rest()),
 
         _ => LineType::Unknown
-    }
+    })
+}
+
+/**
+ * The next value on a line (the `index`th, counting the keyword as 0) as a
+ * number
+ */
+fn number<'a, T: FromStr>(keyword: &str, values: &mut impl Iterator<Item = &'a str>, index: usize) -> Result<T,LoadError> {
+    values.next()
+        .ok_or_else(|| missing_value(keyword, index))
+        .and_then(parse_number)
+}
+
+fn parse_number<T: FromStr>(token: &str) -> Result<T,LoadError> {
+    token.parse().map_err(|_| LoadError::parse(token, "not a valid number"))
 }
 
-fn parse_face_vertex(segment: &str) -> FaceVertex {
-    let nums: Vec<&str> = segment.trim().split("/").collect();
+fn missing_value(keyword: &str, index: usize) -> LoadError {
+    LoadError::parse(keyword, &format!("expected at least {} values", index))
+}
+
+/**
+ * Any of `v`, `v/vt`, `v//vn` or `v/vt/vn`
+ */
+fn parse_face_vertex(segment: &str, counts: &ElementCounts) -> Result<FaceVertex,LoadError> {
+    let mut nums = segment.split('/');
+    let vertex = nums.next().unwrap_or("");
+    let mut optional_index = |count: usize| match nums.next() {
+        Some(num) if !num.is_empty() => resolve_index(num, count).map(Some),
+        _ => Ok(None),
+    };
 
-    FaceVertex(
-        nums[0].parse::<usize>().ok().unwrap() - 1, // vertices are 1-indexed
-        nums[1].parse().ok(), 
-        nums[2].parse().ok())
+    Ok(FaceVertex(
+        resolve_index(vertex, counts.vertices)?,
+        optional_index(counts.uvs)?,
+        optional_index(counts.normals)?))
+}
+
+/**
+ * Indices count from 1, or back from the end if they're negative, and
+ * have to refer to something that came earlier in the file
+ */
+fn resolve_index(num: &str, count: usize) -> Result<usize,LoadError> {
+    let index = num.parse::<isize>().map_err(|_| LoadError::parse(num, "not a valid index"))?;
+
+    let resolved = if index < 0 {
+        count.checked_sub(index.unsigned_abs())
+    } else {
+        (index as usize).checked_sub(1)
+    };
+
+    resolved
+        .filter(|&resolved| resolved < count)
+        .ok_or_else(|| LoadError::parse(num, &format!("index out of range (there are {} so far)", count)))
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum LineType {
-    Comment(String),
-    Object(String),
+pub enum LineType<'a> {
+    Comment(&'a str),
+    Object(&'a str),
+    Group(&'a str),
+
+    // `None` for faces that shouldn't be smoothed
+    SmoothingGroup(Option<u32>),
+
     Vertex(f32, f32, f32),
     VertexNormal(f32, f32, f32),
     VertexTexture(f32, f32),
-    Face(FaceVertex, FaceVertex, FaceVertex),
-    MTLib(String),
-    UseMaterial(String),
+
+    // a polygon, with its corners in order
+    Face(&'a [FaceVertex]),
+
+    MTLib(&'a str),
+    UseMaterial(&'a str),
 
     Unknown,
 }
 
+/**
+ * One corner of a face: the indices (counting from 0) of its vertex, and
+ * of its texture coordinate and normal if it has them
+ */
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub struct FaceVertex (pub usize, pub Option<usize>, pub Option<usize>);
\ No newline at end of file
+pub struct FaceVertex (pub usize, pub Option<usize>, pub Option<usize>);
+
+/**
+ * How many vertices, texture coordinates and normals have been read so far
+ */
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct ElementCounts {
+    pub vertices: usize,
+    pub uvs: usize,
+    pub normals: usize,
+}
+
+impl ElementCounts {
+
+    pub fn add(&mut self, line: &LineType) {
+        match line {
+            LineType::Vertex(..) => self.vertices += 1,
+            LineType::VertexTexture(..) => self.uvs += 1,
+            LineType::VertexNormal(..) => self.normals += 1,
+            _ => ()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::obj_parser::{ObjReader,LineType,FaceVertex};
+    use crate::load_error::LoadError;
+
+    fn read_all(obj: &str) -> Result<(),LoadError> {
+        let mut reader = ObjReader::new(obj.as_bytes());
+        while reader.next_line()?.is_some() { }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut reader = ObjReader::new("o Cube\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nvn 0 0 1\n\
+                                         g side top\ns off\nf 1 2 3 4\nf 1/1 2/1 3/1\nf -4//-1 -3//1 -2//1\ns 2\nf -4/-1/-1 2/1/1 3/1/1".as_bytes());
+
+        assert_eq!(reader.next_line().unwrap(), Some(LineType::Object("Cube")));
+        for _ in 0..6 {
+            reader.next_line().unwrap();
+        }
+        assert_eq!(reader.next_line().unwrap(), Some(LineType::Group("side top")));
+        assert_eq!(reader.next_line().unwrap(), Some(LineType::SmoothingGroup(None)));
+        assert_eq!(reader.next_line().unwrap(), Some(LineType::Face(&[
+            FaceVertex(0, None, None),
+            FaceVertex(1, None, None),
+            FaceVertex(2, None, None),
+            FaceVertex(3, None, None),
+        ])));
+        assert_eq!(reader.next_line().unwrap(), Some(LineType::Face(&[
+            FaceVertex(0, Some(0), None),
+            FaceVertex(1, Some(0), None),
+            FaceVertex(2, Some(0), None),
+        ])));
+        assert_eq!(reader.next_line().unwrap(), Some(LineType::Face(&[
+            FaceVertex(0, None, Some(0)),
+            FaceVertex(1, None, Some(0)),
+            FaceVertex(2, None, Some(0)),
+        ])));
+        assert_eq!(reader.next_line().unwrap(), Some(LineType::SmoothingGroup(Some(2))));
+        assert_eq!(reader.next_line().unwrap(), Some(LineType::Face(&[
+            FaceVertex(0, Some(0), Some(0)),
+            FaceVertex(1, Some(0), Some(0)),
+            FaceVertex(2, Some(0), Some(0)),
+        ])));
+        assert_eq!(reader.next_line().unwrap(), None);
+
+        let err = read_all("v 0 0 0\nv 1 0 0\nv 1 x 0\n").unwrap_err().to_string();
+        assert_eq!(err, "line 3, `x`: not a valid number");
+        let err = read_all("v 0 0 0\nf 1 2 -1\n").unwrap_err().to_string();
+        assert!(err.starts_with("line 2, `2`: index out of range"));
+        let err = read_all("v 0 0\n").unwrap_err().to_string();
+        assert_eq!(err, "line 1, `v`: expected at least 3 values");
+        assert!(read_all("v 0 0 0\nf 1 1\n").is_err());
+        assert!(read_all("# comment\r\n\r\ns\n").is_err());
+    }
+}
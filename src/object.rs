@@ -3,35 +3,75 @@ use rand::rngs::SmallRng;
 
 use crate::ray::Ray;
 use crate::vec3::Vec3;
-use crate::intersection::Intersection;
+use crate::intersection::{Intersection,Interval};
 use crate::illumination::Illumination;
+use crate::bounding_box::BoundingBox;
+use crate::world::World;
+use crate::render_settings::RenderSettings;
 
 use crate::plane::Plane;
 use crate::sphere::Sphere;
+use crate::triangle::Triangle;
+use crate::quad::Quad;
+use crate::disk::Disk;
+use crate::cuboid::Cuboid;
+use crate::cylinder::Cylinder;
+use crate::cone::Cone;
+use crate::torus::Torus;
 use crate::mesh::Mesh;
+use crate::transformed::Transformed;
+use crate::csg::Csg;
+use crate::sdf::DistanceField;
 
 pub trait Object {
 
     /**
      * Get information about the point where a ray intersects this object, 
-     * if it does at all.
+     * if it does at all. This is the hit record later passed to `shade()`,
+     * so it should be filled in (including `uv`) as far as the object can.
      */
     fn intersection(&self, ray: &Ray) -> Option<Intersection>;
 
+    /**
+     * Every stretch of the ray (behind its origin too) that's inside of 
+     * this object, in order. Used to combine solids in CSG; objects with no
+     * inside, like triangles, have no intervals.
+     */
+    fn intervals(&self, ray: &Ray) -> Vec<Interval>;
+
     /**
      * Get the UV coordinate on this object's texture for a given 
      * world-space coordinate.
      */
     fn texture_coordinate(&self, point: &Vec3) -> (f32,f32);
 
+    /**
+     * A box containing every point where this object could be hit, or 
+     * `None` if it's infinite (like a plane).
+     */
+    fn bounds(&self) -> Option<BoundingBox>;
 
-    fn shade(&self, ray: &Ray, objs: &Vec<ObjectEnum>, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination;
+    /**
+     * Compute the illumination coming back along a ray, given the hit 
+     * record that `intersection()` produced for it.
+     */
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination;
 }
 
 pub enum ObjectEnum {
     Plane(Plane),
     Sphere(Sphere),
-    Mesh(Mesh)
+    Triangle(Triangle),
+    Quad(Quad),
+    Disk(Disk),
+    Cuboid(Cuboid),
+    Cylinder(Cylinder),
+    Cone(Cone),
+    Torus(Torus),
+    Mesh(Mesh),
+    Transformed(Transformed),
+    Csg(Csg),
+    DistanceField(DistanceField),
 }
 
 impl Object for ObjectEnum {
@@ -39,21 +79,85 @@ impl Object for ObjectEnum {
         match self {
             ObjectEnum::Plane(data) => data.intersection(ray),
             ObjectEnum::Sphere(data) => data.intersection(ray),
+            ObjectEnum::Triangle(data) => data.intersection(ray),
+            ObjectEnum::Quad(data) => data.intersection(ray),
+            ObjectEnum::Disk(data) => data.intersection(ray),
+            ObjectEnum::Cuboid(data) => data.intersection(ray),
+            ObjectEnum::Cylinder(data) => data.intersection(ray),
+            ObjectEnum::Cone(data) => data.intersection(ray),
+            ObjectEnum::Torus(data) => data.intersection(ray),
             ObjectEnum::Mesh(data) => data.intersection(ray),
+            ObjectEnum::Transformed(data) => data.intersection(ray),
+            ObjectEnum::Csg(data) => data.intersection(ray),
+            ObjectEnum::DistanceField(data) => data.intersection(ray),
+        }
+    }
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        match self {
+            ObjectEnum::Plane(data) => data.intervals(ray),
+            ObjectEnum::Sphere(data) => data.intervals(ray),
+            ObjectEnum::Triangle(data) => data.intervals(ray),
+            ObjectEnum::Quad(data) => data.intervals(ray),
+            ObjectEnum::Disk(data) => data.intervals(ray),
+            ObjectEnum::Cuboid(data) => data.intervals(ray),
+            ObjectEnum::Cylinder(data) => data.intervals(ray),
+            ObjectEnum::Cone(data) => data.intervals(ray),
+            ObjectEnum::Torus(data) => data.intervals(ray),
+            ObjectEnum::Mesh(data) => data.intervals(ray),
+            ObjectEnum::Transformed(data) => data.intervals(ray),
+            ObjectEnum::Csg(data) => data.intervals(ray),
+            ObjectEnum::DistanceField(data) => data.intervals(ray),
         }
     }
     fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
         match self {
             ObjectEnum::Plane(data) => data.texture_coordinate(point),
             ObjectEnum::Sphere(data) => data.texture_coordinate(point),
+            ObjectEnum::Triangle(data) => data.texture_coordinate(point),
+            ObjectEnum::Quad(data) => data.texture_coordinate(point),
+            ObjectEnum::Disk(data) => data.texture_coordinate(point),
+            ObjectEnum::Cuboid(data) => data.texture_coordinate(point),
+            ObjectEnum::Cylinder(data) => data.texture_coordinate(point),
+            ObjectEnum::Cone(data) => data.texture_coordinate(point),
+            ObjectEnum::Torus(data) => data.texture_coordinate(point),
             ObjectEnum::Mesh(data) => data.texture_coordinate(point),
+            ObjectEnum::Transformed(data) => data.texture_coordinate(point),
+            ObjectEnum::Csg(data) => data.texture_coordinate(point),
+            ObjectEnum::DistanceField(data) => data.texture_coordinate(point),
+        }
+    }
+    fn bounds(&self) -> Option<BoundingBox> {
+        match self {
+            ObjectEnum::Plane(data) => data.bounds(),
+            ObjectEnum::Sphere(data) => data.bounds(),
+            ObjectEnum::Triangle(data) => data.bounds(),
+            ObjectEnum::Quad(data) => data.bounds(),
+            ObjectEnum::Disk(data) => data.bounds(),
+            ObjectEnum::Cuboid(data) => data.bounds(),
+            ObjectEnum::Cylinder(data) => data.bounds(),
+            ObjectEnum::Cone(data) => data.bounds(),
+            ObjectEnum::Torus(data) => data.bounds(),
+            ObjectEnum::Mesh(data) => data.bounds(),
+            ObjectEnum::Transformed(data) => data.bounds(),
+            ObjectEnum::Csg(data) => data.bounds(),
+            ObjectEnum::DistanceField(data) => data.bounds(),
         }
     }
-    fn shade(&self, ray: &Ray, objs: &Vec<ObjectEnum>, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
         match self {
-            ObjectEnum::Plane(data) => data.shade(ray, objs, rng, bounces_remaining),
-            ObjectEnum::Sphere(data) => data.shade(ray, objs, rng, bounces_remaining),
-            ObjectEnum::Mesh(data) => data.shade(ray, objs, rng, bounces_remaining),
+            ObjectEnum::Plane(data) => data.shade(intersection, world, settings, rng, bounces_remaining),
+            ObjectEnum::Sphere(data) => data.shade(intersection, world, settings, rng, bounces_remaining),
+            ObjectEnum::Triangle(data) => data.shade(intersection, world, settings, rng, bounces_remaining),
+            ObjectEnum::Quad(data) => data.shade(intersection, world, settings, rng, bounces_remaining),
+            ObjectEnum::Disk(data) => data.shade(intersection, world, settings, rng, bounces_remaining),
+            ObjectEnum::Cuboid(data) => data.shade(intersection, world, settings, rng, bounces_remaining),
+            ObjectEnum::Cylinder(data) => data.shade(intersection, world, settings, rng, bounces_remaining),
+            ObjectEnum::Cone(data) => data.shade(intersection, world, settings, rng, bounces_remaining),
+            ObjectEnum::Torus(data) => data.shade(intersection, world, settings, rng, bounces_remaining),
+            ObjectEnum::Mesh(data) => data.shade(intersection, world, settings, rng, bounces_remaining),
+            ObjectEnum::Transformed(data) => data.shade(intersection, world, settings, rng, bounces_remaining),
+            ObjectEnum::Csg(data) => data.shade(intersection, world, settings, rng, bounces_remaining),
+            ObjectEnum::DistanceField(data) => data.shade(intersection, world, settings, rng, bounces_remaining),
         }
     }
 
@@ -4,11 +4,14 @@ use rand::rngs::SmallRng;
 
 use crate::vec3::Vec3;
 use crate::ray::Ray;
-use crate::object::{Object,ObjectEnum};
-use crate::intersection::Intersection;
+use crate::object::Object;
+use crate::world::World;
+use crate::intersection::{Intersection,Interval};
 use crate::material::Material;
-use crate::utils::{plane_intersection,PI_OVER_TWO,adjusted_for_normal,color_to_normal};
+use crate::utils::{plane_intersection,PI_OVER_TWO};
 use crate::illumination::Illumination;
+use crate::render_settings::RenderSettings;
+use crate::bounding_box::BoundingBox;
 
 pub struct Plane {
     pub position: Vec3,
@@ -43,16 +46,44 @@ impl Object for Plane {
     fn intersection(&self, ray: &Ray) -> Option<Intersection> {
         plane_intersection(&self.position, &self.normal, ray)
             .map(|mut intersection| {
-                
-                self.material.texture_normal.as_ref().map(|texture_normal| {
-                    let normal_color = texture_normal.color_at(self.texture_coordinate(&intersection.position));
-                    intersection.normal = adjusted_for_normal(&intersection.normal, &color_to_normal(&normal_color));
-                });
-                
+                intersection.uv = self.texture_coordinate(&intersection.position);
                 intersection
             })
     }
 
+    /**
+     * For CSG, a plane is the boundary of the half-space behind it
+     */
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let numerator = (&self.position - &ray.origin).dot(&self.normal);
+        let denominator = ray.direction.dot(&self.normal);
+
+        if denominator == 0.0 {
+            // parallel, so either always inside or never
+            return if numerator > 0.0 {
+                vec![Interval::everywhere(ray.direction)]
+            } else {
+                Vec::new()
+            };
+        }
+
+        let distance = numerator / denominator;
+        let point = &ray.origin + &(&ray.direction * distance);
+        let mut intersection = Intersection::new(
+            distance,
+            &point + &(&self.normal * 0.001), // offset to avoid floating-point error
+            self.normal,
+            ray.direction,
+        );
+        intersection.uv = self.texture_coordinate(&point);
+
+        if denominator < 0.0 {
+            vec![Interval::starting_at(intersection)]
+        } else {
+            vec![Interval::ending_at(intersection)]
+        }
+    }
+
 //    #[flame("Plane")]
     fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
         let point_projected_on_plane = self.projection(point);
@@ -68,15 +99,16 @@ impl Object for Plane {
         (u - u.floor(), v - v.floor())
     }
 
-//    #[flame("Plane")]
-    fn shade(&self, ray: &Ray, objs: &Vec<ObjectEnum>, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
-        let mut intersection = self.intersection(ray).unwrap();
-        let uv = self.texture_coordinate(&intersection.position);
+    fn bounds(&self) -> Option<BoundingBox> {
+        None
+    }
 
+//    #[flame("Plane")]
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
         self.material.shade(
-            &mut intersection,
-            uv,
-            objs,
+            intersection,
+            world,
+            settings,
             rng,
             bounces_remaining
         )
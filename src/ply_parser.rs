@@ -0,0 +1,492 @@
+// http://paulbourke.net/dataformats/ply/
+
+use std::str::Lines;
+use std::iter::Enumerate;
+
+use crate::vec3::Vec3;
+use crate::color::Color;
+use crate::load_error::{LoadError,arg,number_arg};
+
+/**
+ * The parts of a PLY file a mesh can use. Normals and colors are either
+ * there for every vertex or not at all; other elements (like edges) and
+ * properties are skipped.
+ */
+pub struct PlyContents {
+    pub vertices: Vec<Vec3>,
+    pub normals: Option<Vec<Vec3>>,
+    pub colors: Option<Vec<Color>>,
+
+    // each face is a polygon, as indices into the vertices
+    pub faces: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum ScalarType {
+    I8, U8, I16, U16, I32, U32, F32, F64,
+}
+
+impl ScalarType {
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(ScalarType::I8),
+            "uchar" | "uint8" => Some(ScalarType::U8),
+            "short" | "int16" => Some(ScalarType::I16),
+            "ushort" | "uint16" => Some(ScalarType::U16),
+            "int" | "int32" => Some(ScalarType::I32),
+            "uint" | "uint32" => Some(ScalarType::U32),
+            "float" | "float32" => Some(ScalarType::F32),
+            "double" | "float64" => Some(ScalarType::F64),
+            _ => None,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            ScalarType::I8 | ScalarType::U8 => 1,
+            ScalarType::I16 | ScalarType::U16 => 2,
+            ScalarType::I32 | ScalarType::U32 | ScalarType::F32 => 4,
+            ScalarType::F64 => 8,
+        }
+    }
+
+    /**
+     * What a color channel of this type is at full brightness
+     */
+    fn color_max(self) -> f64 {
+        match self {
+            ScalarType::I8 => 127.0,
+            ScalarType::U8 => 255.0,
+            ScalarType::I16 => 32767.0,
+            ScalarType::U16 => 65535.0,
+            ScalarType::I32 => 2_147_483_647.0,
+            ScalarType::U32 => 4_294_967_295.0,
+            ScalarType::F32 | ScalarType::F64 => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PropertyType {
+    Scalar(ScalarType),
+    List { count: ScalarType, item: ScalarType },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Property {
+    name: String,
+    kind: PropertyType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+
+    fn property_index(&self, names: &[&str]) -> Option<usize> {
+        self.properties.iter().position(|property| names.contains(&property.name.as_str()))
+    }
+}
+
+pub fn parse(data: &[u8]) -> Result<PlyContents,LoadError> {
+    let (format, elements, body_start, body_line) = parse_header(data)?;
+
+    let mut body = match format {
+        Format::Ascii => {
+            let text = std::str::from_utf8(&data[body_start..])
+                .map_err(|_| LoadError::invalid("an ASCII file has non-text data in it"))?;
+
+            Body::Ascii { lines: text.lines().enumerate(), line_offset: body_line, tokens: Vec::new(), next: 0, line: body_line }
+        },
+        _ => Body::Binary { data: &data[body_start..], offset: 0, big_endian: format == Format::BinaryBigEndian },
+    };
+
+    let mut contents = PlyContents {
+        vertices: Vec::new(),
+        normals: None,
+        colors: None,
+        faces: Vec::new(),
+    };
+
+    // each element's values are read into the same buffers; a list's
+    // values come after its length
+    let mut row: Vec<f64> = Vec::new();
+    let mut starts: Vec<usize> = Vec::new();
+    for element in &elements {
+        let position = match element.name.as_str() {
+            "vertex" => Some([
+                required_property(element, &["x"])?,
+                required_property(element, &["y"])?,
+                required_property(element, &["z"])?,
+            ]),
+            _ => None,
+        };
+        let normal = match (element.property_index(&["nx"]), element.property_index(&["ny"]), element.property_index(&["nz"])) {
+            (Some(x), Some(y), Some(z)) if position.is_some() => Some([x, y, z]),
+            _ => None,
+        };
+        let color = match (
+            element.property_index(&["red", "r", "diffuse_red"]),
+            element.property_index(&["green", "g", "diffuse_green"]),
+            element.property_index(&["blue", "b", "diffuse_blue"]),
+        ) {
+            (Some(r), Some(g), Some(b)) if position.is_some() => Some([r, g, b]),
+            _ => None,
+        };
+        let vertex_indices = match element.name.as_str() {
+            "face" => {
+                let property = required_property(element, &["vertex_indices", "vertex_index"])?;
+                if let PropertyType::Scalar(_) = element.properties[property].kind {
+                    return Err(LoadError::invalid("a face's vertex indices aren't a list"));
+                }
+
+                Some(property)
+            },
+            _ => None,
+        };
+
+        // every instance takes up at least a byte, so the count from the
+        // header can't be trusted to reserve more than the rest of the file
+        let capacity = element.count.min(data.len() - body_start);
+        if position.is_some() {
+            contents.vertices.reserve(capacity);
+        }
+        if normal.is_some() {
+            contents.normals = Some(Vec::with_capacity(capacity));
+        }
+        if color.is_some() {
+            contents.colors = Some(Vec::with_capacity(capacity));
+        }
+
+        for instance in 0..element.count {
+            body.read_instance(element, &mut row, &mut starts)
+                .map_err(|err| match err {
+                    LoadError::Invalid { message, .. } => LoadError::invalid(&format!("{} {}: {}", element.name, instance, message)),
+                    err => err,
+                })?;
+
+            if let Some([x, y, z]) = position {
+                contents.vertices.push(Vec3 { x: row[starts[x]] as f32, y: row[starts[y]] as f32, z: row[starts[z]] as f32 });
+            }
+            if let (Some([x, y, z]), Some(normals)) = (normal, contents.normals.as_mut()) {
+                normals.push(Vec3 { x: row[starts[x]] as f32, y: row[starts[y]] as f32, z: row[starts[z]] as f32 });
+            }
+            if let (Some(channels), Some(colors)) = (color, contents.colors.as_mut()) {
+                let [r, g, b] = channels.map(|channel| match &element.properties[channel].kind {
+                    PropertyType::Scalar(kind) => (row[starts[channel]] / kind.color_max()) as f32,
+                    PropertyType::List { .. } => 1.0,
+                });
+                colors.push(Color(r, g, b));
+            }
+            if let Some(property) = vertex_indices {
+                let start = starts[property];
+                let count = row[start] as usize;
+                let indices = row[start + 1..start + 1 + count].iter()
+                    .map(|&index| whole_number(index)
+                        .ok_or_else(|| LoadError::invalid(&format!("face {} has a vertex index that isn't a whole number ({})", instance, index))))
+                    .collect::<Result<Vec<usize>,LoadError>>()?;
+                contents.faces.push(indices);
+            }
+        }
+    }
+
+    let vertex_count = contents.vertices.len();
+    if let Some((face, _)) = contents.faces.iter().enumerate().find(|(_, face)| face.iter().any(|&index| index >= vertex_count)) {
+        return Err(LoadError::invalid(&format!("face {} has a vertex index out of range (there are {} vertices)", face, vertex_count)));
+    }
+
+    Ok(contents)
+}
+
+/**
+ * A value that's meant to be a count or an index, if it is one
+ */
+fn whole_number(value: f64) -> Option<usize> {
+    if value >= 0.0 && value.fract() == 0.0 && value <= usize::MAX as f64 {
+        Some(value as usize)
+    } else {
+        None
+    }
+}
+
+/**
+ * The file's format and elements, and where the data after the header
+ * starts (in bytes, and as a line number for ASCII files)
+ */
+fn parse_header(data: &[u8]) -> Result<(Format,Vec<Element>,usize,usize),LoadError> {
+    let mut format = None;
+    let mut elements: Vec<Element> = Vec::new();
+
+    let mut offset = 0;
+    let mut line_number = 0;
+    loop {
+        let line_end = data[offset..].iter().position(|&byte| byte == b'\n')
+            .map(|end| offset + end)
+            .ok_or_else(|| LoadError::invalid("the header has no end_header"))?;
+        let line = String::from_utf8_lossy(&data[offset..line_end]);
+        offset = line_end + 1;
+        line_number += 1;
+
+        let segments: Vec<&str> = line.split_whitespace().collect();
+        if segments.first() == Some(&"end_header") {
+            break;
+        }
+
+        parse_header_line(&segments, line_number, &mut format, &mut elements)
+            .map_err(|err| err.at_line(line_number))?;
+    }
+
+    let format = format.ok_or_else(|| LoadError::invalid("the header has no format"))?;
+
+    Ok((format, elements, offset, line_number))
+}
+
+fn parse_header_line(segments: &[&str], line_number: usize, format: &mut Option<Format>, elements: &mut Vec<Element>) -> Result<(),LoadError> {
+    let keyword = segments.first().copied().unwrap_or("");
+    if line_number == 1 {
+        return if keyword == "ply" { Ok(()) } else { Err(LoadError::parse(keyword, "not a PLY file")) };
+    }
+
+    match keyword {
+        "format" => {
+            *format = Some(match arg(segments, 1)? {
+                "ascii" => Format::Ascii,
+                "binary_little_endian" => Format::BinaryLittleEndian,
+                "binary_big_endian" => Format::BinaryBigEndian,
+                other => return Err(LoadError::parse(other, "unknown format")),
+            });
+        },
+        "element" => elements.push(Element {
+            name: String::from(arg(segments, 1)?),
+            count: number_arg(segments, 2)?,
+            properties: Vec::new(),
+        }),
+        "property" => {
+            let property = parse_property(segments)?;
+            elements.last_mut()
+                .ok_or_else(|| LoadError::parse(keyword, "comes before any element"))?
+                .properties.push(property);
+        },
+
+        // comments, obj_info, and blank lines
+        _ => (),
+    }
+
+    Ok(())
+}
+
+fn parse_property(segments: &[&str]) -> Result<Property,LoadError> {
+    let scalar = |index: usize| -> Result<ScalarType,LoadError> {
+        let name = arg(segments, index)?;
+        ScalarType::from_name(name).ok_or_else(|| LoadError::parse(name, "unknown property type"))
+    };
+
+    if arg(segments, 1)? == "list" {
+        Ok(Property {
+            name: String::from(arg(segments, 4)?),
+            kind: PropertyType::List { count: scalar(2)?, item: scalar(3)? },
+        })
+    } else {
+        Ok(Property {
+            name: String::from(arg(segments, 2)?),
+            kind: PropertyType::Scalar(scalar(1)?),
+        })
+    }
+}
+
+fn required_property(element: &Element, names: &[&str]) -> Result<usize,LoadError> {
+    element.property_index(names)
+        .ok_or_else(|| LoadError::invalid(&format!("{} elements have no {} property", element.name, names[0])))
+}
+
+/**
+ * The data after the header, read one element at a time
+ */
+enum Body<'a> {
+    Ascii {
+        lines: Enumerate<Lines<'a>>,
+        line_offset: usize,
+        tokens: Vec<&'a str>,
+        next: usize,
+        line: usize,
+    },
+    Binary {
+        data: &'a [u8],
+        offset: usize,
+        big_endian: bool,
+    },
+}
+
+impl<'a> Body<'a> {
+
+    /**
+     * Read the next instance of an element into `row`, with where each
+     * property's values start in `starts`
+     */
+    fn read_instance(&mut self, element: &Element, row: &mut Vec<f64>, starts: &mut Vec<usize>) -> Result<(),LoadError> {
+        row.clear();
+        starts.clear();
+
+        // in ASCII files, each element is on its own line
+        if let Body::Ascii { lines, line_offset, tokens, next, line } = self {
+            let (index, text) = lines.by_ref()
+                .find(|(_, text)| !text.trim().is_empty())
+                .ok_or_else(|| LoadError::invalid("the file ends before it"))?;
+
+            *tokens = text.split_whitespace().collect();
+            *next = 0;
+            *line = *line_offset + index + 1;
+        }
+
+        for property in &element.properties {
+            starts.push(row.len());
+
+            match property.kind {
+                PropertyType::Scalar(kind) => {
+                    let value = self.value(kind)?;
+                    row.push(value);
+                },
+                PropertyType::List { count, item } => {
+                    let count = self.value(count)?;
+                    let length = whole_number(count)
+                        .ok_or_else(|| LoadError::invalid(&format!("a list's length isn't a whole number ({})", count)))?;
+                    row.push(count);
+
+                    for _ in 0..length {
+                        let value = self.value(item)?;
+                        row.push(value);
+                    }
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    fn value(&mut self, kind: ScalarType) -> Result<f64,LoadError> {
+        match self {
+            Body::Ascii { tokens, next, line, .. } => {
+                let token = tokens.get(*next)
+                    .ok_or_else(|| LoadError::parse(tokens.first().copied().unwrap_or(""), "too few values").at_line(*line))?;
+                *next += 1;
+
+                token.parse().map_err(|_| LoadError::parse(token, "not a valid number").at_line(*line))
+            },
+            Body::Binary { data, offset, big_endian } => {
+                let size = kind.size();
+                let bytes = data.get(*offset..*offset + size)
+                    .ok_or_else(|| LoadError::invalid("the file ends in the middle of it"))?;
+                *offset += size;
+
+                // kept in file order, and flipped if that's not little-endian
+                let mut buffer = [0u8; 8];
+                buffer[..size].copy_from_slice(bytes);
+                if *big_endian {
+                    buffer[..size].reverse();
+                }
+
+                Ok(match kind {
+                    ScalarType::I8 => buffer[0] as i8 as f64,
+                    ScalarType::U8 => buffer[0] as f64,
+                    ScalarType::I16 => i16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+                    ScalarType::U16 => u16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+                    ScalarType::I32 => i32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64,
+                    ScalarType::U32 => u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64,
+                    ScalarType::F32 => f32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64,
+                    ScalarType::F64 => f64::from_le_bytes(buffer),
+                })
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Vec3;
+    use crate::color::Color;
+    use crate::ply_parser::parse;
+
+    #[test]
+    fn test_parse() {
+        let ascii = "ply
+format ascii 1.0
+comment a colored quad, with an edge that's skipped
+element vertex 4
+property float x
+property float y
+property float z
+property float nx
+property float ny
+property float nz
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+element edge 1
+property int vertex1
+property int vertex2
+end_header
+0 0 0 0 0 1 255 0 0
+1 0 0 0 0 1 0 255 0
+1 1 0 0 0 1 0 0 255
+
+0 1 0 0 0 1 255 255 255
+4 0 1 2 3
+0 2
+";
+        let ply = parse(ascii.as_bytes()).unwrap();
+        assert_eq!(ply.vertices.len(), 4);
+        assert_eq!(ply.vertices[2], Vec3 { x: 1.0, y: 1.0, z: 0.0 });
+        assert_eq!(ply.normals.unwrap()[3], Vec3 { x: 0.0, y: 0.0, z: 1.0 });
+        assert_eq!(ply.colors.unwrap()[1], Color(0.0, 1.0, 0.0));
+        assert_eq!(ply.faces, vec![vec![0, 1, 2, 3]]);
+
+        // the same quad in both binary formats, without normals or colors
+        for &(format, big_endian) in &[("binary_little_endian", false), ("binary_big_endian", true)] {
+            let mut binary = format!("ply\nformat {} 1.0\nelement vertex 4\nproperty float x\nproperty float y\nproperty float z\nelement face 1\nproperty list uchar uint vertex_indices\nend_header\n", format).into_bytes();
+            for &(x, y) in &[(0.0f32, 0.0f32), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                for &value in &[x, y, 0.0] {
+                    binary.extend(&if big_endian { value.to_be_bytes() } else { value.to_le_bytes() });
+                }
+            }
+            binary.push(4);
+            for index in 0u32..4 {
+                binary.extend(&if big_endian { index.to_be_bytes() } else { index.to_le_bytes() });
+            }
+
+            let ply = parse(&binary).unwrap();
+            assert_eq!(ply.vertices[2], Vec3 { x: 1.0, y: 1.0, z: 0.0 });
+            assert!(ply.normals.is_none() && ply.colors.is_none());
+            assert_eq!(ply.faces, vec![vec![0, 1, 2, 3]]);
+
+            binary.pop();
+            assert!(parse(&binary).is_err());
+        }
+
+        let err = parse(b"ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\nproperty float z\nend_header\n0 zero 0\n").err().unwrap().to_string();
+        assert_eq!(err, "line 8, `zero`: not a valid number");
+        let err = parse(b"ply\nformat ascii 1.0\nelement vertex 1\nproperty half x\nend_header\n").err().unwrap().to_string();
+        assert_eq!(err, "line 4, `half`: unknown property type");
+        assert!(parse(b"ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\nproperty float z\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n0 0 0\n3 0 1 2\n").is_err());
+
+        // counts and indices that can't be right
+        assert!(parse(b"ply\nformat ascii 1.0\nelement vertex 99999999999999\nproperty float x\nproperty float y\nproperty float z\nend_header\n0 0 0\n").is_err());
+        let triangle = "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float y\nproperty float z\nelement face 1\nproperty list uchar int vertex_indices\nend_header\n0 0 0\n1 0 0\n0 1 0\n";
+        let err = parse(format!("{}3 -1 1 2\n", triangle).as_bytes()).err().unwrap().to_string();
+        assert!(err.ends_with("face 0 has a vertex index that isn't a whole number (-1)"));
+        assert!(parse(format!("{}3 0.5 1 2\n", triangle).as_bytes()).is_err());
+    }
+}
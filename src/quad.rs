@@ -0,0 +1,126 @@
+use rand::rngs::SmallRng;
+
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::object::Object;
+use crate::intersection::{Intersection,Interval};
+use crate::material::Material;
+use crate::illumination::Illumination;
+use crate::render_settings::RenderSettings;
+use crate::bounding_box::BoundingBox;
+use crate::utils::plane_intersection;
+use crate::world::World;
+
+/**
+ * A flat parallelogram (a rectangle, if the edges are perpendicular),
+ * covering `position + u * edge_u + v * edge_v` for u and v between 0 and
+ * 1. Those same u and v are its texture coordinates.
+ */
+pub struct Quad {
+    position: Vec3,
+    edge_u: Vec3,
+    edge_v: Vec3,
+    material: Material,
+
+    normal: Vec3,
+
+    // the edges' cross product, scaled so that dotting it with another
+    // cross product gives a ratio of areas
+    w: Vec3,
+}
+
+impl Quad {
+
+    pub fn new(position: Vec3, edge_u: Vec3, edge_v: Vec3, material: Material) -> Self {
+        let cross = edge_u.cross(&edge_v);
+
+        Self {
+            position,
+            edge_u,
+            edge_v,
+            material,
+            normal: cross.normalized(),
+            w: &cross * (1.0 / cross.dot(&cross)),
+        }
+    }
+}
+
+impl Object for Quad {
+
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        plane_intersection(&self.position, &self.normal, ray)
+            .and_then(|mut intersection| {
+                let uv = self.texture_coordinate(&intersection.position);
+
+                if (0.0..=1.0).contains(&uv.0) && (0.0..=1.0).contains(&uv.1) {
+                    intersection.uv = uv;
+                    Some(intersection)
+                } else {
+                    None
+                }
+            })
+    }
+
+    fn intervals(&self, _ray: &Ray) -> Vec<Interval> {
+        // flat, so there's no inside
+        Vec::new()
+    }
+
+    fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
+        let relative = point - &self.position;
+
+        (
+            self.w.dot(&relative.cross(&self.edge_v)),
+            self.w.dot(&self.edge_u.cross(&relative)),
+        )
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        let opposite = &self.position + &(&self.edge_u + &self.edge_v);
+
+        Some(BoundingBox::from_points(&[
+            self.position,
+            &self.position + &self.edge_u,
+            &self.position + &self.edge_v,
+            opposite,
+        ]))
+    }
+
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+        self.material.shade(
+            intersection,
+            world,
+            settings,
+            rng,
+            bounces_remaining
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Vec3;
+    use crate::ray::Ray;
+    use crate::material::Material;
+    use crate::object::Object;
+    use crate::quad::Quad;
+
+    #[test]
+    fn test_intersection_and_uv() {
+        // a floor tile, skewed along x
+        let quad = Quad::new(
+            Vec3 { x: 0.0, y: 0.0, z: 0.0 },
+            Vec3 { x: 4.0, y: 0.0, z: 0.0 },
+            Vec3 { x: 1.0, y: 0.0, z: -2.0 },
+            Material::new(),
+        );
+
+        let down = Vec3 { x: 0.0, y: -1.0, z: 0.0 };
+        let hit = quad.intersection(&Ray { origin: Vec3 { x: 2.5, y: 3.0, z: -1.0 }, direction: down }).unwrap();
+        assert!((hit.distance - 3.0).abs() < 0.0001);
+        assert!((hit.uv.0 - 0.5).abs() < 0.0001 && (hit.uv.1 - 0.5).abs() < 0.0001);
+
+        assert!(quad.intersection(&Ray { origin: Vec3 { x: 0.1, y: 3.0, z: -1.9 }, direction: down }).is_none());
+        assert!(quad.intersection(&Ray { origin: Vec3 { x: 4.5, y: 3.0, z: -0.1 }, direction: down }).is_none());
+    }
+}
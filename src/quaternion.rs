@@ -0,0 +1,89 @@
+use crate::vec3::Vec3;
+
+/**
+ * A rotation, stored as a unit quaternion. Mostly useful for composing
+ * rotations without accumulating shear; turn it into a `Matrix` with
+ * `Matrix::from_quaternion()` to actually apply it.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Quaternion {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Quaternion {
+
+    pub fn new() -> Self {
+        Self { x: 0.0, y: 0.0, z: 0.0, w: 1.0 }
+    }
+
+    /**
+     * A rotation of `theta` radians around `axis` (which doesn't need to
+     * be normalized)
+     */
+    pub fn from_axis_angle(axis: &Vec3, theta: f32) -> Self {
+        let axis = axis.normalized();
+        let half_sin = (theta / 2.0).sin();
+
+        Self {
+            x: axis.x * half_sin,
+            y: axis.y * half_sin,
+            z: axis.z * half_sin,
+            w: (theta / 2.0).cos(),
+        }
+    }
+
+    pub fn len(&self) -> f32 {
+        (self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w).sqrt()
+    }
+
+    pub fn normalized(&self) -> Self {
+        let len = self.len();
+
+        Self {
+            x: self.x / len,
+            y: self.y / len,
+            z: self.z / len,
+            w: self.w / len,
+        }
+    }
+
+    pub fn conjugate(&self) -> Self {
+        Self {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+            w: self.w,
+        }
+    }
+
+    pub fn rotate(&self, vec: &Vec3) -> Vec3 {
+        let result = &(self * &Quaternion { x: vec.x, y: vec.y, z: vec.z, w: 0.0 }) * &self.conjugate();
+
+        Vec3 { x: result.x, y: result.y, z: result.z }
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+ * Composition: `a * b` rotates by `b` first, then by `a`
+ */
+impl std::ops::Mul for &Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, other: Self) -> Self::Output {
+        Quaternion {
+            x: self.w * other.x + self.x * other.w + self.y * other.z - self.z * other.y,
+            y: self.w * other.y - self.x * other.z + self.y * other.w + self.z * other.x,
+            z: self.w * other.z + self.x * other.y - self.y * other.x + self.z * other.w,
+            w: self.w * other.w - self.x * other.x - self.y * other.y - self.z * other.z,
+        }
+    }
+}
@@ -0,0 +1,62 @@
+/**
+ * Knobs that trade render time for image quality. These are chosen at
+ * runtime, so a quick preview and a final render can share one build.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RenderSettings {
+
+    // Image resolution, in pixels
+    pub resolution_x: usize,
+    pub resolution_y: usize,
+
+    // Number of rays to cast through each pixel; more than one smooths out
+    // edges and depth-of-field blur
+    pub pixel_samples: usize,
+
+    // Number of sample rays to cast for diffuse/specular illumination
+    pub sample_count: usize,
+
+    // Max number of indirect bounces to make
+    pub bounces: u8,
+
+    // How many chunks the image should be split up into, for multithreading
+    pub threads: usize,
+
+    pub preview_mode: bool,
+}
+
+impl RenderSettings {
+
+    pub fn new() -> Self {
+        Self {
+            resolution_x: 103, // 16:10
+            resolution_y: 64,
+            pixel_samples: 1,
+            sample_count: 32,
+            bounces: 2,
+            threads: 256,
+            preview_mode: false,
+        }
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.resolution_x as f32 / self.resolution_y as f32
+    }
+
+    pub fn total_buffer_size(&self) -> usize {
+        self.resolution_x * self.resolution_y
+    }
+
+    /**
+     * Rounded up, so that the last chunk picks up any leftover pixels.
+     */
+    pub fn pixels_per_thread(&self) -> usize {
+        self.total_buffer_size().div_ceil(self.threads)
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use crate::object::{Object,ObjectEnum};
+use crate::camera::Camera;
+use crate::render_settings::RenderSettings;
+use crate::scene_parser::{load_and_parse,SceneError};
+use crate::gltf::Gltf;
+use crate::bounding_box::BoundingBox;
+use crate::vec3::Vec3;
+
+// for framing glTF files that don't have a camera
+const GLTF_FOV_DEGREES: f32 = 45.0;
+
+/**
+ * Everything needed to render an image, as loaded from a scene file.
+ */
+pub struct Scene {
+    pub objects: Vec<ObjectEnum>,
+    pub camera: Camera,
+    pub settings: RenderSettings,
+}
+
+impl Scene {
+
+    pub fn new(objects: Vec<ObjectEnum>) -> Self {
+        Self {
+            objects,
+            camera: Camera::new(),
+            settings: RenderSettings::new(),
+        }
+    }
+
+    /**
+     * Load a scene description file from disk. See `scene_parser` for
+     * the format. `.gltf` and `.glb` files can be loaded directly too.
+     */
+    pub fn from_file(path: &str) -> Result<Self,SceneError> {
+        match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+            Some("gltf") | Some("glb") => Gltf::load(path, None)
+                .map(Self::from_gltf)
+                .map_err(SceneError::Load),
+            _ => load_and_parse(path),
+        }
+    }
+
+    /**
+     * Everything in a glTF file, seen through its first camera. Files
+     * without one are looked at from the front, far enough back to fit
+     * everything in.
+     */
+    pub fn from_gltf(gltf: Gltf) -> Self {
+        let mut objects: Vec<ObjectEnum> = gltf.meshes.into_iter()
+            .map(|(_, mesh)| ObjectEnum::Mesh(mesh))
+            .collect();
+        objects.extend(gltf.lights);
+
+        let camera = gltf.cameras.first().copied().unwrap_or_else(|| {
+            let bounds = objects.iter()
+                .filter_map(|object| object.bounds())
+                .fold(BoundingBox::empty(), |bounds, object_bounds| bounds.union(&object_bounds));
+            if bounds.is_empty() {
+                return Camera::new();
+            }
+
+            let center = bounds.centroid();
+            let radius = bounds.size().len() / 2.0;
+            let distance = radius / (GLTF_FOV_DEGREES.to_radians() / 2.0).sin();
+
+            Camera::look_at(
+                &center + &Vec3 { x: 0.0, y: 0.0, z: distance },
+                center,
+                Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+                GLTF_FOV_DEGREES,
+            )
+        });
+
+        let mut scene = Self::new(objects);
+        scene.camera = camera;
+
+        scene
+    }
+}
@@ -0,0 +1,1348 @@
+/*!
+ * Parser for the raytracer's scene description files.
+ *
+ * A scene file is plain text made up of blocks. Each block starts with a
+ * header line (`settings`, `camera`, `newmtl`, an object type, `group`, a
+ * CSG operation or `end`) and is followed by property lines that apply to it, until the
+ * next header. Indentation is ignored, and anything after a `#`
+ * is a comment.
+ *
+ * ```text
+ * # render quality; every property is optional
+ * settings
+ *     resolution 1920 1200
+ *     pixel_samples 4     # rays per pixel, for antialiasing and depth of field
+ *     samples 32          # sample rays per diffuse/specular bounce
+ *     bounces 2
+ *     threads 256         # chunks the image is split into
+ *     preview false
+ *
+ * # where the image is taken from; every property is optional
+ * camera
+ *     position 0 2 5
+ *     target 0 0 -10      # the point at the center of the image
+ *     up 0 1 0
+ *     projection perspective  # or: orthographic <height>, fisheye, equirectangular
+ *     fov 60              # vertical field of view in degrees (fisheye: up to 360)
+ *     aspect 1.6          # defaults to the resolution's aspect ratio
+ *     aperture 0.1        # lens radius; 0 (the default) keeps everything sharp
+ *     focus_distance 12   # defaults to the distance to the target
+ *
+ * # a named material (as in MTL files); objects refer to it by name
+ * newmtl floor
+ *     albedo map texture.jpg
+ *
+ * newmtl light
+ *     emission_color 1.0 0.95 0.8
+ *     emission_intensity 1.0
+ *
+ * sphere
+ *     position 3 -3 -13
+ *     radius 1
+ *     material light
+ *
+ * sphere                  # an ellipsoid
+ *     position 0 0 0
+ *     radius 1
+ *     scale 2 1 1
+ *     translate -3 -4 -12
+ *     material light
+ *
+ * plane
+ *     position 0 -5 0
+ *     normal 0 1 0
+ *     bias 0 0 -1         # optional; orients the texture on the plane
+ *     material floor
+ *
+ * triangle                # textured from (0, 0) at v0, to (1, 0) at v1 and (0, 1) at v2
+ *     v0 -1 0 -5
+ *     v1 1 0 -5
+ *     v2 0 1 -5
+ *     material floor
+ *
+ * quad                    # covers position + u * edge_u + v * edge_v, for u and v in 0..1
+ *     position -1 4.9 -11
+ *     edge_u 2 0 0
+ *     edge_v 0 0 -2
+ *     material light
+ *
+ * disk
+ *     position 0 -4.9 -10
+ *     normal 0 1 0
+ *     radius 2
+ *     material floor
+ *
+ * box                     # axis-aligned; rotate it with a transform
+ *     min -4 -5 -14
+ *     max -2 -3 -12
+ *     material floor
+ *
+ * cylinder                # standing upright on its base position; capped
+ *     position 2 -5 -14
+ *     radius 0.5
+ *     height 2
+ *     material floor
+ *
+ * cone                    # same as a cylinder, narrowing to a point
+ *     position 4 -5 -14
+ *     radius 0.5
+ *     height 2
+ *     material floor
+ *
+ * torus                   # lying flat, around its position
+ *     position 0 -4.5 -8
+ *     major_radius 1      # from the center to the middle of the tube
+ *     minor_radius 0.25
+ *     material floor
+ *
+ * sdf                     # a distance field, ray marched (see below)
+ *     shape smooth_union 0.3 sphere 1 translate 1 0 0 twist 1 box 0.5 1 0.5
+ *     translate 0 -3 -14
+ *     material floor
+ *
+ * mesh
+ *     file test.obj       # or a .ply or .stl file; whole OBJ files are cached
+ *                         # beside them, in test.obj.cache, until they change
+ *     object Fox_Cube.003 # optional; just this object (or group) from the file
+ *     scale 0.5           # transforms are applied in the order listed
+ *     rotate_y 180
+ *     translate 0 -3 -10
+ *     material floor      # optional; used for faces with no MTL material
+ *     smooth 60           # optional; smooth normals for faces without any, except
+ *                         # where faces meet at more than this many degrees
+ *
+ * gltf                    # every mesh and point light in a .gltf or .glb file
+ *     file helmet.glb
+ *     rotate_y 90
+ *     material floor      # optional; used for primitives with no material
+ *
+ * # objects between `group` and `end` are moved as a unit; groups can nest
+ * group
+ *     rotate_y 30         # applies on top of each object's own transform
+ *     translate 2 -4 -12
+ *
+ *     sphere
+ *         position 0 1 0
+ *         radius 1
+ *         material floor
+ *
+ *     mesh
+ *         file test.obj
+ *         material floor
+ * end
+ *
+ * # solids can be combined with `union`, `intersection` or `difference`
+ * # (the first object minus the rest); like a group, they take transforms
+ * difference
+ *     translate 0 -3 -8
+ *
+ *     box
+ *         min -1 -1 -1
+ *         max 1 1 1
+ *         material floor
+ *
+ *     cylinder            # each piece of the surface keeps its own material
+ *         position 0 -2 0
+ *         radius 0.5
+ *         height 4
+ *         material light
+ * end
+ * ```
+ *
+ * An `sdf` shape is written in prefix order, with each operation followed
+ * by its numbers and then the shape(s) it applies to. Shapes are centered
+ * on the origin:
+ * - `sphere <radius>`, `box <half size xyz>`, `torus <major> <minor>`,
+ *   `cylinder <radius> <height>`, `mandelbulb <power> <iterations>`
+ * - `translate <xyz> <shape>`, `repeat <period xyz> <shape>` (0 to not
+ *   repeat along an axis), `twist <radians per unit> <shape>`
+ * - `union <shape> <shape>`, `smooth_union <smoothness> <shape> <shape>`
+ *
+ * Material properties are `albedo`, `specular`, `normal`, `emission_color`,
+ * `emission_intensity` and `opacity`. Each takes one of:
+ * - a single number, for a solid gray (`specular 1`)
+ * - three numbers, for a solid color (`albedo 1 0 0`)
+ * - `map <path>`, for an image texture (`normal map cobblestone_normal.jpg`)
+ * - `checker`, for the built-in procedural checkerboard
+ *
+ * An `opacity` below 1 lets light through, bent by `index_of_refraction`
+ * (a single number) if there is one. Meshes get their materials from their
+ * MTL files, which can set all of these too, and glTF files bring their own.
+ *
+ * Any object or group can be transformed, with `translate x y z`, `rotate_x deg`,
+ * `rotate_y deg`, `rotate_z deg`, and `scale s` or `scale x y z`. Mesh
+ * blocks that share a file (and material) load it only once, and each
+ * becomes a transformed instance of it.
+ *
+ * Relative paths are resolved against the directory of the scene file.
+ * Materials must be defined before the objects that use them, and are
+ * visible everywhere after that (including outside of the group they
+ * were defined in). `settings` and `camera` can't be inside a group.
+ */
+
+use std::fs;
+use std::f32::consts::PI;
+use std::fmt;
+use std::path::Path;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::scene::Scene;
+use crate::render_settings::RenderSettings;
+use crate::camera::{Camera,Projection};
+use crate::object::ObjectEnum;
+use crate::material::Material;
+use crate::texture::{Texture,checker};
+use crate::color::Color;
+use crate::vec3::Vec3;
+use crate::matrix::{Matrix,IDENTITY};
+use crate::sphere::Sphere;
+use crate::plane::Plane;
+use crate::triangle::Triangle;
+use crate::quad::Quad;
+use crate::disk::Disk;
+use crate::cuboid::Cuboid;
+use crate::cylinder::Cylinder;
+use crate::cone::Cone;
+use crate::torus::Torus;
+use crate::mesh::Mesh;
+use crate::gltf::Gltf;
+use crate::load_error::LoadError;
+use crate::transformed::Transformed;
+use crate::group::Group;
+use crate::csg::{Csg,CsgOperation};
+use crate::sdf::{Sdf,DistanceField};
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io { path: String, message: String },
+    Parse { line: usize, field: String, message: String },
+
+    // a model loaded directly, rather than through a scene file
+    Load(LoadError),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io { path, message } =>
+                write!(f, "failed to read \"{}\": {}", path, message),
+            SceneError::Parse { line, field, message } =>
+                write!(f, "line {}, `{}`: {}", line, field, message),
+            SceneError::Load(err) =>
+                write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for SceneError { }
+
+pub fn load_and_parse(path: &str) -> Result<Scene,SceneError> {
+    let data = fs::read_to_string(path).map_err(|err| SceneError::Io {
+        path: String::from(path),
+        message: err.to_string(),
+    })?;
+
+    println!("Loading scene...");
+
+    let base_dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+
+    parse(&data, base_dir)
+}
+
+pub fn parse(scene: &str, base_dir: &Path) -> Result<Scene,SceneError> {
+    let mut settings = RenderSettings::new();
+    let mut camera = Camera::new();
+    let mut materials: HashMap<String,Material> = HashMap::new();
+    let mut meshes: MeshCache = HashMap::new();
+
+    // the whole scene, and the groups currently open inside of it
+    let mut root = Group::new();
+    let mut open_groups: Vec<OpenGroup> = Vec::new();
+
+    let blocks = split_blocks(scene)?;
+    for block in &blocks {
+        match block.header.keyword {
+            "settings" | "camera" if !open_groups.is_empty() =>
+                return Err(block.header.error("can't be inside of a group")),
+            "settings" => apply_settings(block, &mut settings)?,
+            "camera" => apply_camera(block, &mut camera)?,
+            "newmtl" => {
+                let (name, material) = build_material(block, base_dir)?;
+
+                if materials.contains_key(&name) {
+                    return Err(block.header.error(&format!("material \"{}\" is already defined", name)));
+                }
+
+                materials.insert(name, material);
+            },
+            "sphere" => innermost_group(&mut root, &mut open_groups).push_object(build_sphere(block, &materials)?, block.header.line),
+            "plane" => innermost_group(&mut root, &mut open_groups).push_object(build_plane(block, &materials)?, block.header.line),
+            "triangle" => innermost_group(&mut root, &mut open_groups).push_object(build_triangle(block, &materials)?, block.header.line),
+            "quad" => innermost_group(&mut root, &mut open_groups).push_object(build_quad(block, &materials)?, block.header.line),
+            "disk" => innermost_group(&mut root, &mut open_groups).push_object(build_disk(block, &materials)?, block.header.line),
+            "box" => innermost_group(&mut root, &mut open_groups).push_object(build_box(block, &materials)?, block.header.line),
+            "cylinder" => innermost_group(&mut root, &mut open_groups).push_object(build_cylinder(block, &materials)?, block.header.line),
+            "cone" => innermost_group(&mut root, &mut open_groups).push_object(build_cone(block, &materials)?, block.header.line),
+            "torus" => innermost_group(&mut root, &mut open_groups).push_object(build_torus(block, &materials)?, block.header.line),
+            "sdf" => innermost_group(&mut root, &mut open_groups).push_object(build_sdf(block, &materials)?, block.header.line),
+            "mesh" => innermost_group(&mut root, &mut open_groups).push_object(build_mesh(block, &materials, &mut meshes, base_dir)?, block.header.line),
+            "gltf" => innermost_group(&mut root, &mut open_groups).push_group(build_gltf(block, &materials, base_dir)?),
+            "group" | "union" | "intersection" | "difference" => open_groups.push(OpenGroup {
+                line: block.header.line,
+                keyword: block.header.keyword,
+                group: build_group(block)?,
+            }),
+            "end" => {
+                block.header.expect_args(0)?;
+                if let Some(statement) = block.body.first() {
+                    return Err(block.unknown_property(statement));
+                }
+
+                let OpenGroup { line, keyword, group } = open_groups.pop()
+                    .ok_or_else(|| block.header.error("there's no group to end"))?;
+
+                match csg_operation(keyword) {
+                    Some(operation) => {
+                        let csg = Csg::from_objects(operation, flattened(group, &blocks)?)
+                            .ok_or_else(|| SceneError::Parse {
+                                line,
+                                field: String::from(keyword),
+                                message: format!("{} needs at least two objects", keyword),
+                            })?;
+
+                        innermost_group(&mut root, &mut open_groups).push_object(ObjectEnum::Csg(csg), line);
+                    },
+                    None => innermost_group(&mut root, &mut open_groups).push_group(group),
+                }
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    if let Some(open) = open_groups.last() {
+        return Err(SceneError::Parse {
+            line: open.line,
+            field: String::from(open.keyword),
+            message: format!("{} is missing its `end`", open.keyword),
+        });
+    }
+
+    let mut scene = Scene::new(flattened(root, &blocks)?);
+    scene.camera = camera;
+    scene.settings = settings;
+
+    Ok(scene)
+}
+
+/**
+ * The objects in a group, placed by all of the transforms they're under
+ */
+fn flattened(group: Group, blocks: &[Block]) -> Result<Vec<ObjectEnum>,SceneError> {
+    group.flatten().map_err(|line| {
+        let header = blocks.iter()
+            .map(|block| &block.header)
+            .find(|header| header.line == line)
+            .expect("objects are added from the blocks");
+
+        header.error("transform can't be inverted once combined with its groups' (is something scaled too small?)")
+    })
+}
+
+/**
+ * The group that objects are currently being added to
+ */
+fn innermost_group<'a>(root: &'a mut Group, open_groups: &'a mut [OpenGroup]) -> &'a mut Group {
+    match open_groups.last_mut() {
+        Some(open) => &mut open.group,
+        None => root,
+    }
+}
+
+/**
+ * A `group` (or CSG block, which collects its objects the same way) that
+ * hasn't reached its `end` yet
+ */
+struct OpenGroup<'a> {
+    line: usize,
+    keyword: &'a str,
+    group: Group,
+}
+
+fn csg_operation(keyword: &str) -> Option<CsgOperation> {
+    match keyword {
+        "union" => Some(CsgOperation::Union),
+        "intersection" => Some(CsgOperation::Intersection),
+        "difference" => Some(CsgOperation::Difference),
+        _ => None,
+    }
+}
+
+const BLOCK_KEYWORDS: [&str;20] = [
+    "settings", "camera", "newmtl",
+    "sphere", "plane", "triangle", "quad", "disk",
+    "box", "cylinder", "cone", "torus", "sdf", "mesh", "gltf",
+    "group", "union", "intersection", "difference", "end",
+];
+
+/**
+ * One non-empty line of a scene file, split into its keyword and arguments.
+ */
+struct Statement<'a> {
+    line: usize,
+    keyword: &'a str,
+    args: Vec<&'a str>,
+}
+
+impl<'a> Statement<'a> {
+
+    fn error(&self, message: &str) -> SceneError {
+        SceneError::Parse {
+            line: self.line,
+            field: String::from(self.keyword),
+            message: String::from(message),
+        }
+    }
+
+    fn expect_args(&self, count: usize) -> Result<(),SceneError> {
+        if self.args.len() == count {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {} argument(s), found {}", count, self.args.len())))
+        }
+    }
+
+    fn f32_at(&self, index: usize) -> Result<f32,SceneError> {
+        self.args[index].parse()
+            .map_err(|_| self.error(&format!("expected a number, found \"{}\"", self.args[index])))
+    }
+
+    fn usize_at(&self, index: usize) -> Result<usize,SceneError> {
+        match self.args[index].parse() {
+            Ok(value) if value > 0 => Ok(value),
+            _ => Err(self.error(&format!("expected a positive whole number, found \"{}\"", self.args[index]))),
+        }
+    }
+
+    fn usize_arg(&self) -> Result<usize,SceneError> {
+        self.expect_args(1)?;
+        self.usize_at(0)
+    }
+
+    fn bool_arg(&self) -> Result<bool,SceneError> {
+        self.expect_args(1)?;
+        match self.args[0] {
+            "true" | "on" => Ok(true),
+            "false" | "off" => Ok(false),
+            other => Err(self.error(&format!("expected true or false, found \"{}\"", other))),
+        }
+    }
+
+    fn f32_arg(&self) -> Result<f32,SceneError> {
+        self.expect_args(1)?;
+        self.f32_at(0)
+    }
+
+    fn vec3_arg(&self) -> Result<Vec3,SceneError> {
+        self.expect_args(3)?;
+        Ok(Vec3 {
+            x: self.f32_at(0)?,
+            y: self.f32_at(1)?,
+            z: self.f32_at(2)?,
+        })
+    }
+
+    fn string_arg(&self) -> Result<&'a str,SceneError> {
+        self.expect_args(1)?;
+        Ok(self.args[0])
+    }
+}
+
+struct Block<'a> {
+    header: Statement<'a>,
+    body: Vec<Statement<'a>>,
+}
+
+impl<'a> Block<'a> {
+
+    /**
+     * Find the (last) property with the given keyword, if any.
+     */
+    fn find(&self, keyword: &str) -> Option<&Statement<'a>> {
+        self.body.iter().rev().find(|statement| statement.keyword == keyword)
+    }
+
+    fn require(&self, keyword: &str) -> Result<&Statement<'a>,SceneError> {
+        self.find(keyword).ok_or_else(|| SceneError::Parse {
+            line: self.header.line,
+            field: String::from(keyword),
+            message: format!("{} is missing this required property", self.header.keyword),
+        })
+    }
+
+    fn unknown_property(&self, statement: &Statement) -> SceneError {
+        statement.error(&format!("unknown property for {}", self.header.keyword))
+    }
+}
+
+fn split_blocks(scene: &str) -> Result<Vec<Block<'_>>,SceneError> {
+    let mut blocks: Vec<Block> = Vec::new();
+
+    for (index, line) in scene.lines().enumerate() {
+        let content = match line.find('#') {
+            Some(comment_start) => &line[..comment_start],
+            None => line,
+        };
+
+        let mut segments = content.split_whitespace();
+        let keyword = match segments.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+
+        let statement = Statement {
+            line: index + 1,
+            keyword,
+            args: segments.collect(),
+        };
+
+        if BLOCK_KEYWORDS.contains(&keyword) {
+            blocks.push(Block {
+                header: statement,
+                body: Vec::new(),
+            });
+        } else {
+            match blocks.last_mut() {
+                Some(block) => block.body.push(statement),
+                None => return Err(statement.error("property appears before any settings, camera, newmtl or object")),
+            }
+        }
+    }
+
+    Ok(blocks)
+}
+
+fn apply_settings(block: &Block, settings: &mut RenderSettings) -> Result<(),SceneError> {
+    block.header.expect_args(0)?;
+
+    for statement in &block.body {
+        match statement.keyword {
+            "resolution" => {
+                statement.expect_args(2)?;
+                settings.resolution_x = statement.usize_at(0)?;
+                settings.resolution_y = statement.usize_at(1)?;
+            },
+            "pixel_samples" => settings.pixel_samples = statement.usize_arg()?,
+            "samples" => settings.sample_count = statement.usize_arg()?,
+            "bounces" => {
+                statement.expect_args(1)?;
+                settings.bounces = statement.args[0].parse()
+                    .map_err(|_| statement.error(&format!("expected a whole number from 0 to {}, found \"{}\"", u8::MAX, statement.args[0])))?;
+            },
+            "threads" => settings.threads = statement.usize_arg()?,
+            "preview" => settings.preview_mode = statement.bool_arg()?,
+            _ => return Err(block.unknown_property(statement)),
+        }
+    }
+
+    Ok(())
+}
+
+fn apply_camera(block: &Block, camera: &mut Camera) -> Result<(),SceneError> {
+    block.header.expect_args(0)?;
+
+    for statement in &block.body {
+        match statement.keyword {
+            "position" => camera.position = statement.vec3_arg()?,
+            "target" => camera.target = statement.vec3_arg()?,
+            "up" => camera.up = statement.vec3_arg()?,
+            "projection" => camera.projection = match statement.args.as_slice() {
+                ["perspective"] => Projection::Perspective,
+                ["orthographic", _] => {
+                    let height = statement.f32_at(1)?;
+                    if height <= 0.0 {
+                        return Err(statement.error("orthographic height must be positive"));
+                    }
+                    Projection::Orthographic { height }
+                },
+                ["fisheye"] => Projection::Fisheye,
+                ["equirectangular"] => Projection::Equirectangular,
+                _ => return Err(statement.error("expected perspective, orthographic <height>, fisheye or equirectangular")),
+            },
+            "fov" => {
+                let fov = statement.f32_arg()?;
+                if fov <= 0.0 || fov > 360.0 {
+                    return Err(statement.error("expected an angle between 0 and 360 degrees"));
+                }
+                camera.fov = fov.to_radians();
+            },
+            "aspect" => {
+                let aspect_ratio = statement.f32_arg()?;
+                if aspect_ratio <= 0.0 {
+                    return Err(statement.error("aspect ratio must be positive"));
+                }
+                camera.aspect_ratio = Some(aspect_ratio);
+            },
+            "aperture" => {
+                let aperture = statement.f32_arg()?;
+                if aperture < 0.0 {
+                    return Err(statement.error("aperture can't be negative"));
+                }
+                camera.aperture = aperture;
+            },
+            "focus_distance" => {
+                let focus_distance = statement.f32_arg()?;
+                if focus_distance <= 0.0 {
+                    return Err(statement.error("focus distance must be positive"));
+                }
+                camera.focus_distance = Some(focus_distance);
+            },
+            _ => return Err(block.unknown_property(statement)),
+        }
+    }
+
+    if camera.projection == Projection::Perspective && camera.fov >= PI {
+        let fov = block.find("fov").unwrap_or(&block.header);
+        return Err(fov.error("a perspective camera's field of view must be less than 180 degrees"));
+    }
+
+    let (forward, _, _) = camera.basis();
+    if forward.len_squared().is_nan() || forward.cross(&camera.up).len_squared() < 1e-12 {
+        return Err(block.header.error("camera target must differ from its position, and not lie straight along its up vector"));
+    }
+
+    Ok(())
+}
+
+fn build_material(block: &Block, base_dir: &Path) -> Result<(String,Material),SceneError> {
+    let name = String::from(block.header.string_arg()?);
+    let mut material = Material::new();
+
+    for statement in &block.body {
+        if statement.keyword == "index_of_refraction" {
+            material.index_of_refraction = Some(statement.f32_arg()?);
+            continue;
+        }
+
+        let texture = Some(parse_texture(statement, base_dir)?);
+
+        match statement.keyword {
+            "albedo" => material.texture_albedo = texture,
+            "specular" => material.texture_specular = texture,
+            "normal" => material.texture_normal = texture,
+            "emission_color" => material.texture_emission_color = texture,
+            "emission_intensity" => material.texture_emission_intensity = texture,
+            "opacity" => material.texture_opacity = texture,
+            _ => return Err(block.unknown_property(statement)),
+        }
+    }
+
+    Ok((name, material))
+}
+
+fn parse_texture(statement: &Statement, base_dir: &Path) -> Result<Texture,SceneError> {
+    match statement.args.as_slice() {
+        ["map", file] => Texture::from_image(&resolve_path(statement, base_dir, file)?)
+            .map_err(|err| statement.error(&err.to_string())),
+        ["checker"] => Ok(Texture::Procedural(&checker)),
+        [_] => Ok(Texture::Solid(Color::gray(statement.f32_at(0)?))),
+        [_, _, _] => Ok(Texture::Solid(Color(
+            statement.f32_at(0)?,
+            statement.f32_at(1)?,
+            statement.f32_at(2)?,
+        ))),
+        _ => Err(statement.error("expected a gray value, an RGB color, `map <path>` or `checker`")),
+    }
+}
+
+fn build_sphere(block: &Block, materials: &HashMap<String,Material>) -> Result<ObjectEnum,SceneError> {
+    check_object_properties(block, &["position", "radius", "material"])?;
+
+    placed(block, ObjectEnum::Sphere(Sphere::new(
+        block.require("position")?.vec3_arg()?,
+        block.require("radius")?.f32_arg()?,
+        lookup_material(block.require("material")?, materials)?,
+    )))
+}
+
+fn build_plane(block: &Block, materials: &HashMap<String,Material>) -> Result<ObjectEnum,SceneError> {
+    check_object_properties(block, &["position", "normal", "bias", "material"])?;
+
+    let normal = block.require("normal")?.vec3_arg()?;
+    let bias = match block.find("bias") {
+        Some(statement) => statement.vec3_arg()?,
+        None => default_bias(&normal),
+    };
+
+    placed(block, ObjectEnum::Plane(Plane::new(
+        block.require("position")?.vec3_arg()?,
+        normal,
+        bias,
+        lookup_material(block.require("material")?, materials)?,
+    )))
+}
+
+fn build_triangle(block: &Block, materials: &HashMap<String,Material>) -> Result<ObjectEnum,SceneError> {
+    check_object_properties(block, &["v0", "v1", "v2", "material"])?;
+
+    placed(block, ObjectEnum::Triangle(Triangle::new(
+        block.require("v0")?.vec3_arg()?,
+        block.require("v1")?.vec3_arg()?,
+        block.require("v2")?.vec3_arg()?,
+        lookup_material(block.require("material")?, materials)?,
+    )))
+}
+
+fn build_quad(block: &Block, materials: &HashMap<String,Material>) -> Result<ObjectEnum,SceneError> {
+    check_object_properties(block, &["position", "edge_u", "edge_v", "material"])?;
+
+    let edge_u = block.require("edge_u")?;
+    let edge_v = block.require("edge_v")?;
+    if edge_u.vec3_arg()?.cross(&edge_v.vec3_arg()?).len_squared() == 0.0 {
+        return Err(edge_v.error("a quad's edges can't be parallel (or zero)"));
+    }
+
+    placed(block, ObjectEnum::Quad(Quad::new(
+        block.require("position")?.vec3_arg()?,
+        edge_u.vec3_arg()?,
+        edge_v.vec3_arg()?,
+        lookup_material(block.require("material")?, materials)?,
+    )))
+}
+
+fn build_disk(block: &Block, materials: &HashMap<String,Material>) -> Result<ObjectEnum,SceneError> {
+    check_object_properties(block, &["position", "normal", "radius", "material"])?;
+
+    placed(block, ObjectEnum::Disk(Disk::new(
+        block.require("position")?.vec3_arg()?,
+        block.require("normal")?.vec3_arg()?,
+        block.require("radius")?.f32_arg()?,
+        lookup_material(block.require("material")?, materials)?,
+    )))
+}
+
+fn build_box(block: &Block, materials: &HashMap<String,Material>) -> Result<ObjectEnum,SceneError> {
+    check_object_properties(block, &["min", "max", "material"])?;
+
+    placed(block, ObjectEnum::Cuboid(Cuboid::new(
+        block.require("min")?.vec3_arg()?,
+        block.require("max")?.vec3_arg()?,
+        lookup_material(block.require("material")?, materials)?,
+    )))
+}
+
+fn build_cylinder(block: &Block, materials: &HashMap<String,Material>) -> Result<ObjectEnum,SceneError> {
+    check_object_properties(block, &["position", "radius", "height", "material"])?;
+
+    placed(block, ObjectEnum::Cylinder(Cylinder::new(
+        block.require("position")?.vec3_arg()?,
+        block.require("radius")?.f32_arg()?,
+        block.require("height")?.f32_arg()?,
+        lookup_material(block.require("material")?, materials)?,
+    )))
+}
+
+fn build_cone(block: &Block, materials: &HashMap<String,Material>) -> Result<ObjectEnum,SceneError> {
+    check_object_properties(block, &["position", "radius", "height", "material"])?;
+
+    let height = block.require("height")?;
+    if height.f32_arg()? <= 0.0 {
+        return Err(height.error("a cone's height has to be positive"));
+    }
+
+    placed(block, ObjectEnum::Cone(Cone::new(
+        block.require("position")?.vec3_arg()?,
+        block.require("radius")?.f32_arg()?,
+        height.f32_arg()?,
+        lookup_material(block.require("material")?, materials)?,
+    )))
+}
+
+fn build_torus(block: &Block, materials: &HashMap<String,Material>) -> Result<ObjectEnum,SceneError> {
+    check_object_properties(block, &["position", "major_radius", "minor_radius", "material"])?;
+
+    placed(block, ObjectEnum::Torus(Torus::new(
+        block.require("position")?.vec3_arg()?,
+        block.require("major_radius")?.f32_arg()?,
+        block.require("minor_radius")?.f32_arg()?,
+        lookup_material(block.require("material")?, materials)?,
+    )))
+}
+
+fn build_sdf(block: &Block, materials: &HashMap<String,Material>) -> Result<ObjectEnum,SceneError> {
+    check_object_properties(block, &["shape", "material"])?;
+
+    let shape = block.require("shape")?;
+    let mut args = shape.args.iter();
+    let sdf = parse_sdf(shape, &mut args)?;
+    if let Some(extra) = args.next() {
+        return Err(shape.error(&format!("unexpected \"{}\" after the end of the shape", extra)));
+    }
+
+    placed(block, ObjectEnum::DistanceField(DistanceField::new(
+        sdf,
+        lookup_material(block.require("material")?, materials)?,
+    )))
+}
+
+/**
+ * Read one (possibly nested) shape from the front of an `sdf` block's
+ * `shape` arguments
+ */
+fn parse_sdf<'a, I: Iterator<Item = &'a &'a str>>(statement: &Statement, args: &mut I) -> Result<Sdf,SceneError> {
+    let number = |args: &mut I| -> Result<f32,SceneError> {
+        let arg = args.next().ok_or_else(|| statement.error("shape ends too early"))?;
+        arg.parse().map_err(|_| statement.error(&format!("expected a number, found \"{}\"", arg)))
+    };
+    let count = |args: &mut I| -> Result<usize,SceneError> {
+        let arg = args.next().ok_or_else(|| statement.error("shape ends too early"))?;
+        match arg.parse() {
+            Ok(value) if value > 0 => Ok(value),
+            _ => Err(statement.error(&format!("expected a positive whole number, found \"{}\"", arg))),
+        }
+    };
+    let vec3 = |args: &mut I| -> Result<Vec3,SceneError> {
+        Ok(Vec3 { x: number(args)?, y: number(args)?, z: number(args)? })
+    };
+
+    let name = args.next().ok_or_else(|| statement.error("shape ends too early"))?;
+    Ok(match *name {
+        "sphere" => Sdf::Sphere { radius: number(args)? },
+        "box" => Sdf::Box { half_size: vec3(args)? },
+        "torus" => Sdf::Torus { major_radius: number(args)?, minor_radius: number(args)? },
+        "cylinder" => Sdf::Cylinder { radius: number(args)?, height: number(args)? },
+        "mandelbulb" => Sdf::Mandelbulb { power: number(args)?, iterations: count(args)? },
+        "translate" => Sdf::Translate { offset: vec3(args)?, sdf: Box::new(parse_sdf(statement, args)?) },
+        "repeat" => Sdf::Repeat { period: vec3(args)?, sdf: Box::new(parse_sdf(statement, args)?) },
+        "twist" => Sdf::Twist { rate: number(args)?, sdf: Box::new(parse_sdf(statement, args)?) },
+        "union" => Sdf::Union(Box::new(parse_sdf(statement, args)?), Box::new(parse_sdf(statement, args)?)),
+        "smooth_union" => {
+            let smoothness = number(args)?;
+            if smoothness <= 0.0 {
+                return Err(statement.error("smoothness has to be positive"));
+            }
+
+            Sdf::SmoothUnion {
+                smoothness,
+                a: Box::new(parse_sdf(statement, args)?),
+                b: Box::new(parse_sdf(statement, args)?),
+            }
+        },
+        other => return Err(statement.error(&format!("unknown shape \"{}\"", other))),
+    })
+}
+
+/**
+ * Pick a texture orientation for a plane that didn't specify one; any
+ * direction that isn't parallel to the normal will do.
+ */
+fn default_bias(normal: &Vec3) -> Vec3 {
+    if normal.y.abs() > normal.x.abs() && normal.y.abs() > normal.z.abs() {
+        Vec3 { x: 0.0, y: 0.0, z: -1.0 }
+    } else {
+        Vec3 { x: 0.0, y: 1.0, z: 0.0 }
+    }
+}
+
+/**
+ * Meshes loaded so far, by file, default material name, smoothing crease
+ * angle (as bits, to be hashable) and object name, so that instances can
+ * share them
+ */
+type MeshCache = HashMap<(String,Option<String>,Option<u32>,Option<String>),Arc<ObjectEnum>>;
+
+fn build_mesh(block: &Block, materials: &HashMap<String,Material>, meshes: &mut MeshCache, base_dir: &Path) -> Result<ObjectEnum,SceneError> {
+    check_object_properties(block, &["file", "object", "material", "smooth"])?;
+
+    let file = block.require("file")?;
+    let path = resolve_path(file, base_dir, file.string_arg()?)?;
+
+    let material_name = match block.find("material") {
+        Some(statement) => Some(statement.string_arg()?),
+        None => None,
+    };
+
+    let crease_angle = match block.find("smooth") {
+        Some(statement) => Some(statement.f32_arg()?.to_radians()),
+        None => None,
+    };
+
+    let object_name = match block.find("object") {
+        Some(statement) => Some(statement.string_arg()?),
+        None => None,
+    };
+
+    let key = (path, material_name.map(String::from), crease_angle.map(f32::to_bits), object_name.map(String::from));
+    let mesh = match meshes.get(&key) {
+        Some(mesh) => mesh.clone(),
+        None => {
+            let default_material = match block.find("material") {
+                Some(statement) => Some(lookup_material(statement, materials)?),
+                None => None,
+            };
+
+            let mut mesh = match &key.3 {
+                Some(_) if !is_obj(&key.0) =>
+                    return Err(block.find("object").unwrap().error("only OBJ files have objects to pick from")),
+                Some(name) => Mesh::from_obj_objects(&key.0, &IDENTITY, default_material)
+                    .map_err(|err| file.error(&err.to_string()))?
+                    .into_iter()
+                    .find(|(object_name, _)| object_name == name)
+                    .map(|(_, mesh)| mesh)
+                    .ok_or_else(|| block.find("object").unwrap().error(&format!("no object or group named \"{}\" in \"{}\"", name, key.0)))?,
+                None => Mesh::from_file(&key.0, &IDENTITY, default_material)
+                    .map_err(|err| file.error(&err.to_string()))?,
+            };
+            if let Some(crease_angle) = crease_angle {
+                mesh.generate_smooth_normals(crease_angle);
+            }
+
+            let mesh = Arc::new(ObjectEnum::Mesh(mesh));
+            meshes.insert(key, mesh.clone());
+            mesh
+        }
+    };
+
+    transformed(block, mesh, &block_transform(block)?.unwrap_or(IDENTITY))
+}
+
+/**
+ * Every mesh and light in a glTF file, moved together by the block's
+ * transform. The file's cameras aren't used, since the scene has its own.
+ */
+fn build_gltf(block: &Block, materials: &HashMap<String,Material>, base_dir: &Path) -> Result<Group,SceneError> {
+    check_object_properties(block, &["file", "material"])?;
+
+    let file = block.require("file")?;
+    let path = resolve_path(file, base_dir, file.string_arg()?)?;
+
+    let default_material = match block.find("material") {
+        Some(statement) => Some(lookup_material(statement, materials)?),
+        None => None,
+    };
+
+    let gltf = Gltf::load(&path, default_material)
+        .map_err(|err| file.error(&err.to_string()))?;
+
+    let mut group = match block_transform(block)? {
+        Some(transform) => Group::with_transform(&transform)
+            .ok_or_else(|| block.header.error("transform can't be inverted (is something scaled by 0?)"))?,
+        None => Group::new(),
+    };
+    for (_, mesh) in gltf.meshes {
+        group.push_object(ObjectEnum::Mesh(mesh), block.header.line);
+    }
+    for light in gltf.lights {
+        group.push_object(light, block.header.line);
+    }
+
+    Ok(group)
+}
+
+fn is_obj(path: &str) -> bool {
+    !matches!(
+        Path::new(path).extension().map(|extension| extension.to_string_lossy().to_lowercase()).as_deref(),
+        Some("ply") | Some("stl")
+    )
+}
+
+fn build_group(block: &Block) -> Result<Group,SceneError> {
+    block.header.expect_args(0)?;
+
+    for statement in &block.body {
+        if !is_transform(statement.keyword) {
+            return Err(block.unknown_property(statement));
+        }
+    }
+
+    match block_transform(block)? {
+        Some(transform) => Group::with_transform(&transform)
+            .ok_or_else(|| block.header.error("transform can't be inverted (is something scaled by 0?)")),
+        None => Ok(Group::new()),
+    }
+}
+
+/**
+ * Make sure an object block has no arguments, and only the given 
+ * properties (plus transforms).
+ */
+fn check_object_properties(block: &Block, properties: &[&str]) -> Result<(),SceneError> {
+    block.header.expect_args(0)?;
+
+    for statement in &block.body {
+        if !properties.contains(&statement.keyword) && !is_transform(statement.keyword) {
+            return Err(block.unknown_property(statement));
+        }
+    }
+
+    Ok(())
+}
+
+fn is_transform(keyword: &str) -> bool {
+    matches!(keyword, "translate" | "rotate_x" | "rotate_y" | "rotate_z" | "scale")
+}
+
+/**
+ * Combine an object block's transform properties, if it has any.
+ */
+fn block_transform(block: &Block) -> Result<Option<Matrix>,SceneError> {
+    let mut transform: Option<Matrix> = None;
+
+    for statement in &block.body {
+        let step = match statement.keyword {
+            "translate" => Matrix::translation(&statement.vec3_arg()?),
+            "rotate_x" => Matrix::rotation_x(statement.f32_arg()?.to_radians()),
+            "rotate_y" => Matrix::rotation_y(statement.f32_arg()?.to_radians()),
+            "rotate_z" => Matrix::rotation_z(statement.f32_arg()?.to_radians()),
+            "scale" => match statement.args.len() {
+                1 => Matrix::scale(&Vec3::from_scalar(statement.f32_arg()?)),
+                _ => Matrix::scale(&statement.vec3_arg()?),
+            },
+            _ => continue,
+        };
+
+        // each transform applies on top of the ones listed before it
+        transform = Some(transform.unwrap_or(IDENTITY).then(&step));
+    }
+
+    Ok(transform)
+}
+
+/**
+ * Wrap an object in its block's transform, if it has one.
+ */
+fn placed(block: &Block, object: ObjectEnum) -> Result<ObjectEnum,SceneError> {
+    match block_transform(block)? {
+        Some(transform) => transformed(block, Arc::new(object), &transform),
+        None => Ok(object),
+    }
+}
+
+fn transformed(block: &Block, object: Arc<ObjectEnum>, transform: &Matrix) -> Result<ObjectEnum,SceneError> {
+    Transformed::new(object, transform)
+        .map(ObjectEnum::Transformed)
+        .ok_or_else(|| block.header.error("transform can't be inverted (is something scaled by 0?)"))
+}
+
+/**
+ * A copy of a material defined earlier. Image textures are shared between
+ * the copies rather than copied themselves.
+ */
+fn lookup_material(statement: &Statement, materials: &HashMap<String,Material>) -> Result<Material,SceneError> {
+    let name = statement.string_arg()?;
+
+    materials.get(name)
+        .cloned()
+        .ok_or_else(|| statement.error(&format!("no material named \"{}\" has been defined", name)))
+}
+
+/**
+ * Resolve a file referenced by a scene relative to the scene's directory,
+ * making sure it exists so that a typo is reported with its line number.
+ */
+fn resolve_path(statement: &Statement, base_dir: &Path, file: &str) -> Result<String,SceneError> {
+    let path = base_dir.join(file);
+
+    if path.is_file() {
+        Ok(path.to_string_lossy().into_owned())
+    } else {
+        Err(statement.error(&format!("file \"{}\" does not exist", path.display())))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::object::{Object,ObjectEnum};
+    use crate::scene_parser::{parse,SceneError};
+
+    const SCENE: &str = "
+        # a comment
+        settings
+            resolution 320 200
+            samples 4
+            preview on
+
+        camera
+            position 0 1 0
+            target 0 0 -5
+            fov 90
+
+        newmtl white
+            albedo 1         # gray
+            specular 0.5 0.5 0.5
+
+        newmtl checkered
+            albedo checker
+
+        newmtl glass
+            specular 1
+            opacity 0.1
+            index_of_refraction 1.5
+
+        sphere
+            position 0 0 -5
+            radius 1
+            material white
+
+        sphere
+            position 0 0 0
+            radius 1
+            scale 2 1 1
+            translate 3 0 -5
+            material white
+
+        quad
+            position -1 2 -6
+            edge_u 2 0 0
+            edge_v 0 0 2
+            material white
+
+        torus
+            position 0 -1 -5
+            major_radius 2
+            minor_radius 0.5
+            rotate_x 90
+            material white
+
+        plane
+            position 0 -1 0
+            normal 0 1 0
+            material checkered
+    ";
+
+    #[test]
+    fn test_parse_scene() {
+        let scene = parse(SCENE, Path::new("")).unwrap();
+
+        assert_eq!(scene.settings.resolution_x, 320);
+        assert_eq!(scene.settings.resolution_y, 200);
+        assert_eq!(scene.settings.sample_count, 4);
+        assert_eq!(scene.settings.bounces, 2);
+        assert!(scene.settings.preview_mode);
+
+        assert_eq!(scene.camera.position.y, 1.0);
+        assert_eq!(scene.camera.fov, std::f32::consts::FRAC_PI_2);
+
+        assert_eq!(scene.objects.len(), 5);
+        assert!(matches!(scene.objects[0], ObjectEnum::Sphere(_)));
+        assert!(matches!(scene.objects[1], ObjectEnum::Transformed(_)));
+        assert!(matches!(scene.objects[2], ObjectEnum::Quad(_)));
+        assert!(matches!(scene.objects[3], ObjectEnum::Transformed(_)));
+        assert!(matches!(scene.objects[4], ObjectEnum::Plane(_)));
+    }
+
+    #[test]
+    fn test_parse_groups() {
+        let table = "
+            newmtl wood
+                albedo 0.5 0.3 0.1
+
+            group
+                translate 10 0 0
+
+                sphere              # the top
+                    position 0 1 0
+                    radius 1
+                    material wood
+
+                group               # the legs
+                    translate 0 -1 0
+
+                    sphere
+                        position 1 0 1
+                        radius 0.1
+                        material wood
+                    sphere
+                        position -1 0 1
+                        radius 0.1
+                        scale 2
+                        material wood
+                end
+            end
+
+            sphere
+                position 0 0 0
+                radius 1
+                material wood
+        ";
+
+        let scene = parse(table, Path::new("")).unwrap();
+        assert_eq!(scene.objects.len(), 4);
+
+        // the legs have both groups' transforms, on top of their own
+        let expected_centers = [(10.0, 1.0), (11.0, -1.0), (8.0, -1.0), (0.0, 0.0)];
+        for (object, (x, y)) in scene.objects.iter().zip(expected_centers.iter()) {
+            let center = object.bounds().unwrap().centroid();
+            assert!((center.x - x).abs() < 0.0001 && (center.y - y).abs() < 0.0001);
+        }
+        assert!(matches!(scene.objects[3], ObjectEnum::Sphere(_)));
+
+        let unclosed = "group\n  translate 0 1 0\ngroup\nend";
+        match parse(unclosed, Path::new("")) {
+            Err(SceneError::Parse { line, field, .. }) => {
+                assert_eq!(line, 1);
+                assert_eq!(field, "group");
+            },
+            _ => panic!("expected a parse error"),
+        }
+
+        let unopened = "group\nend\nend";
+        match parse(unopened, Path::new("")) {
+            Err(SceneError::Parse { line, field, .. }) => {
+                assert_eq!(line, 3);
+                assert_eq!(field, "end");
+            },
+            _ => panic!("expected a parse error"),
+        }
+
+        let camera_in_group = "group\ncamera\nend";
+        assert!(parse(camera_in_group, Path::new("")).is_err());
+    }
+
+    #[test]
+    fn test_parse_csg() {
+        let carved = "
+            newmtl white
+                albedo 1
+
+            difference
+                translate 0 0 -10
+
+                box
+                    min -1 -1 -1
+                    max 1 1 1
+                    material white
+                sphere
+                    position 0 0 1
+                    radius 0.5
+                    material white
+                sphere
+                    position 0 0 -1
+                    radius 0.5
+                    material white
+            end
+        ";
+
+        let scene = parse(carved, Path::new("")).unwrap();
+        assert_eq!(scene.objects.len(), 1);
+        match &scene.objects[0] {
+            ObjectEnum::Csg(csg) => {
+                let bounds = csg.bounds().unwrap();
+                assert!((bounds.centroid().z + 10.0).abs() < 0.0001);
+            },
+            _ => panic!("expected a CSG object"),
+        }
+
+        let lonely = "newmtl white\n  albedo 1\nunion\n  sphere\n    position 0 0 0\n    radius 1\n    material white\nend";
+        match parse(lonely, Path::new("")) {
+            Err(SceneError::Parse { line, field, .. }) => {
+                assert_eq!(line, 3);
+                assert_eq!(field, "union");
+            },
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn test_parse_sdf() {
+        let blob = "
+            newmtl white
+                albedo 1
+
+            sdf
+                shape smooth_union 0.5 sphere 1 translate 2 0 0 twist 0.5 box 0.5 1 0.5
+                translate 0 0 -10
+                material white
+        ";
+
+        let scene = parse(blob, Path::new("")).unwrap();
+        let bounds = scene.objects[0].bounds().unwrap();
+        assert!(bounds.min.x < -1.0 && bounds.max.x > 2.5);
+
+        let unfinished = "newmtl white\n  albedo 1\nsdf\n  shape union sphere 1\n  material white";
+        match parse(unfinished, Path::new("")) {
+            Err(SceneError::Parse { line, field, .. }) => {
+                assert_eq!(line, 4);
+                assert_eq!(field, "shape");
+            },
+            _ => panic!("expected a parse error"),
+        }
+
+        for iterations in &["-3", "2.7", "0"] {
+            let bad_count = format!("newmtl white\n  albedo 1\nsdf\n  shape mandelbulb 8 {}\n  material white", iterations);
+            match parse(&bad_count, Path::new("")) {
+                Err(err @ SceneError::Parse { .. }) => assert!(err.to_string().contains(iterations)),
+                _ => panic!("expected a parse error"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let bad_number = "sphere\n  position 0 0 -5\n  radius big\n  material white";
+        match parse(bad_number, Path::new("")) {
+            Err(SceneError::Parse { line, field, .. }) => {
+                assert_eq!(line, 3);
+                assert_eq!(field, "radius");
+            },
+            _ => panic!("expected a parse error"),
+        }
+
+        let missing_material = "newmtl white\n  albedo 1\nsphere\n  position 0 0 -5\n  radius 1\n  material black";
+        match parse(missing_material, Path::new("")) {
+            Err(SceneError::Parse { line, field, .. }) => {
+                assert_eq!(line, 6);
+                assert_eq!(field, "material");
+            },
+            _ => panic!("expected a parse error"),
+        }
+
+        let flattened = "newmtl white\n  albedo 1\nsphere\n  position 0 0 -5\n  radius 1\n  scale 1 0 1\n  material white";
+        match parse(flattened, Path::new("")) {
+            Err(SceneError::Parse { line, field, .. }) => {
+                assert_eq!(line, 3);
+                assert_eq!(field, "sphere");
+            },
+            _ => panic!("expected a parse error"),
+        }
+
+        let missing_radius = "newmtl white\n  albedo 1\nsphere\n  position 0 0 -5\n  material white";
+        match parse(missing_radius, Path::new("")) {
+            Err(SceneError::Parse { line, field, .. }) => {
+                assert_eq!(line, 3);
+                assert_eq!(field, "radius");
+            },
+            _ => panic!("expected a parse error"),
+        }
+
+        let shrunk = "newmtl white\n  albedo 1\ngroup\n  scale 1e-13\nsphere\n  position 0 0 0\n  radius 1\n  scale 1e-13\n  material white\nend";
+        match parse(shrunk, Path::new("")) {
+            Err(SceneError::Parse { line, field, .. }) => {
+                assert_eq!(line, 5);
+                assert_eq!(field, "sphere");
+            },
+            _ => panic!("expected a parse error"),
+        }
+
+        let flipped_camera = "camera\n  position 0 0 0\n  aspect -1.5";
+        match parse(flipped_camera, Path::new("")) {
+            Err(SceneError::Parse { line, field, .. }) => {
+                assert_eq!(line, 3);
+                assert_eq!(field, "aspect");
+            },
+            _ => panic!("expected a parse error"),
+        }
+
+        let flat_camera = "camera\n  projection orthographic 0";
+        match parse(flat_camera, Path::new("")) {
+            Err(SceneError::Parse { line, field, .. }) => {
+                assert_eq!(line, 2);
+                assert_eq!(field, "projection");
+            },
+            _ => panic!("expected a parse error"),
+        }
+    }
+}
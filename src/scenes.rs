@@ -8,8 +8,9 @@ use crate::plane::Plane;
 use crate::texture::Texture;
 use crate::matrix::Matrix;
 use crate::object::ObjectEnum;
+use crate::load_error::LoadError;
 
-pub fn construct_reflect_scene() -> Vec<ObjectEnum> {
+pub fn construct_reflect_scene() -> Result<Vec<ObjectEnum>,LoadError> {
     let mut objs: Vec<ObjectEnum> = Vec::new();
 
     objs.push(ObjectEnum::Sphere(Sphere::new(
@@ -20,7 +21,9 @@ pub fn construct_reflect_scene() -> Vec<ObjectEnum> {
             texture_specular: None,//Some(Texture::Solid(Color::gray(1.0))),
             texture_normal: None,
             texture_emission_color: None,
-            texture_emission_intensity: Some(Texture::Solid(Color::gray(10.0)))
+            texture_emission_intensity: Some(Texture::Solid(Color::gray(10.0))),
+            texture_opacity: None,
+            index_of_refraction: None
         }
     )));
 
@@ -31,16 +34,18 @@ pub fn construct_reflect_scene() -> Vec<ObjectEnum> {
         Material {
             texture_albedo: Some(Texture::Solid(Color::gray(1.0))),
             texture_specular: None,
-            texture_normal: Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg")),
+            texture_normal: Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg")?),
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
-    return objs;
+    Ok(objs)
 }
 
-pub fn construct_material_scene() -> Vec<ObjectEnum> {
+pub fn construct_material_scene() -> Result<Vec<ObjectEnum>,LoadError> {
     let mut objs: Vec<ObjectEnum> = Vec::new();
 
     objs.push(ObjectEnum::Sphere(Sphere::new(
@@ -51,7 +56,9 @@ pub fn construct_material_scene() -> Vec<ObjectEnum> {
             texture_specular: None,//Some(Texture::Solid(Color::gray(1.0))),
             texture_normal: None,
             texture_emission_color: Some(Texture::Solid(Color(1.0,0.0,0.0))),
-            texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0)))
+            texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None
         }
     )));
 
@@ -66,6 +73,8 @@ pub fn construct_material_scene() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -75,17 +84,19 @@ pub fn construct_material_scene() -> Vec<ObjectEnum> {
         Vec3 { x: 0.0, y: 0.0, z: -1.0 },
         Material {
             texture_albedo: None,//Some(Texture::Solid(Color::gray(1.0))),
-            texture_specular: Some(Texture::from_image("/Users/brundolf/git/raytracer/CobblestoneSpecular.jpg")),
+            texture_specular: Some(Texture::from_image("/Users/brundolf/git/raytracer/CobblestoneSpecular.jpg")?),
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
-    return objs;
+    Ok(objs)
 }
 
-pub fn construct_tree_scene() -> Vec<ObjectEnum> {
+pub fn construct_tree_scene() -> Result<Vec<ObjectEnum>,LoadError> {
     let mut objs: Vec<ObjectEnum> = Vec::new();
 
     objs.push(ObjectEnum::Mesh(Mesh::from_obj(
@@ -93,7 +104,7 @@ pub fn construct_tree_scene() -> Vec<ObjectEnum> {
         &(Matrix::translation(&Vec3 { x: 0.0, y: 0.0, z: -3.0 })
         * Matrix::rotation_y(std::f32::consts::PI / -4.0)),
         None
-    )));
+    )?));
 
     objs.push(ObjectEnum::Plane(Plane::new(
         Vec3 { x: 2.0, y: 0.0, z: 2.0, },
@@ -104,14 +115,16 @@ pub fn construct_tree_scene() -> Vec<ObjectEnum> {
             texture_specular: None,
             texture_normal: None,
             texture_emission_color: Some(Texture::Solid(Color(1.0, 0.52, 0.17))),
-            texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0)))
+            texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None
         }
     )));
 
-    return objs;
+    Ok(objs)
 }
 
-pub fn construct_room_scene() -> Vec<ObjectEnum> {
+pub fn construct_room_scene() -> Result<Vec<ObjectEnum>,LoadError> {
     let mut objs: Vec<ObjectEnum> = Vec::new();
 
     // spheres
@@ -121,9 +134,11 @@ pub fn construct_room_scene() -> Vec<ObjectEnum> {
         Material {
             texture_albedo: Some(Texture::Solid(Color::gray(1.0))),
             texture_specular: None,//Some(Texture::Solid(Color::gray(1.0))),
-            texture_normal: Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg")),
+            texture_normal: Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg")?),
             texture_emission_color: Some(Texture::Solid(Color(0.0, 1.0, 1.0))),
-            texture_emission_intensity: Some(Texture::Solid(Color::gray(5.0)))
+            texture_emission_intensity: Some(Texture::Solid(Color::gray(5.0))),
+            texture_opacity: None,
+            index_of_refraction: None
         }
     )));
 
@@ -133,9 +148,11 @@ pub fn construct_room_scene() -> Vec<ObjectEnum> {
         Material {
             texture_albedo: None,//Some(Texture::Solid(Color::gray(1.0))),
             texture_specular: Some(Texture::Solid(Color::gray(1.0))),
-            texture_normal: None,//Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg")),
+            texture_normal: None,//Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg").unwrap()),
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -146,7 +163,7 @@ pub fn construct_room_scene() -> Vec<ObjectEnum> {
         * Matrix::rotation_y(std::f32::consts::PI)
         * Matrix::scale(&Vec3::from_scalar(0.5))),
         None
-    )));*/
+    ).unwrap()));*/
     
     // ceiling
     objs.push(ObjectEnum::Plane(Plane::new(
@@ -159,6 +176,8 @@ pub fn construct_room_scene() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,//Some(Texture::Solid(Color(1.0, 0.95, 0.8))),
             texture_emission_intensity: None,//Some(Texture::Solid(Color(1.0, 0.95, 0.8))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -170,9 +189,11 @@ pub fn construct_room_scene() -> Vec<ObjectEnum> {
         Material {
             texture_albedo: Some(Texture::Solid(Color::gray(1.0))),
             texture_specular: None,
-            texture_normal: Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg")),
+            texture_normal: Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg")?),
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -185,9 +206,11 @@ pub fn construct_room_scene() -> Vec<ObjectEnum> {
         Material {
             texture_albedo: Some(Texture::Solid(Color(1.0,0.0,0.0))),
             texture_specular: None,
-            texture_normal: Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg")),
+            texture_normal: Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg")?),
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -199,9 +222,11 @@ pub fn construct_room_scene() -> Vec<ObjectEnum> {
         Material {
             texture_albedo: Some(Texture::Solid(Color(0.0, 1.0, 0.0))),
             texture_specular: None,//Some(Texture::Solid(Color::gray(1.0))),
-            texture_normal: Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg")),
+            texture_normal: Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg")?),
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -213,9 +238,11 @@ pub fn construct_room_scene() -> Vec<ObjectEnum> {
         Material {
             texture_albedo: Some(Texture::Solid(Color::gray(1.0))),
             texture_specular: None,
-            texture_normal: None,//Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg")),
+            texture_normal: None,//Some(Texture::from_image("/Users/brundolf/git/raytracer/cobblestone_normal.jpg").unwrap()),
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -230,10 +257,12 @@ pub fn construct_room_scene() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
     
-    return objs;
+    Ok(objs)
 }
 
 
@@ -255,7 +284,7 @@ pub fn construct_room_scene() -> Vec<ObjectEnum> {
     }*/
 
 
-pub fn construct_plane_texture_test() -> Vec<ObjectEnum> {
+pub fn construct_plane_texture_test() -> Result<Vec<ObjectEnum>,LoadError> {
     let mut objs: Vec<ObjectEnum> = Vec::new();
 
     objs.push(ObjectEnum::Plane(Plane::new(
@@ -268,6 +297,8 @@ pub fn construct_plane_texture_test() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -276,29 +307,33 @@ pub fn construct_plane_texture_test() -> Vec<ObjectEnum> {
         Vec3 { x: 0.0, y: 1.0, z: 0.0 },
         Vec3 { x: 0.0, y: 0.0, z: -1.0 },
         Material {
-            texture_albedo: Some(Texture::from_image("/Users/brundolf/git/raytracer/grid.jpg")),
+            texture_albedo: Some(Texture::from_image("/Users/brundolf/git/raytracer/grid.jpg")?),
             texture_specular: None,
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
-    return objs;
+    Ok(objs)
 }
 
-pub fn construct_sphere_texture_test() -> Vec<ObjectEnum> {
+pub fn construct_sphere_texture_test() -> Result<Vec<ObjectEnum>,LoadError> {
     let mut objs: Vec<ObjectEnum> = Vec::new();
     
     objs.push(ObjectEnum::Sphere(Sphere::new(
         Vec3 { x: 0.0, y: 0.0, z: -5.0 },
         1.0,
         Material {
-            texture_albedo: Some(Texture::from_image("C:\\Users\\Brundon\\git\\raytracer\\texture.jpg")),
+            texture_albedo: Some(Texture::from_image("C:\\Users\\Brundon\\git\\raytracer\\texture.jpg")?),
             texture_specular: None,//Some(Texture::Solid(Color::gray(1.0))),
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -314,6 +349,8 @@ pub fn construct_sphere_texture_test() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
     
@@ -329,6 +366,8 @@ pub fn construct_sphere_texture_test() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -344,6 +383,8 @@ pub fn construct_sphere_texture_test() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -358,6 +399,8 @@ pub fn construct_sphere_texture_test() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -372,6 +415,8 @@ pub fn construct_sphere_texture_test() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -386,13 +431,15 @@ pub fn construct_sphere_texture_test() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
     
-    return objs;
+    Ok(objs)
 }
 
-pub fn construct_wallpaper_scene() -> Vec<ObjectEnum> {
+pub fn construct_wallpaper_scene() -> Result<Vec<ObjectEnum>,LoadError> {
     let mut objs: Vec<ObjectEnum> = Vec::new();
 
     // floor
@@ -406,6 +453,8 @@ pub fn construct_wallpaper_scene() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -423,16 +472,18 @@ pub fn construct_wallpaper_scene() -> Vec<ObjectEnum> {
                     texture_specular: None,
                     texture_normal: None,
                     texture_emission_color: Some(Texture::Solid(Color(0.0, z_inc as f32 / 4.0, x_inc as f32 / 8.0))),
-                    texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0)))
+                    texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+                    texture_opacity: None,
+                    index_of_refraction: None
                 }
             )));
         }
     }
 
-    return objs;
+    Ok(objs)
 }
 
-pub fn construct_wallpaper_scene_2() -> Vec<ObjectEnum> {
+pub fn construct_wallpaper_scene_2() -> Result<Vec<ObjectEnum>,LoadError> {
     let mut objs: Vec<ObjectEnum> = Vec::new();
 
     objs.push(ObjectEnum::Mesh(Mesh::from_obj(
@@ -449,8 +500,10 @@ pub fn construct_wallpaper_scene_2() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         })
-    )));
+    )?));
 
     for a in 0..8 {
         for b in 0..4 {
@@ -472,16 +525,18 @@ pub fn construct_wallpaper_scene_2() -> Vec<ObjectEnum> {
                     texture_specular: None,
                     texture_normal: None,
                     texture_emission_color: Some(Texture::Solid(Color(1.0, 0.0, 0.0))),
-                    texture_emission_intensity: Some(Texture::Solid(Color::gray(5.0)))
+                    texture_emission_intensity: Some(Texture::Solid(Color::gray(5.0))),
+                    texture_opacity: None,
+                    index_of_refraction: None
                 }
             )));
         }
     }
 
-    return objs;
+    Ok(objs)
 }
 
-pub fn construct_wallpaper_scene_3() -> Vec<ObjectEnum> {
+pub fn construct_wallpaper_scene_3() -> Result<Vec<ObjectEnum>,LoadError> {
     let mut objs: Vec<ObjectEnum> = Vec::new();
 
     // sky
@@ -495,6 +550,8 @@ pub fn construct_wallpaper_scene_3() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: Some(Texture::Solid(Color(0.8470588235294118, 0.9529411764705882, 1.0))),
             texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));*/
 
@@ -509,6 +566,8 @@ pub fn construct_wallpaper_scene_3() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: Some(Texture::Solid(Color(1.0, 0.95, 0.8))),
             texture_emission_intensity: Some(Texture::Solid(Color::gray(10.0))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));*/
 
@@ -525,6 +584,8 @@ pub fn construct_wallpaper_scene_3() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: Some(Texture::Solid(Color(0.8470588235294118, 0.9529411764705882, 1.0))),
             texture_emission_intensity: Some(Texture::Solid(Color::gray(1.0))),
+            texture_opacity: None,
+            index_of_refraction: None,
         }
     )));
 
@@ -538,10 +599,12 @@ pub fn construct_wallpaper_scene_3() -> Vec<ObjectEnum> {
             texture_normal: None,
             texture_emission_color: None,
             texture_emission_intensity: None,
+            texture_opacity: None,
+            index_of_refraction: None,
         })
-    )));
+    )?));
 
-    return objs;
+    Ok(objs)
 }
 
 const COUNT_X: usize = 8;
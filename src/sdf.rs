@@ -0,0 +1,484 @@
+use std::f32::consts::PI;
+
+use rand::rngs::SmallRng;
+
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::object::Object;
+use crate::intersection::{Intersection,Interval};
+use crate::material::Material;
+use crate::illumination::Illumination;
+use crate::render_settings::RenderSettings;
+use crate::bounding_box::{BoundingBox,inverse_direction};
+use crate::utils::{TWO_PI,clamp,lerp};
+use crate::world::World;
+
+// a point closer to the surface than this counts as on it
+const HIT_EPSILON: f32 = 0.0001;
+
+// the smallest step taken, so that marching along right next to a
+// surface still makes progress
+const MIN_STEP: f32 = 0.001;
+
+const NORMAL_EPSILON: f32 = 0.0001;
+const MAX_STEPS: usize = 512;
+
+// how far to march when there are no bounds to stop at
+const MAX_DISTANCE: f32 = 1000.0;
+
+// room around the bounds, so that marching starts and ends off the surface
+const BOUNDS_MARGIN: f32 = 0.01;
+
+/**
+ * A signed distance function: for any point, how far it is from the
+ * nearest surface (negative inside). All shapes are centered on the
+ * origin; use `Translate` to move them within a combination, or wrap the
+ * whole object in a `Transformed`.
+ */
+pub enum Sdf {
+    Sphere { radius: f32 },
+    Box { half_size: Vec3 },
+
+    // lying flat, around the Y axis
+    Torus { major_radius: f32, minor_radius: f32 },
+
+    // standing along the Y axis
+    Cylinder { radius: f32, height: f32 },
+
+    // the bulb fits within a radius of about 1.2
+    Mandelbulb { power: f32, iterations: usize },
+
+    Translate { offset: Vec3, sdf: Box<Sdf> },
+
+    // Copies of a shape every `period` along each axis (0 means no
+    // copies along that axis). The shape should fit within one period.
+    Repeat { period: Vec3, sdf: Box<Sdf> },
+
+    // Rotates each horizontal slice by `rate` radians per unit of height
+    Twist { rate: f32, sdf: Box<Sdf> },
+
+    Union(Box<Sdf>, Box<Sdf>),
+
+    // like a union, but blended together over about `smoothness` units
+    SmoothUnion { smoothness: f32, a: Box<Sdf>, b: Box<Sdf> },
+}
+
+impl Sdf {
+
+    pub fn distance(&self, point: &Vec3) -> f32 {
+        match self {
+            Sdf::Sphere { radius } => point.len() - radius,
+            Sdf::Box { half_size } => {
+                let q = Vec3 {
+                    x: point.x.abs() - half_size.x,
+                    y: point.y.abs() - half_size.y,
+                    z: point.z.abs() - half_size.z,
+                };
+                let outside = Vec3 { x: q.x.max(0.0), y: q.y.max(0.0), z: q.z.max(0.0) };
+
+                outside.len() + f32::min(q.x.max(q.y).max(q.z), 0.0)
+            },
+            Sdf::Torus { major_radius, minor_radius } => {
+                let from_ring = (point.x * point.x + point.z * point.z).sqrt() - major_radius;
+
+                (from_ring * from_ring + point.y * point.y).sqrt() - minor_radius
+            },
+            Sdf::Cylinder { radius, height } => {
+                let radial = (point.x * point.x + point.z * point.z).sqrt() - radius;
+                let vertical = point.y.abs() - height / 2.0;
+                let outside = (radial.max(0.0) * radial.max(0.0) + vertical.max(0.0) * vertical.max(0.0)).sqrt();
+
+                outside + f32::min(radial.max(vertical), 0.0)
+            },
+            Sdf::Mandelbulb { power, iterations } => mandelbulb(point, *power, *iterations),
+            Sdf::Translate { offset, sdf } => sdf.distance(&(point - offset)),
+            Sdf::Repeat { period, sdf } => sdf.distance(&Vec3 {
+                x: repeated(point.x, period.x),
+                y: repeated(point.y, period.y),
+                z: repeated(point.z, period.z),
+            }),
+            Sdf::Twist { rate, sdf } => {
+                let (sin, cos) = (-rate * point.y).sin_cos();
+
+                sdf.distance(&Vec3 {
+                    x: cos * point.x - sin * point.z,
+                    y: point.y,
+                    z: sin * point.x + cos * point.z,
+                })
+            },
+            Sdf::Union(a, b) => f32::min(a.distance(point), b.distance(point)),
+            Sdf::SmoothUnion { smoothness, a, b } => {
+                let a = a.distance(point);
+                let b = b.distance(point);
+                let h = clamp(0.5 + 0.5 * (b - a) / smoothness, 0.0, 1.0);
+
+                lerp(b, a, h) - smoothness * h * (1.0 - h)
+            },
+        }
+    }
+
+    /**
+     * A box containing the whole surface, if it's finite
+     */
+    pub fn bounds(&self) -> Option<BoundingBox> {
+        let centered = |extent: Vec3| BoundingBox {
+            min: &extent * -1.0,
+            max: extent,
+        };
+
+        match self {
+            Sdf::Sphere { radius } => Some(centered(Vec3::from_scalar(*radius))),
+            Sdf::Box { half_size } => Some(centered(*half_size)),
+            Sdf::Torus { major_radius, minor_radius } => Some(centered(Vec3 {
+                x: major_radius + minor_radius,
+                y: *minor_radius,
+                z: major_radius + minor_radius,
+            })),
+            Sdf::Cylinder { radius, height } => Some(centered(Vec3 { x: *radius, y: height / 2.0, z: *radius })),
+            Sdf::Mandelbulb { .. } => Some(centered(Vec3::from_scalar(1.25))),
+            Sdf::Translate { offset, sdf } => sdf.bounds().map(|bounds| BoundingBox {
+                min: &bounds.min + offset,
+                max: &bounds.max + offset,
+            }),
+            Sdf::Repeat { .. } => None,
+            Sdf::Twist { sdf, .. } => sdf.bounds().map(|bounds| {
+                let radius = horizontal_radius(&bounds);
+
+                BoundingBox {
+                    min: Vec3 { x: -radius, y: bounds.min.y, z: -radius },
+                    max: Vec3 { x: radius, y: bounds.max.y, z: radius },
+                }
+            }),
+            Sdf::Union(a, b) => Some(a.bounds()?.union(&b.bounds()?)),
+            Sdf::SmoothUnion { smoothness, a, b } => {
+                let bounds = a.bounds()?.union(&b.bounds()?);
+
+                // blending can only fill in by a quarter of the smoothness
+                let extent = Vec3::from_scalar(smoothness / 4.0);
+                Some(BoundingBox {
+                    min: &bounds.min - &extent,
+                    max: &bounds.max + &extent,
+                })
+            },
+        }
+    }
+
+    /**
+     * How much of each distance it's safe to step along a ray. Twisting
+     * stretches space, so its distances can overestimate.
+     */
+    pub fn step_scale(&self) -> f32 {
+        match self {
+            Sdf::Twist { rate, sdf } => {
+                let radius = sdf.bounds().map(|bounds| horizontal_radius(&bounds)).unwrap_or(1.0);
+
+                sdf.step_scale() / (1.0 + rate * rate * radius * radius).sqrt()
+            },
+            Sdf::Translate { sdf, .. } | Sdf::Repeat { sdf, .. } => sdf.step_scale(),
+            Sdf::Union(a, b) | Sdf::SmoothUnion { a, b, .. } => f32::min(a.step_scale(), b.step_scale()),
+            _ => 1.0,
+        }
+    }
+}
+
+/**
+ * The distance estimate for a power-`power` Mandelbulb
+ */
+fn mandelbulb(point: &Vec3, power: f32, iterations: usize) -> f32 {
+    let mut z = *point;
+    let mut derivative = 1.0;
+    let mut radius = z.len();
+
+    for _ in 0..iterations {
+        // escaped, or stuck at the center
+        if !(1e-6..=2.0).contains(&radius) {
+            break;
+        }
+
+        // z = z^power + point, in spherical coordinates
+        let theta = (z.z / radius).acos() * power;
+        let phi = z.y.atan2(z.x) * power;
+        derivative = radius.powf(power - 1.0) * power * derivative + 1.0;
+
+        let scaled = radius.powf(power);
+        z = &Vec3 {
+            x: theta.sin() * phi.cos(),
+            y: theta.sin() * phi.sin(),
+            z: theta.cos(),
+        } * scaled;
+        z = &z + point;
+        radius = z.len();
+    }
+
+    if radius < 1e-6 {
+        return 0.0;
+    }
+
+    0.5 * radius.ln() * radius / derivative
+}
+
+fn repeated(value: f32, period: f32) -> f32 {
+    if period > 0.0 {
+        value - period * (value / period).round()
+    } else {
+        value
+    }
+}
+
+/**
+ * How far from the Y axis a box reaches, at most
+ */
+fn horizontal_radius(bounds: &BoundingBox) -> f32 {
+    let x = f32::max(bounds.min.x.abs(), bounds.max.x.abs());
+    let z = f32::max(bounds.min.z.abs(), bounds.max.z.abs());
+
+    (x * x + z * z).sqrt()
+}
+
+/**
+ * An object whose surface is wherever a signed distance function is 0,
+ * found by sphere tracing: stepping along the ray by the distance to the
+ * nearest surface, which can never step through one. Textured with a
+ * spherical projection around its origin.
+ */
+pub struct DistanceField {
+    sdf: Sdf,
+    material: Material,
+
+    bounds: Option<BoundingBox>,
+    step_scale: f32,
+}
+
+impl DistanceField {
+
+    pub fn new(sdf: Sdf, material: Material) -> Self {
+        let margin = Vec3::from_scalar(BOUNDS_MARGIN);
+
+        Self {
+            bounds: sdf.bounds().map(|bounds| BoundingBox {
+                min: &bounds.min - &margin,
+                max: &bounds.max + &margin,
+            }),
+            step_scale: sdf.step_scale(),
+            sdf,
+            material,
+        }
+    }
+
+    pub fn sdf(&self) -> &Sdf {
+        &self.sdf
+    }
+
+    /**
+     * The stretch of the ray worth marching along (in units of distance
+     * along the normalized direction)
+     */
+    fn span(&self, ray: &Ray, length: f32) -> Option<(f32,f32)> {
+        match &self.bounds {
+            Some(bounds) => bounds.ray_span(ray, &inverse_direction(ray))
+                .map(|(near, far)| (near * length, far * length)),
+            None => Some((0.0, MAX_DISTANCE)),
+        }
+    }
+
+    fn normal_at(&self, point: &Vec3) -> Vec3 {
+        let offset = |x: f32, y: f32, z: f32| self.sdf.distance(&(point + &Vec3 { x, y, z }));
+
+        Vec3 {
+            x: offset(NORMAL_EPSILON, 0.0, 0.0) - offset(-NORMAL_EPSILON, 0.0, 0.0),
+            y: offset(0.0, NORMAL_EPSILON, 0.0) - offset(0.0, -NORMAL_EPSILON, 0.0),
+            z: offset(0.0, 0.0, NORMAL_EPSILON) - offset(0.0, 0.0, -NORMAL_EPSILON),
+        }.normalized()
+    }
+
+    /**
+     * `distance` is along the normalized direction, and converted back to
+     * the ray's own units for the hit record
+     */
+    fn hit_at(&self, ray: &Ray, direction: &Vec3, length: f32, distance: f32) -> Intersection {
+        let position = &ray.origin + &(direction * distance);
+        let normal = self.normal_at(&position);
+
+        let mut intersection = Intersection::new(
+            distance / length,
+            &position + &(&normal * 0.001), // offset to avoid floating-point error
+            normal,
+            ray.direction,
+        );
+        intersection.uv = self.texture_coordinate(&position);
+
+        intersection
+    }
+}
+
+impl Object for DistanceField {
+
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        let length = ray.direction.len();
+        let direction = &ray.direction * (1.0 / length);
+        let (start, end) = self.span(ray, length)?;
+
+        let mut distance = f32::max(start, 0.0);
+        for _ in 0..MAX_STEPS {
+            if distance > end {
+                return None;
+            }
+
+            // from inside, this marches out to the surface instead
+            let to_surface = self.sdf.distance(&(&ray.origin + &(&direction * distance))).abs();
+            if to_surface < HIT_EPSILON {
+                return Some(self.hit_at(ray, &direction, length, distance));
+            }
+
+            distance += to_surface * self.step_scale;
+        }
+
+        None
+    }
+
+    /**
+     * Only bounded fields have intervals, since an unbounded one (like a
+     * repetition) could go on being entered and left forever
+     */
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let mut intervals = Vec::new();
+        if self.bounds.is_none() {
+            return intervals;
+        }
+
+        let length = ray.direction.len();
+        let direction = &ray.direction * (1.0 / length);
+        let (start, end) = match self.span(ray, length) {
+            Some(span) => span,
+            None => return intervals,
+        };
+        let distance_at = |distance: f32| self.sdf.distance(&(&ray.origin + &(&direction * distance)));
+
+        let mut entry: Option<Intersection> = None;
+        let mut previous = start;
+        let mut previous_inside = distance_at(start) < 0.0;
+        let mut distance = start;
+
+        for _ in 0..MAX_STEPS {
+            let value = distance_at(distance);
+            let inside = value < 0.0;
+
+            if inside != previous_inside {
+                // narrow down where the sign changed
+                let (mut low, mut high) = (previous, distance);
+                for _ in 0..16 {
+                    let middle = (low + high) / 2.0;
+                    if (distance_at(middle) < 0.0) == previous_inside {
+                        low = middle;
+                    } else {
+                        high = middle;
+                    }
+                }
+
+                let crossing = self.hit_at(ray, &direction, length, (low + high) / 2.0);
+                if inside {
+                    entry = Some(crossing);
+                } else {
+                    intervals.push(match entry.take() {
+                        Some(entry) => Interval::new(entry, crossing),
+                        None => Interval::ending_at(crossing),
+                    });
+                }
+            }
+
+            if distance >= end {
+                break;
+            }
+
+            previous = distance;
+            previous_inside = inside;
+            distance = f32::min(distance + f32::max(value.abs() * self.step_scale, MIN_STEP), end);
+        }
+
+        intervals
+    }
+
+    fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
+        let len = point.len();
+        if len == 0.0 {
+            return (0.0, 0.0);
+        }
+
+        (
+            (point.z.atan2(point.x) + PI) / TWO_PI,
+            1.0 - (point.y / len).acos() / PI,
+        )
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        self.bounds
+    }
+
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+        self.material.shade(
+            intersection,
+            world,
+            settings,
+            rng,
+            bounces_remaining
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Vec3;
+    use crate::ray::Ray;
+    use crate::material::Material;
+    use crate::object::Object;
+    use crate::sdf::{Sdf,DistanceField};
+
+    #[test]
+    fn test_distances() {
+        let point = Vec3 { x: 3.0, y: 0.0, z: 0.0 };
+
+        assert!((Sdf::Sphere { radius: 1.0 }.distance(&point) - 2.0).abs() < 0.0001);
+        assert!((Sdf::Box { half_size: Vec3::from_scalar(1.0) }.distance(&point) - 2.0).abs() < 0.0001);
+        assert!((Sdf::Torus { major_radius: 2.0, minor_radius: 0.5 }.distance(&point) - 0.5).abs() < 0.0001);
+
+        let repeated = Sdf::Repeat { period: Vec3 { x: 4.0, y: 0.0, z: 0.0 }, sdf: Box::new(Sdf::Sphere { radius: 1.0 }) };
+        assert!((repeated.distance(&point)).abs() < 0.0001);
+        assert!(repeated.bounds().is_none());
+
+        // a smooth union is never farther than a plain one
+        let a = || Box::new(Sdf::Sphere { radius: 1.0 });
+        let b = || Box::new(Sdf::Translate { offset: Vec3 { x: 1.5, y: 0.0, z: 0.0 }, sdf: Box::new(Sdf::Sphere { radius: 1.0 }) });
+        let between = Vec3 { x: 0.75, y: 1.0, z: 0.0 };
+        let smooth = Sdf::SmoothUnion { smoothness: 0.5, a: a(), b: b() }.distance(&between);
+        assert!(smooth < Sdf::Union(a(), b()).distance(&between));
+
+        // the origin is inside of the bulb
+        assert!(Sdf::Mandelbulb { power: 8.0, iterations: 8 }.distance(&Vec3::new()) <= 0.0);
+    }
+
+    #[test]
+    fn test_sphere_tracing() {
+        let field = DistanceField::new(
+            Sdf::Translate { offset: Vec3 { x: 0.0, y: 0.0, z: -5.0 }, sdf: Box::new(Sdf::Sphere { radius: 1.0 }) },
+            Material::new(),
+        );
+        let ray = Ray {
+            origin: Vec3::new(),
+            direction: Vec3 { x: 0.0, y: 0.0, z: -2.0 }, // not normalized
+        };
+
+        let hit = field.intersection(&ray).unwrap();
+        assert!((hit.distance - 2.0).abs() < 0.001);
+        assert!((&hit.normal - &Vec3 { x: 0.0, y: 0.0, z: 1.0 }).len() < 0.001);
+
+        let intervals = field.intervals(&ray);
+        assert_eq!(intervals.len(), 1);
+        assert!((intervals[0].entry.distance - 2.0).abs() < 0.001);
+        assert!((intervals[0].exit.distance - 3.0).abs() < 0.001);
+
+        assert!(field.intersection(&Ray {
+            origin: Vec3 { x: 2.0, y: 0.0, z: 0.0 },
+            direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+        }).is_none());
+    }
+}
@@ -4,11 +4,14 @@ use std::f32::consts::PI;
 
 use crate::vec3::Vec3;
 use crate::ray::Ray;
-use crate::object::{Object,ObjectEnum};
-use crate::intersection::Intersection;
+use crate::object::Object;
+use crate::world::World;
+use crate::intersection::{Intersection,Interval};
 use crate::material::Material;
 use crate::illumination::Illumination;
-use crate::utils::{TWO_PI,color_to_normal,adjusted_for_normal};
+use crate::render_settings::RenderSettings;
+use crate::bounding_box::BoundingBox;
+use crate::utils::{TWO_PI,solve_quadratic};
 
 pub struct Sphere {
     position: Vec3,
@@ -28,6 +31,36 @@ impl Sphere {
         }
     }
 
+    /**
+     * Distances along the ray to where it enters and leaves the sphere
+     */
+    fn roots(&self, ray: &Ray) -> Option<(f32,f32)> {
+
+        // analytic solution
+        let l: Vec3 = &ray.origin - &self.position;
+        let a: f32 = ray.direction.dot(&ray.direction);
+        let b: f32 = 2.0 * ray.direction.dot(&l);
+        let c: f32 = l.dot(&l) - self.radius_squared;
+
+        solve_quadratic(a, b, c)
+    }
+
+    fn hit_at(&self, ray: &Ray, distance: f32) -> Intersection {
+        let position = &ray.origin + &(&ray.direction * distance);
+        let mut normal = &position - &self.position;
+        normal.normalize();
+
+        let mut intersection = Intersection::new(
+            distance,
+            &position + &(&normal * 0.001), // offset to avoid floating-point error
+            normal,
+            ray.direction,
+        );
+        intersection.uv = self.texture_coordinate(&position);
+
+        intersection
+    }
+
     /*
     pub fn contains(&self, vec: &Vec3) -> bool {
         (vec - &self.position).len_squared() < self.radius_squared
@@ -52,14 +85,7 @@ impl Object for Sphere {
 
 //    #[flame("Sphere")]
     fn intersection(&self, ray: &Ray) -> Option<Intersection> {
-        
-        // analytic solution
-        let l: Vec3 = &ray.origin - &self.position;
-        let a: f32 = ray.direction.dot(&ray.direction);
-        let b: f32 = 2.0 * ray.direction.dot(&l);
-        let c: f32 = l.dot(&l) - self.radius_squared;
-
-        match solve_quadratic(a, b, c) {
+        match self.roots(ray) {
             Some((mut t0, t1)) => {
 
                 if t0 < 0.0 {
@@ -70,27 +96,19 @@ impl Object for Sphere {
                     }
                 }
 
-                let distance = t0;
-                let position = &ray.origin + &(&ray.direction * distance);
-                let mut normal = &position - &self.position;
-                normal.normalize();
-
-                if let Some(texture_normal) = self.material.texture_normal.as_ref() {
-                    let normal_color = texture_normal.color_at(self.texture_coordinate(&position));
-                    normal = adjusted_for_normal(&normal, &color_to_normal(&normal_color));
-                }
-
-                Some(Intersection::new(
-                    distance,
-                    &position + &(&normal * 0.001), // offset to avoid floating-point error
-                    normal,
-                    ray.direction,
-                ))
+                Some(self.hit_at(ray, t0))
             },
             None => None
         }
     }
 
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        match self.roots(ray) {
+            Some((t0, t1)) => vec![Interval::new(self.hit_at(ray, t0), self.hit_at(ray, t1))],
+            None => Vec::new(),
+        }
+    }
+
 //    #[flame("Sphere")]
     fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
         let relative_point = point - &self.position;
@@ -112,48 +130,23 @@ impl Object for Sphere {
         );
     }
 
-//    #[flame("Sphere")]
-    fn shade(&self, ray: &Ray, objs: &Vec<ObjectEnum>, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
-        let mut intersection = self.intersection(ray).unwrap();
-        let uv = self.texture_coordinate(&intersection.position);
+    fn bounds(&self) -> Option<BoundingBox> {
+        let extent = Vec3::from_scalar(self.radius);
 
+        Some(BoundingBox {
+            min: &self.position - &extent,
+            max: &self.position + &extent,
+        })
+    }
+
+//    #[flame("Sphere")]
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
         self.material.shade(
-            &mut intersection,
-            uv,
-            objs,
+            intersection,
+            world,
+            settings,
             rng,
             bounces_remaining
         )
     }
 }
-
-
-fn solve_quadratic(a: f32, b: f32, c: f32) -> Option<(f32,f32)> {
-    let discr = b * b - 4.0 * a * c;
-
-    let mut t0: f32;
-    let mut t1: f32;
-
-    if discr < 0.0 {
-        return None;
-    } else if discr == 0.0 {//discr.abs() < 0.0001 {
-        t0 = -0.5 * b / a;
-        t1 = t0;
-    } else {
-        let q = 
-            if b > 0.0 {
-                -0.5 * (b + discr.sqrt())
-            } else {
-                -0.5 * (b - discr.sqrt())
-            };
-
-        t0 = q / a;
-        t1 = c / q;
-    }
-
-    if t0 > t1 {
-        std::mem::swap(&mut t0, &mut t1);
-    }
-
-    return Some((t0, t1));
-}
@@ -0,0 +1,147 @@
+// https://en.wikipedia.org/wiki/STL_(file_format)
+
+use crate::vec3::Vec3;
+use crate::load_error::{LoadError,number_arg};
+
+// binary files start with an 80-byte header and a triangle count, and
+// then have 50 bytes per triangle (a normal, three corners, and two bytes
+// of attributes)
+const BINARY_HEADER_SIZE: usize = 84;
+const BINARY_TRIANGLE_SIZE: usize = 50;
+
+/**
+ * The corners of each triangle in an STL file, ASCII or binary. Facet
+ * normals are left out, since they're often missing or wrong; the corners
+ * are counter-clockwise, so they can be worked out again.
+ */
+pub fn parse(data: &[u8]) -> Result<Vec<[Vec3;3]>,LoadError> {
+
+    // some binary files start with "solid" too, so their size (which is
+    // known up front) is what gives them away
+    let binary_size = data.get(80..BINARY_HEADER_SIZE)
+        .map(|count| u32::from_le_bytes([count[0], count[1], count[2], count[3]]) as usize)
+        .map(|count| BINARY_HEADER_SIZE + count * BINARY_TRIANGLE_SIZE);
+
+    if binary_size != Some(data.len()) && data.trim_ascii_start().starts_with(b"solid") {
+        let text = std::str::from_utf8(data)
+            .map_err(|_| LoadError::invalid("an ASCII file has non-text data in it"))?;
+
+        parse_ascii(text)
+    } else {
+        parse_binary(data)
+    }
+}
+
+fn parse_binary(data: &[u8]) -> Result<Vec<[Vec3;3]>,LoadError> {
+    if data.len() < BINARY_HEADER_SIZE {
+        return Err(LoadError::invalid("too short to be an STL file"));
+    }
+
+    let count = u32::from_le_bytes([data[80], data[81], data[82], data[83]]) as usize;
+    if data.len() < BINARY_HEADER_SIZE + count * BINARY_TRIANGLE_SIZE {
+        return Err(LoadError::invalid(&format!("should have {} triangles, but ends early", count)));
+    }
+
+    let float = |offset: usize| f32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]]);
+    let vec3 = |offset: usize| Vec3 { x: float(offset), y: float(offset + 4), z: float(offset + 8) };
+
+    Ok((0..count)
+        .map(|triangle| {
+            // the corners come after the facet normal
+            let start = BINARY_HEADER_SIZE + triangle * BINARY_TRIANGLE_SIZE + 12;
+
+            [vec3(start), vec3(start + 12), vec3(start + 24)]
+        })
+        .collect())
+}
+
+fn parse_ascii(text: &str) -> Result<Vec<[Vec3;3]>,LoadError> {
+    let mut triangles = Vec::new();
+
+    // the corners of the facet being read
+    let mut corners: Vec<Vec3> = Vec::with_capacity(3);
+    for (index, line) in text.lines().enumerate() {
+        parse_line(line, &mut corners, &mut triangles).map_err(|err| err.at_line(index + 1))?;
+    }
+
+    Ok(triangles)
+}
+
+fn parse_line(line: &str, corners: &mut Vec<Vec3>, triangles: &mut Vec<[Vec3;3]>) -> Result<(),LoadError> {
+    let segments: Vec<&str> = line.split_whitespace().collect();
+
+    match segments.first().copied().unwrap_or("") {
+        "vertex" => {
+            if corners.len() == 3 {
+                return Err(LoadError::parse("vertex", "a facet can only have 3 vertices"));
+            }
+
+            corners.push(Vec3 {
+                x: number_arg(&segments, 1)?,
+                y: number_arg(&segments, 2)?,
+                z: number_arg(&segments, 3)?,
+            });
+        },
+        "endloop" => {
+            if corners.len() != 3 {
+                return Err(LoadError::parse("endloop", &format!("a facet needs 3 vertices, not {}", corners.len())));
+            }
+
+            triangles.push([corners[0], corners[1], corners[2]]);
+            corners.clear();
+        },
+
+        // solid, facet, outer loop and their ends just hold the vertices
+        _ => (),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Vec3;
+    use crate::stl_parser::parse;
+
+    #[test]
+    fn test_parse() {
+        let ascii = "solid part
+            facet normal 0 0 1
+                outer loop
+                    vertex 0 0 0
+                    vertex 1 0 0
+                    vertex 0 1 0
+                endloop
+            endfacet
+            facet normal 0 0 0
+                outer loop
+                    vertex 1 0 0
+                    vertex 1 1 0
+                    vertex 0 1 0
+                endloop
+            endfacet
+        endsolid part
+        ";
+        let triangles = parse(ascii.as_bytes()).unwrap();
+        assert_eq!(triangles.len(), 2);
+        assert_eq!(triangles[1][1], Vec3 { x: 1.0, y: 1.0, z: 0.0 });
+
+        // a binary file, with a header that looks like an ASCII one
+        let mut binary = b"solid but actually binary".to_vec();
+        binary.resize(80, 0);
+        binary.extend(&1u32.to_le_bytes());
+        for &value in &[0.0f32, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            binary.extend(&value.to_le_bytes());
+        }
+        binary.extend(&[0, 0]);
+        let triangles = parse(&binary).unwrap();
+        assert_eq!(triangles, vec![[Vec3::new(), Vec3 { x: 1.0, y: 0.0, z: 0.0 }, Vec3 { x: 0.0, y: 1.0, z: 0.0 }]]);
+
+        binary.truncate(100);
+        assert!(parse(&binary).is_err());
+
+        let err = parse(b"solid\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0\n").err().unwrap().to_string();
+        assert_eq!(err, "line 5, `vertex`: expected at least 3 values");
+        assert!(parse(b"solid\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nendloop\n").is_err());
+    }
+}
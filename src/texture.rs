@@ -1,25 +1,69 @@
 
 use std::fs::File;
 use std::io::BufReader;
-use image::{DynamicImage,ImageFormat,GenericImageView,load};
+use std::sync::Arc;
+use image::{DynamicImage,ImageFormat,GenericImageView,RgbImage,Rgb,load};
 
 use crate::color::Color;
+use crate::vec3::Vec3;
+use crate::load_error::LoadError;
 
-
+#[derive(Clone)]
 pub enum Texture {
     Solid(Color),
-    Image(DynamicImage),
+
+    // shared, so that copying a material doesn't copy its images
+    Image(Arc<DynamicImage>),
     Procedural(&'static (dyn Send + Sync + Fn((f32,f32)) -> Color)),
+
+    // another texture, scaled and then moved across the surface; outside
+    // of 0-1 it either repeats or, if clamped, stretches its edges
+    Mapped { texture: Box<Texture>, scale: (f32,f32), offset: (f32,f32), clamp: bool },
 }
 
 impl Texture {
 
-    pub fn from_image(path: &str) -> Self {
-        let f = File::open(path).expect("Failed to open texture file");
+    pub fn from_image(path: &str) -> Result<Self,LoadError> {
+        let f = File::open(path).map_err(|err| LoadError::Io {
+            path: String::from(path),
+            message: err.to_string(),
+        })?;
         let f = BufReader::new(f);
-        let image = load(f, ImageFormat::JPEG).expect("Failed to load image");
+        let format = ImageFormat::from_path(path).unwrap_or(ImageFormat::JPEG);
+        let image = load(f, format).map_err(|err| LoadError::Image {
+            path: String::from(path),
+            message: err.to_string(),
+        })?;
+
+        Ok(Texture::Image(Arc::new(image)))
+    }
+
+    /**
+     * Read this texture as a height map (brighter is higher) and make a
+     * normal map from its slopes, steepened by `strength`. Only images
+     * have slopes; any other texture comes out flat.
+     */
+    pub fn heights_to_normals(&self, strength: f32) -> Self {
+        let image = match self {
+            Texture::Image(image) => image,
+            _ => return Texture::Solid(Color(0.5, 0.5, 1.0)),
+        };
 
-        Texture::Image(image)
+        // the edges wrap around, like the texture does
+        let heights = image.to_luma();
+        let (width, height) = heights.dimensions();
+        let height_at = |x: i64, y: i64| u8_to_f32(heights.get_pixel(x.rem_euclid(width as i64) as u32, y.rem_euclid(height as i64) as u32)[0]);
+
+        let normals = RgbImage::from_fn(width, height, |x, y| {
+            let (x, y) = (x as i64, y as i64);
+            let slope_x = (height_at(x + 1, y) - height_at(x - 1, y)) / 2.0 * strength;
+            let slope_y = (height_at(x, y + 1) - height_at(x, y - 1)) / 2.0 * strength;
+            let normal = Vec3 { x: -slope_x, y: -slope_y, z: 1.0 }.normalized();
+
+            Rgb([normal.x, normal.y, normal.z].map(|component| ((component * 0.5 + 0.5) * 255.0).round() as u8))
+        });
+
+        Texture::Image(Arc::new(DynamicImage::ImageRgb8(normals)))
     }
 
     pub fn color_at(&self, uv: (f32,f32)) -> Color {
@@ -36,6 +80,16 @@ impl Texture {
                     u8_to_f32(p[2]))
             }
             Texture::Procedural(func) => func(uv),
+            Texture::Mapped { texture, scale, offset, clamp } => {
+                let u = uv.0 * scale.0 + offset.0;
+                let v = uv.1 * scale.1 + offset.1;
+
+                if *clamp {
+                    texture.color_at((clamp_uv(u), clamp_uv(v)))
+                } else {
+                    texture.color_at((u - u.floor(), v - v.floor()))
+                }
+            },
         }
     }
 }
@@ -44,6 +98,11 @@ fn uv_to_pixel(uv: f32, dim: u32) -> u32 {
     (uv * dim as f32).floor() as u32
 }
 
+// just short of 1, which would be off the edge of an image
+fn clamp_uv(uv: f32) -> f32 {
+    uv.clamp(0.0, 1.0 - f32::EPSILON)
+}
+
 fn u8_to_f32(val: u8) -> f32 {
     val as f32 / 255.0
 }
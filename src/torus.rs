@@ -0,0 +1,202 @@
+use std::f32::consts::PI;
+
+use rand::rngs::SmallRng;
+
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::object::Object;
+use crate::intersection::{Intersection,Interval};
+use crate::material::Material;
+use crate::illumination::Illumination;
+use crate::render_settings::RenderSettings;
+use crate::bounding_box::BoundingBox;
+use crate::utils::{TWO_PI,solve_quadratic,solve_quartic};
+use crate::world::World;
+
+/**
+ * A ring lying flat (around the Y axis), centered on its position.
+ * `major_radius` is from the center to the middle of the tube, and
+ * `minor_radius` is the tube's own radius. u goes around the ring, and v
+ * goes around the tube.
+ */
+pub struct Torus {
+    position: Vec3,
+    major_radius: f32,
+    minor_radius: f32,
+    material: Material,
+}
+
+impl Torus {
+
+    pub fn new(position: Vec3, major_radius: f32, minor_radius: f32, material: Material) -> Self {
+        Self {
+            position,
+            major_radius,
+            minor_radius,
+            material,
+        }
+    }
+
+    /**
+     * Every distance along the ray where it crosses the surface (the roots
+     * of the torus's quartic), in order
+     */
+    fn roots(&self, ray: &Ray) -> Vec<f32> {
+        let origin = &ray.origin - &self.position;
+        let direction = &ray.direction;
+
+        // Starting the quartic from far away makes its coefficients huge and
+        // the roots imprecise, so start from where the ray enters the
+        // bounding sphere instead
+        let outer_radius = self.major_radius + self.minor_radius;
+        let (entry, _) = match solve_quadratic(
+            direction.dot(direction),
+            2.0 * direction.dot(&origin),
+            origin.dot(&origin) - outer_radius * outer_radius,
+        ) {
+            Some(roots) => roots,
+            None => return Vec::new(),
+        };
+        let start = entry as f64;
+
+        let (dx, dy, dz) = (direction.x as f64, direction.y as f64, direction.z as f64);
+        let (ox, oy, oz) = (origin.x as f64 + dx * start, origin.y as f64 + dy * start, origin.z as f64 + dz * start);
+        let major_squared = self.major_radius as f64 * self.major_radius as f64;
+        let minor_squared = self.minor_radius as f64 * self.minor_radius as f64;
+
+        let dd = dx * dx + dy * dy + dz * dz;
+        let od = ox * dx + oy * dy + oz * dz;
+        let e = ox * ox + oy * oy + oz * oz + major_squared - minor_squared;
+
+        let a = dd * dd;
+        let b = 4.0 * dd * od;
+        let c = 2.0 * dd * e + 4.0 * od * od - 4.0 * major_squared * (dx * dx + dz * dz);
+        let d = 4.0 * od * e - 8.0 * major_squared * (ox * dx + oz * dz);
+        let e = e * e - 4.0 * major_squared * (ox * ox + oz * oz);
+
+        let mut roots: Vec<f32> = solve_quartic(a, b, c, d, e).into_iter()
+            .map(|t| {
+                // polish the root, since Ferrari's method can lose precision
+                let mut t = t;
+                for _ in 0..2 {
+                    let value = (((a * t + b) * t + c) * t + d) * t + e;
+                    let slope = ((4.0 * a * t + 3.0 * b) * t + 2.0 * c) * t + d;
+                    if slope == 0.0 {
+                        break;
+                    }
+                    t -= value / slope;
+                }
+                (t + start) as f32
+            })
+            .collect();
+
+        roots.sort_by(|a, b| a.total_cmp(b));
+        roots
+    }
+
+    fn hit_at(&self, ray: &Ray, distance: f32) -> Intersection {
+        let position = &ray.origin + &(&ray.direction * distance);
+        let relative = &position - &self.position;
+
+        // the gradient of the torus's implicit function
+        let sum = relative.len_squared() + self.major_radius * self.major_radius - self.minor_radius * self.minor_radius;
+        let horizontal = sum - 2.0 * self.major_radius * self.major_radius;
+        let normal = Vec3 {
+            x: relative.x * horizontal,
+            y: relative.y * sum,
+            z: relative.z * horizontal,
+        }.normalized();
+
+        let mut intersection = Intersection::new(
+            distance,
+            &position + &(&normal * 0.001), // offset to avoid floating-point error
+            normal,
+            ray.direction,
+        );
+        intersection.uv = self.texture_coordinate(&position);
+
+        intersection
+    }
+}
+
+impl Object for Torus {
+
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        self.roots(ray).into_iter()
+            .find(|&distance| distance > 1e-4)
+            .map(|distance| self.hit_at(ray, distance))
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        // the roots alternate between entering and leaving the tube
+        self.roots(ray)
+            .chunks_exact(2)
+            .map(|pair| Interval::new(self.hit_at(ray, pair[0]), self.hit_at(ray, pair[1])))
+            .collect()
+    }
+
+    fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
+        let relative = point - &self.position;
+        let from_axis = (relative.x * relative.x + relative.z * relative.z).sqrt();
+
+        (
+            (relative.z.atan2(relative.x) + PI) / TWO_PI,
+            (relative.y.atan2(from_axis - self.major_radius) + PI) / TWO_PI,
+        )
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        let outer_radius = self.major_radius + self.minor_radius;
+        let extent = Vec3 { x: outer_radius, y: self.minor_radius, z: outer_radius };
+
+        Some(BoundingBox {
+            min: &self.position - &extent,
+            max: &self.position + &extent,
+        })
+    }
+
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+        self.material.shade(
+            intersection,
+            world,
+            settings,
+            rng,
+            bounces_remaining
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Vec3;
+    use crate::ray::Ray;
+    use crate::material::Material;
+    use crate::object::Object;
+    use crate::torus::Torus;
+
+    #[test]
+    fn test_intersection() {
+        let torus = Torus::new(Vec3 { x: 0.0, y: 0.0, z: -10.0 }, 2.0, 0.5, Material::new());
+
+        // straight through the middle of the hole
+        assert!(torus.intersection(&Ray {
+            origin: Vec3 { x: 0.0, y: 5.0, z: -10.0 },
+            direction: Vec3 { x: 0.0, y: -1.0, z: 0.0 },
+        }).is_none());
+
+        let side = torus.intersection(&Ray {
+            origin: Vec3 { x: -10.0, y: 0.0, z: -10.0 },
+            direction: Vec3 { x: 2.0, y: 0.0, z: 0.0 },
+        }).unwrap();
+        assert!((side.distance - 3.75).abs() < 0.0001); // the direction isn't normalized
+        assert!((&side.normal - &Vec3 { x: -1.0, y: 0.0, z: 0.0 }).len() < 0.0001);
+
+        let top = torus.intersection(&Ray {
+            origin: Vec3 { x: 2.0, y: 5.0, z: -10.0 },
+            direction: Vec3 { x: 0.0, y: -1.0, z: 0.0 },
+        }).unwrap();
+        assert!((top.distance - 4.5).abs() < 0.0001);
+        assert!((&top.normal - &Vec3 { x: 0.0, y: 1.0, z: 0.0 }).len() < 0.0001);
+        assert!((top.uv.1 - 0.75).abs() < 0.0001);
+    }
+}
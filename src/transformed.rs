@@ -0,0 +1,188 @@
+use std::sync::Arc;
+
+use rand::rngs::SmallRng;
+
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::object::{Object,ObjectEnum};
+use crate::intersection::{Intersection,Interval};
+use crate::illumination::Illumination;
+use crate::render_settings::RenderSettings;
+use crate::matrix::Matrix;
+use crate::bounding_box::BoundingBox;
+use crate::world::World;
+
+/**
+ * Another object, placed in the world by an arbitrary affine transform.
+ * Rays are moved into the object's space to be intersected, and hits are
+ * moved back out. The object itself is shared, so (for example) a mesh
+ * can be loaded once and appear any number of times.
+ */
+pub struct Transformed {
+    object: Arc<ObjectEnum>,
+
+    object_to_world: Matrix,
+    world_to_object: Matrix,
+    normal_matrix: Matrix,
+}
+
+impl Transformed {
+
+    /**
+     * Returns `None` if the transform can't be inverted (like a scale by
+     * zero), since there'd be no way to bring rays into object space.
+     */
+    pub fn new(object: Arc<ObjectEnum>, transform: &Matrix) -> Option<Self> {
+        Some(Self {
+            object,
+            object_to_world: *transform,
+            world_to_object: transform.inverse()?,
+            normal_matrix: transform.normal_matrix()?,
+        })
+    }
+
+    pub fn object(&self) -> &Arc<ObjectEnum> {
+        &self.object
+    }
+
+    pub fn transform(&self) -> &Matrix {
+        &self.object_to_world
+    }
+
+    fn object_ray(&self, ray: &Ray) -> Ray {
+
+        // The direction isn't re-normalized, so that distances along the
+        // object-space ray are the same as along the world-space one
+        Ray {
+            origin: ray.origin.transform_point(&self.world_to_object),
+            direction: ray.direction.transform_direction(&self.world_to_object),
+        }
+    }
+
+    /**
+     * Bring a hit on the object back out into world space
+     */
+    fn to_world(&self, ray: &Ray, mut intersection: Intersection) -> Intersection {
+        intersection.normal = intersection.normal.transform_direction(&self.normal_matrix).normalized();
+        intersection.geometric_normal = intersection.geometric_normal.transform_direction(&self.normal_matrix).normalized();
+        intersection.direction = ray.direction;
+        intersection.tangent_frame = intersection.tangent_frame.map(|(tangent, bitangent)| (
+            tangent.transform_direction(&self.object_to_world).normalized(),
+            bitangent.transform_direction(&self.object_to_world).normalized(),
+        ));
+
+        // recomputed rather than transformed, so that the offset
+        // from the surface isn't scaled along with the object
+        let position = &ray.origin + &(&ray.direction * intersection.distance);
+        intersection.position = &position + &(&intersection.geometric_normal * 0.001); // offset to avoid floating-point error
+
+        intersection
+    }
+}
+
+impl Object for Transformed {
+
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+        self.object.intersection(&self.object_ray(ray))
+            .map(|intersection| self.to_world(ray, intersection))
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        self.object.intervals(&self.object_ray(ray)).into_iter()
+            .map(|interval| Interval::new(self.to_world(ray, interval.entry), self.to_world(ray, interval.exit)))
+            .collect()
+    }
+
+    fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
+        self.object.texture_coordinate(&point.transform_point(&self.world_to_object))
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        let bounds = self.object.bounds()?;
+        if bounds.is_empty() {
+            return Some(bounds);
+        }
+
+        let corners: Vec<Vec3> = (0..8)
+            .map(|i| Vec3 {
+                x: if i & 1 == 0 { bounds.min.x } else { bounds.max.x },
+                y: if i & 2 == 0 { bounds.min.y } else { bounds.max.y },
+                z: if i & 4 == 0 { bounds.min.z } else { bounds.max.z },
+            }.transform_point(&self.object_to_world))
+            .collect();
+
+        Some(BoundingBox::from_points(&corners))
+    }
+
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+        // the hit record is already in world space
+        self.object.shade(intersection, world, settings, rng, bounces_remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::vec3::Vec3;
+    use crate::ray::Ray;
+    use crate::matrix::{Matrix,IDENTITY};
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+    use crate::mesh::Mesh;
+    use crate::object::{Object,ObjectEnum};
+    use crate::transformed::Transformed;
+
+    #[test]
+    fn test_ellipsoid() {
+        let sphere = Arc::new(ObjectEnum::Sphere(Sphere::new(Vec3::new(), 1.0, Material::new())));
+        let ellipsoid = Transformed::new(sphere, &Matrix::scale(&Vec3 { x: 2.0, y: 1.0, z: 1.0 })).unwrap();
+
+        let side = ellipsoid.intersection(&Ray {
+            origin: Vec3 { x: -5.0, y: 0.0, z: 0.0 },
+            direction: Vec3 { x: 1.0, y: 0.0, z: 0.0 },
+        }).unwrap();
+        assert!((side.distance - 3.0).abs() < 0.0001);
+        assert!((&side.normal - &Vec3 { x: -1.0, y: 0.0, z: 0.0 }).len() < 0.0001);
+
+        let top = ellipsoid.intersection(&Ray {
+            origin: Vec3 { x: 0.0, y: 5.0, z: 0.0 },
+            direction: Vec3 { x: 0.0, y: -1.0, z: 0.0 },
+        }).unwrap();
+        assert!((top.distance - 4.0).abs() < 0.0001);
+
+        let bounds = ellipsoid.bounds().unwrap();
+        assert!((&bounds.max - &Vec3 { x: 2.0, y: 1.0, z: 1.0 }).len() < 0.0001);
+
+        let singular = Arc::new(ObjectEnum::Sphere(Sphere::new(Vec3::new(), 1.0, Material::new())));
+        assert!(Transformed::new(singular, &Matrix::scale(&Vec3 { x: 1.0, y: 0.0, z: 1.0 })).is_none());
+    }
+
+    #[test]
+    fn test_instance_matches_baked_mesh() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/test.obj");
+        let transform = Matrix::scale(&Vec3::from_scalar(0.5))
+            .then(&Matrix::rotation_y(1.0))
+            .then(&Matrix::translation(&Vec3 { x: 1.0, y: -2.0, z: -3.0 }));
+
+        let baked = Mesh::from_obj(path, &transform, None).unwrap();
+        let instance = Transformed::new(Arc::new(ObjectEnum::Mesh(Mesh::from_obj(path, &IDENTITY, None).unwrap())), &transform).unwrap();
+
+        let target = baked.bounds().unwrap().centroid();
+        for i in 0..100 {
+            let origin = Vec3::from_angles(i as f32 * 0.7, (i as f32 * 0.3).sin());
+            let origin = &target + &(&origin * 10.0);
+            let ray = Ray { origin, direction: (&target - &origin).normalized() };
+
+            let expected = baked.intersection(&ray);
+            let actual = instance.intersection(&ray);
+
+            assert_eq!(expected.is_some(), actual.is_some());
+            if let (Some(expected), Some(actual)) = (expected, actual) {
+                assert!((expected.distance - actual.distance).abs() < 0.001);
+                assert!((&expected.normal.normalized() - &actual.normal).len() < 0.001);
+                assert_eq!(expected.face_index, actual.face_index);
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use rand::rngs::SmallRng;
+
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::object::Object;
+use crate::intersection::{Intersection,Interval};
+use crate::material::Material;
+use crate::illumination::Illumination;
+use crate::render_settings::RenderSettings;
+use crate::bounding_box::BoundingBox;
+use crate::world::World;
+
+/**
+ * A single standalone triangle. Texture coordinates are interpolated
+ * between the ones given for each vertex; by default the first vertex is
+ * at (0, 0), the second at (1, 0) and the third at (0, 1).
+ */
+pub struct Triangle {
+    v0: Vec3,
+    v1: Vec3,
+    v2: Vec3,
+    uvs: [(f32,f32); 3],
+    material: Material,
+
+    edge1: Vec3,
+    edge2: Vec3,
+    normal: Vec3,
+}
+
+impl Triangle {
+
+    pub fn new(v0: Vec3, v1: Vec3, v2: Vec3, material: Material) -> Self {
+        let edge1 = &v1 - &v0;
+        let edge2 = &v2 - &v0;
+
+        Self {
+            v0,
+            v1,
+            v2,
+            uvs: [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)],
+            material,
+            normal: edge1.cross(&edge2).normalized(),
+            edge1,
+            edge2,
+        }
+    }
+
+    pub fn with_uvs(mut self, uvs: [(f32,f32); 3]) -> Self {
+        self.uvs = uvs;
+        self
+    }
+
+    fn interpolated_uv(&self, b1: f32, b2: f32) -> (f32,f32) {
+        let b0 = 1.0 - b1 - b2;
+
+        (
+            b0 * self.uvs[0].0 + b1 * self.uvs[1].0 + b2 * self.uvs[2].0,
+            b0 * self.uvs[0].1 + b1 * self.uvs[1].1 + b2 * self.uvs[2].1,
+        )
+    }
+}
+
+impl Object for Triangle {
+
+    fn intersection(&self, ray: &Ray) -> Option<Intersection> {
+
+        // Möller–Trumbore
+        let p = ray.direction.cross(&self.edge2);
+        let determinant = self.edge1.dot(&p);
+        if determinant.abs() < f32::EPSILON {
+            return None; // parallel to the triangle
+        }
+
+        let inverse_determinant = 1.0 / determinant;
+        let s = &ray.origin - &self.v0;
+        let b1 = s.dot(&p) * inverse_determinant;
+        if !(0.0..=1.0).contains(&b1) {
+            return None;
+        }
+
+        let q = s.cross(&self.edge1);
+        let b2 = ray.direction.dot(&q) * inverse_determinant;
+        if b2 < 0.0 || b1 + b2 > 1.0 {
+            return None;
+        }
+
+        let distance = self.edge2.dot(&q) * inverse_determinant;
+        if distance <= 0.0 {
+            return None;
+        }
+
+        let position = &ray.origin + &(&ray.direction * distance);
+        let mut intersection = Intersection::new(
+            distance,
+            &position + &(&self.normal * 0.001), // offset to avoid floating-point error
+            self.normal,
+            ray.direction,
+        );
+        intersection.barycentric = Some((b1, b2));
+        intersection.uv = self.interpolated_uv(b1, b2);
+
+        Some(intersection)
+    }
+
+    fn intervals(&self, _ray: &Ray) -> Vec<Interval> {
+        // flat, so there's no inside
+        Vec::new()
+    }
+
+    fn texture_coordinate(&self, point: &Vec3) -> (f32,f32) {
+        let relative = point - &self.v0;
+        let area = self.edge1.cross(&self.edge2).dot(&self.normal);
+
+        let b1 = relative.cross(&self.edge2).dot(&self.normal) / area;
+        let b2 = self.edge1.cross(&relative).dot(&self.normal) / area;
+
+        self.interpolated_uv(b1, b2)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        Some(BoundingBox::from_points(&[self.v0, self.v1, self.v2]))
+    }
+
+    fn shade(&self, intersection: &mut Intersection, world: &World, settings: &RenderSettings, rng: &mut SmallRng, bounces_remaining: u8) -> Illumination {
+        self.material.shade(
+            intersection,
+            world,
+            settings,
+            rng,
+            bounces_remaining
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::Vec3;
+    use crate::ray::Ray;
+    use crate::material::Material;
+    use crate::object::Object;
+    use crate::triangle::Triangle;
+
+    #[test]
+    fn test_intersection_and_uv() {
+        let triangle = Triangle::new(
+            Vec3 { x: 0.0, y: 0.0, z: -2.0 },
+            Vec3 { x: 2.0, y: 0.0, z: -2.0 },
+            Vec3 { x: 0.0, y: 2.0, z: -2.0 },
+            Material::new(),
+        ).with_uvs([(0.0, 0.0), (1.0, 0.0), (0.0, 0.5)]);
+
+        let hit = triangle.intersection(&Ray {
+            origin: Vec3 { x: 0.5, y: 1.0, z: 0.0 },
+            direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+        }).unwrap();
+        assert!((hit.distance - 2.0).abs() < 0.0001);
+        assert!((hit.normal.z - 1.0).abs() < 0.0001);
+        assert!((hit.uv.0 - 0.25).abs() < 0.0001 && (hit.uv.1 - 0.25).abs() < 0.0001);
+
+        let uv = triangle.texture_coordinate(&Vec3 { x: 0.5, y: 1.0, z: -2.0 });
+        assert!((uv.0 - 0.25).abs() < 0.0001 && (uv.1 - 0.25).abs() < 0.0001);
+
+        assert!(triangle.intersection(&Ray {
+            origin: Vec3 { x: 1.5, y: 1.5, z: 0.0 },
+            direction: Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+        }).is_none());
+    }
+}
@@ -55,5 +55,150 @@ pub fn color_to_normal(color: &Color) -> Vec3 {
 
 pub fn adjusted_for_normal(original_normal: &Vec3, normal_from_map: &Vec3) -> Vec3 {
     let transformation = Matrix::from_to_rotation(&FORWARD, &normal_from_map);
-    original_normal.transformed(&transformation)
+    original_normal.transform_direction(&transformation)
+}
+
+/**
+ * The direction light bends to when it crosses a surface into something
+ * with the given index of refraction (or out of it, when `direction` goes
+ * out through the front of `normal`). `None` if the light can't get out,
+ * and is reflected instead.
+ */
+pub fn refract(direction: &Vec3, normal: &Vec3, index_of_refraction: f32) -> Option<Vec3> {
+    let direction = direction.normalized();
+    let mut normal = normal.normalized();
+    let mut cos_incoming = -direction.dot(&normal);
+    let mut ratio = 1.0 / index_of_refraction;
+
+    if cos_incoming < 0.0 {
+        normal = &normal * -1.0;
+        cos_incoming = -cos_incoming;
+        ratio = index_of_refraction;
+    }
+
+    let cos_squared_outgoing = 1.0 - ratio * ratio * (1.0 - cos_incoming * cos_incoming);
+    if cos_squared_outgoing < 0.0 {
+        None
+    } else {
+        Some(&(&direction * ratio) + &(&normal * (ratio * cos_incoming - cos_squared_outgoing.sqrt())))
+    }
+}
+
+/**
+ * Some unit vector perpendicular to `normal`, for building a tangent frame
+ * when nothing else decides which way is "up" on a surface
+ */
+pub fn perpendicular(normal: &Vec3) -> Vec3 {
+    let other = if normal.x.abs() < 0.9 {
+        Vec3 { x: 1.0, y: 0.0, z: 0.0 }
+    } else {
+        Vec3 { x: 0.0, y: 1.0, z: 0.0 }
+    };
+
+    normal.cross(&other).normalized()
+}
+
+/**
+ * Real roots of `a*x^2 + b*x + c`, smallest first
+ */
+pub fn solve_quadratic(a: f32, b: f32, c: f32) -> Option<(f32,f32)> {
+    let discr = b * b - 4.0 * a * c;
+
+    let mut t0: f32;
+    let mut t1: f32;
+
+    if discr < 0.0 {
+        return None;
+    } else if discr == 0.0 {//discr.abs() < 0.0001 {
+        t0 = -0.5 * b / a;
+        t1 = t0;
+    } else {
+        let q = 
+            if b > 0.0 {
+                -0.5 * (b + discr.sqrt())
+            } else {
+                -0.5 * (b - discr.sqrt())
+            };
+
+        t0 = q / a;
+        t1 = c / q;
+    }
+
+    if t0 > t1 {
+        std::mem::swap(&mut t0, &mut t1);
+    }
+
+    return Some((t0, t1));
+}
+
+/**
+ * The largest real root of `x^3 + a*x^2 + b*x + c` (there's always at 
+ * least one)
+ */
+fn largest_cubic_root(a: f64, b: f64, c: f64) -> f64 {
+
+    // substitute x = z - a/3 to get z^3 + p*z + q
+    let p = b - a * a / 3.0;
+    let q = 2.0 * a * a * a / 27.0 - a * b / 3.0 + c;
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+
+    let z = if discriminant >= 0.0 {
+        // one real root
+        let sqrt = discriminant.sqrt();
+        (-q / 2.0 + sqrt).cbrt() + (-q / 2.0 - sqrt).cbrt()
+    } else {
+        // three real roots; this is the largest
+        let r = (-p / 3.0).sqrt();
+        2.0 * r * ((-q / (2.0 * r * r * r)).clamp(-1.0, 1.0).acos() / 3.0).cos()
+    };
+
+    z - a / 3.0
+}
+
+/**
+ * Real roots of `a*x^4 + b*x^3 + c*x^2 + d*x + e` (Ferrari's method), in no
+ * particular order. Done in double precision, since the intermediate 
+ * values get large enough for single precision to lose whole roots.
+ */
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // substitute x = y - b/4 to get y^4 + p*y^2 + q*y + r
+    let p = c - 3.0 * b * b / 8.0;
+    let q = d - b * c / 2.0 + b * b * b / 8.0;
+    let r = e - b * d / 4.0 + b * b * c / 16.0 - 3.0 * b * b * b * b / 256.0;
+
+    let mut roots: Vec<f64> = Vec::with_capacity(4);
+    let mut push_quadratic_roots = |linear: f64, constant: f64| {
+        let discriminant = linear * linear - 4.0 * constant;
+        if discriminant >= 0.0 {
+            let sqrt = discriminant.sqrt();
+            roots.push((-linear + sqrt) / 2.0 - b / 4.0);
+            roots.push((-linear - sqrt) / 2.0 - b / 4.0);
+        }
+    };
+
+    if q.abs() < 1e-12 {
+        // biquadratic: solve for y^2
+        let discriminant = p * p - 4.0 * r;
+        if discriminant >= 0.0 {
+            for y_squared in [(-p + discriminant.sqrt()) / 2.0, (-p - discriminant.sqrt()) / 2.0].iter() {
+                if *y_squared >= 0.0 {
+                    push_quadratic_roots(0.0, -y_squared);
+                }
+            }
+        }
+    } else {
+        // split into two quadratics, using a root of the resolvent cubic
+        let m = largest_cubic_root(p, p * p / 4.0 - r, -q * q / 8.0);
+        if m <= 0.0 {
+            return roots;
+        }
+
+        let s = (2.0 * m).sqrt();
+        push_quadratic_roots(-s, p / 2.0 + m + q / (2.0 * s));
+        push_quadratic_roots(s, p / 2.0 + m - q / (2.0 * s));
+    }
+
+    roots
 }
@@ -60,7 +60,10 @@ impl Vec3 {
         return result;
     }
 
-    pub fn transformed(&self, matrix: &Matrix) -> Self {
+    /**
+     * Transform this as a position: translation applies
+     */
+    pub fn transform_point(&self, matrix: &Matrix) -> Self {
         Vec3 {
             x:  matrix.get(0, 0) * self.x +
                 matrix.get(0, 1) * self.y +
@@ -77,6 +80,25 @@ impl Vec3 {
         }
     }
 
+    /**
+     * Transform this as a direction (or offset): translation doesn't 
+     * apply. Normals should be transformed by `Matrix::normal_matrix()`
+     * instead of the matrix itself.
+     */
+    pub fn transform_direction(&self, matrix: &Matrix) -> Self {
+        Vec3 {
+            x:  matrix.get(0, 0) * self.x +
+                matrix.get(0, 1) * self.y +
+                matrix.get(0, 2) * self.z,
+            y:  matrix.get(1, 0) * self.x +
+                matrix.get(1, 1) * self.y +
+                matrix.get(1, 2) * self.z,
+            z:  matrix.get(2, 0) * self.x +
+                matrix.get(2, 1) * self.y +
+                matrix.get(2, 2) * self.z
+        }
+    }
+
     pub fn scale(&mut self, scale: f32) {
         self.x *= scale;
         self.y *= scale;
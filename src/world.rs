@@ -0,0 +1,146 @@
+use crate::ray::Ray;
+use crate::intersection::Intersection;
+use crate::object::{Object,ObjectEnum};
+use crate::bounding_box::BoundingBox;
+use crate::bvh::Bvh;
+
+/**
+ * All of the objects in a scene, organized so that a ray only has to be
+ * tested against the handful that it might actually hit. Objects with
+ * bounds go into a BVH; ones without (like planes) are kept in a separate
+ * list that every ray is tested against.
+ */
+pub struct World {
+    objects: Vec<ObjectEnum>,
+
+    // indices into `objects`
+    bounded: Vec<usize>,
+    unbounded: Vec<usize>,
+
+    // built over `bounded`, so its primitive indices are indices into that
+    bvh: Bvh,
+}
+
+impl World {
+
+    pub fn new(objects: Vec<ObjectEnum>) -> Self {
+        let mut bounded = Vec::new();
+        let mut bounds: Vec<BoundingBox> = Vec::new();
+        let mut unbounded = Vec::new();
+
+        for (index, object) in objects.iter().enumerate() {
+            match object.bounds() {
+                Some(object_bounds) => {
+                    // an empty box (like a mesh with no faces) can never be hit
+                    if !object_bounds.is_empty() {
+                        bounded.push(index);
+                        bounds.push(object_bounds);
+                    }
+                },
+                None => unbounded.push(index),
+            }
+        }
+
+        Self {
+            bvh: Bvh::build(&bounds),
+            objects,
+            bounded,
+            unbounded,
+        }
+    }
+
+    pub fn objects(&self) -> &[ObjectEnum] {
+        &self.objects
+    }
+
+    /**
+     * The nearest object hit by the ray, along with where it was hit
+     */
+    pub fn nearest_intersection(&self, ray: &Ray) -> Option<(Intersection,&ObjectEnum)> {
+        let mut nearest = self.bvh
+            .nearest_intersection(ray, |index| self.objects[self.bounded[index]].intersection(ray))
+            .map(|(intersection, index)| (intersection, &self.objects[self.bounded[index]]));
+
+        for &index in &self.unbounded {
+            if let Some(intersection) = self.objects[index].intersection(ray) {
+                if nearest.as_ref().map(|n| intersection.distance < n.0.distance).unwrap_or(true) {
+                    nearest = Some((intersection, &self.objects[index]));
+                }
+            }
+        }
+
+        nearest
+    }
+
+    /**
+     * Same as `nearest_intersection()`, but testing every object in turn.
+     * Used as a reference in tests and benchmarks.
+     */
+    pub fn brute_force_intersection(&self, ray: &Ray) -> Option<(Intersection,&ObjectEnum)> {
+        let mut nearest: Option<(Intersection,&ObjectEnum)> = None;
+
+        for object in &self.objects {
+            if let Some(intersection) = object.intersection(ray) {
+                if nearest.as_ref().map(|n| intersection.distance < n.0.distance).unwrap_or(true) {
+                    nearest = Some((intersection, object));
+                }
+            }
+        }
+
+        nearest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+    use rand::rngs::SmallRng;
+
+    use crate::vec3::Vec3;
+    use crate::ray::Ray;
+    use crate::material::Material;
+    use crate::sphere::Sphere;
+    use crate::plane::Plane;
+    use crate::object::ObjectEnum;
+    use crate::world::World;
+
+    #[test]
+    fn test_bvh_matches_brute_force() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut objs: Vec<ObjectEnum> = Vec::new();
+
+        objs.push(ObjectEnum::Plane(Plane::new(
+            Vec3 { x: 0.0, y: -10.0, z: 0.0 },
+            Vec3 { x: 0.0, y: 1.0, z: 0.0 },
+            Vec3 { x: 0.0, y: 0.0, z: -1.0 },
+            Material::new(),
+        )));
+
+        for _ in 0..500 {
+            objs.push(ObjectEnum::Sphere(Sphere::new(
+                Vec3 {
+                    x: rng.gen_range(-10.0, 10.0),
+                    y: rng.gen_range(-10.0, 10.0),
+                    z: rng.gen_range(-30.0, -10.0),
+                },
+                rng.gen_range(0.1, 1.0),
+                Material::new(),
+            )));
+        }
+
+        let world = World::new(objs);
+
+        for _ in 0..2000 {
+            let ray = Ray::random_direction(Vec3::new(), &mut rng);
+
+            let expected = world.brute_force_intersection(&ray);
+            let actual = world.nearest_intersection(&ray);
+
+            assert_eq!(expected.is_some(), actual.is_some());
+            if let (Some(expected), Some(actual)) = (expected, actual) {
+                assert_eq!(expected.0.distance, actual.0.distance);
+                assert!(std::ptr::eq(expected.1, actual.1));
+            }
+        }
+    }
+}